@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+
+use crate::heroes::{create_sim_hero, SimHero};
+
+/// Defines a Champion that contains info on its stats at each rank and the aura it grants its team
+/// Champions fight alongside the heroes, but do not equip gear or learn skills
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Champion {
+    champion: String,
+    element_type: String,
+    base_eva: f64,
+    base_crit_chance: f64,
+    base_crit_mult: f64,
+    base_threat_rating: u16,
+
+    ranks: Vec<ChampionRank>, // The first entry is rank 1
+    #[serde(default)]
+    provisional_stats: bool, // Set while the rank stats are estimates rather than the game's values
+
+    aura: Vec<AuraModifier>,
+}
+
+/// The stats of a champion at one rank, and the tier of its aura at that rank
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChampionRank {
+    hp: f64,
+    atk: f64,
+    def: f64,
+    innate_tier: u8,
+}
+
+/// Defines the effects a champion's aura can have on the team
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AuraEffect {
+    Attack,        // % bonus to each hero's attack
+    Defense,       // % bonus to each hero's defense
+    HP,            // % bonus to each hero's hp
+    CritChance,    // Flat bonus to each hero's crit chance
+    Evasion,       // Flat bonus to each hero's evasion
+    LootChance,    // Chance of bonus loot from each hit on the encounter
+    LootCap,       // Max bonus loot per fight
+    Heal,          // Hp healed by each hero at the end of each round
+    SelfHeal,      // Hp healed by the champion each round, before the heroes attack
+    DrainHP,       // Share of max hp the champion drains from each other hero above it each round
+    DrainAttack,   // Share of the champion's attack it gains for each hero drained
+    BarrierDamage, // % bonus to the damage each hero's element deals to barriers
+}
+
+/// Defines the archetypes whose count on the team can scale an aura modifier
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AuraArchetype {
+    Fighter,
+    Rogue,
+    Spellcaster,
+    Trickster,
+}
+
+/// One effect of a champion's aura and its value at innate tiers 1-4
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuraModifier {
+    effect: AuraEffect,
+    values: [f64; 4],
+    #[serde(default)]
+    per_archetype: Option<AuraArchetype>, // If set, the value is multiplied by the number of heroes of the archetype on the team
+    #[serde(default = "default_multiplier")]
+    mercenary_multiplier: f64, // Multiplies the value for Mercenaries, as they get more from champion skills
+    #[serde(default = "default_multiplier")]
+    mercenary_stat_multiplier: f64, // Multiplies a Mercenary's whole HP, CritChance or Evasion stat after the bonus is added
+    #[serde(default = "default_multiplier")]
+    boss_multiplier: f64, // Multiplies the value in boss encounters
+    #[serde(default)]
+    rounds: Option<[u8; 4]>, // If set, a CritChance or BarrierDamage bonus only lasts this many rounds at innate tiers 1-4
+}
+
+fn default_multiplier() -> f64 {
+    return 1.0;
+}
+
+impl AuraModifier {
+    pub fn get_effect(&self) -> AuraEffect {
+        return self.effect;
+    }
+
+    pub fn get_per_archetype(&self) -> Option<AuraArchetype> {
+        return self.per_archetype;
+    }
+
+    /// Returns whether the modifier only lasts the first rounds of the fight
+    pub fn is_timed(&self) -> bool {
+        return self.rounds.is_some();
+    }
+
+    /// Returns whether the modifier still applies in the round at the given innate tier
+    pub fn is_active_in_round(&self, innate_tier: u8, round: i16) -> bool {
+        return match self.rounds {
+            Some(rounds) => round <= i16::from(rounds[get_tier_index(innate_tier)]),
+            None => true,
+        };
+    }
+
+    pub fn get_mercenary_stat_multiplier(&self) -> f64 {
+        return self.mercenary_stat_multiplier;
+    }
+
+    /// Returns the modifier's value at the innate tier for one hero, given the number of heroes of the modifier's archetype on the team
+    pub fn get_value(
+        &self,
+        innate_tier: u8,
+        archetype_qty: u8,
+        is_boss: bool,
+        is_mercenary: bool,
+    ) -> f64 {
+        let mut value = self.values[get_tier_index(innate_tier)];
+        if self.per_archetype.is_some() {
+            value *= f64::from(archetype_qty);
+        }
+        if is_boss {
+            value *= self.boss_multiplier;
+        }
+        if is_mercenary {
+            value *= self.mercenary_multiplier;
+        }
+        return value;
+    }
+}
+
+fn get_tier_index(innate_tier: u8) -> usize {
+    return usize::from(innate_tier.clamp(1, 4) - 1);
+}
+
+impl Champion {
+    /// Returns whether the champion's rank stats are estimates rather than the game's values
+    pub fn has_provisional_stats(&self) -> bool {
+        return self.provisional_stats;
+    }
+
+    pub fn get_max_rank(&self) -> u8 {
+        return self.ranks.len() as u8;
+    }
+
+    /// Check that every timed aura modifier is a CritChance or BarrierDamage bonus, the only effects applied round by round,
+    /// and that only untimed HP, CritChance and Evasion modifiers multiply a Mercenary's whole stat
+    pub fn validate_aura(&self) -> Result<(), String> {
+        for modifier in &self.aura {
+            if modifier.mercenary_stat_multiplier != 1.0
+                && (modifier.is_timed()
+                    || !matches!(
+                        modifier.effect,
+                        AuraEffect::HP | AuraEffect::CritChance | AuraEffect::Evasion
+                    ))
+            {
+                return Err(f!(
+                    "{}'s {:?} aura modifier has a mercenary_stat_multiplier, only untimed HP, CritChance and Evasion modifiers can multiply a Mercenary's whole stat",
+                    self.champion,
+                    modifier.effect
+                ));
+            }
+            if modifier.is_timed()
+                && modifier.effect != AuraEffect::CritChance
+                && modifier.effect != AuraEffect::BarrierDamage
+            {
+                return Err(f!(
+                    "{}'s {:?} aura modifier has rounds, only CritChance and BarrierDamage modifiers can be limited to the first rounds",
+                    self.champion,
+                    modifier.effect
+                ));
+            }
+        }
+        return Ok(());
+    }
+
+    /// Create a hero with the champion's stats and aura at the given rank
+    pub fn create_sim_hero(
+        &self,
+        identifier: String,
+        level: u8,
+        rank: u8,
+    ) -> Result<SimHero, String> {
+        if rank < 1 || rank > self.get_max_rank() {
+            return Err(f!(
+                "{} has no rank {}, ranks run from 1 to {}",
+                self.champion,
+                rank,
+                self.get_max_rank()
+            ));
+        }
+        let champion_rank = &self.ranks[usize::from(rank - 1)];
+
+        let mut sim_hero = create_sim_hero(
+            identifier,
+            self.champion.to_string(),
+            level,
+            rank,
+            champion_rank.innate_tier,
+            champion_rank.hp,
+            0.0,
+            champion_rank.atk,
+            champion_rank.def,
+            self.base_threat_rating,
+            self.base_crit_chance,
+            self.base_crit_mult,
+            self.base_eva,
+            0.0,
+            0,
+            self.element_type.to_string(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            0.0,
+            0.0,
+        )?;
+        sim_hero.set_aura(self.aura.clone());
+        return Ok(sim_hero);
+    }
+}
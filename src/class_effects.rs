@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::hero_builder::HeroClass;
+
+/// Defines the special effects of hero classes that the simulation models
+/// Each is applied by the matching hooks on SimHero (on_fight_start, on_round_start, on_evade, on_hit_taken, on_crit, on_fatal_blow, ...)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ClassEffect {
+    ChampionAuraBoost, // Gets the aura modifiers' mercenary_multiplier and mercenary_stat_multiplier
+    SurviveFatalBlow,  // Survives the first fatal blow of the fight
+    ProtectAlly,       // Takes the first fatal blow of the fight for another hero
+    OpeningEvade,      // Evades every attack until the first round's attacks
+    OpeningCrit,       // Crits on the first round, ignoring the barrier
+    BerserkerStages,   // Gains attack and evasion as hp drops
+    RaisedEvasionCap,  // Has a higher evasion cap
+    NinjaBonus,        // Gains crit chance and evasion until hurt
+    SenseiBonus,       // Gains crit chance and evasion, lost for 2 rounds after being hit
+    CritAfterEvade,    // Crits on its next attack after evading
+    ConsecutiveCrits,  // Gains crit damage with each consecutive crit
+    ThreatToAttack,    // Gains 40% of its threat rating as % attack
+    AttackPerElement,  // Gains 1% attack per point of its element
+    AnyElement,        // Damages barriers of any element, but at 30% power
+    ChampionLootBoost, // Counts toward the champion aura modifiers scaled per Trickster, e.g. Polonia's loot bonuses
+}
+
+/// The classes with special effects and their effects, the one place class mechanics are assigned
+const CLASS_EFFECTS: [(&str, &[ClassEffect]); 20] = [
+    ("Mercenary", &[ClassEffect::ChampionAuraBoost]),
+    ("Trickster", &[ClassEffect::ChampionLootBoost]),
+    ("Chieftain", &[ClassEffect::ThreatToAttack]),
+    ("Lord", &[ClassEffect::ProtectAlly]),
+    ("Samurai", &[ClassEffect::OpeningCrit]),
+    (
+        "Daimyo",
+        &[ClassEffect::OpeningEvade, ClassEffect::OpeningCrit],
+    ),
+    ("Berserker", &[ClassEffect::BerserkerStages]),
+    ("Jarl", &[ClassEffect::BerserkerStages]),
+    ("Conquistador", &[ClassEffect::ConsecutiveCrits]),
+    ("Pathfinder", &[ClassEffect::RaisedEvasionCap]),
+    ("Ninja", &[ClassEffect::NinjaBonus]),
+    ("Sensei", &[ClassEffect::SenseiBonus]),
+    ("Dancer", &[ClassEffect::CritAfterEvade]),
+    ("Acrobat", &[ClassEffect::CritAfterEvade]),
+    ("Cleric", &[ClassEffect::SurviveFatalBlow]),
+    ("Bishop", &[ClassEffect::SurviveFatalBlow]),
+    ("Spellblade", &[ClassEffect::AnyElement]),
+    ("Spellknight", &[ClassEffect::AnyElement]),
+    ("Geomancer", &[ClassEffect::AttackPerElement]),
+    ("Astramancer", &[ClassEffect::AttackPerElement]),
+];
+
+/// Returns the special effects of the class, empty if it has none
+pub fn get_class_effects(class: &str) -> Vec<ClassEffect> {
+    return CLASS_EFFECTS
+        .iter()
+        .filter(|(class_name, _)| *class_name == class)
+        .flat_map(|(_, effects)| effects.iter().copied())
+        .collect();
+}
+
+/// Check that every class given special effects is in hero_classes.yaml, so a misspelled class cannot silently lose its effects
+pub fn validate_class_effects(hero_classes: &HashMap<String, HeroClass>) -> Result<(), String> {
+    for (class_name, _) in CLASS_EFFECTS.iter() {
+        if !hero_classes.contains_key(*class_name) {
+            return Err(f!(
+                "{} has class effects but is not a class in hero_classes.yaml",
+                class_name
+            ));
+        }
+    }
+    return Ok(());
+}
@@ -0,0 +1,138 @@
+use std::time::Instant;
+
+use log::{error, info};
+
+use crate::{
+    dockets::{commence_from_sim_data, Docket},
+    init,
+    simdata::SimData,
+};
+
+const USAGE: &str =
+    "Usage: adept run [--docket <path>] [--data <directory>] [--study <identifier>]";
+
+/// Defines the arguments accepted by the run command
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunArguments {
+    docket_path: Option<String>, // Overrides the default docket path of <data_directory>/config/study_docket.tsv
+    data_directory: String, // The directory containing the bundle, config, logs and output folders
+    study: Option<String>,  // When set, only the study with this identifier is run
+}
+
+/// Parse the arguments following the run command
+pub fn parse_run_arguments(args: &[String]) -> Result<RunArguments, String> {
+    let mut run_arguments = RunArguments {
+        docket_path: None,
+        data_directory: String::from("adept_data"),
+        study: None,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = args.get(i + 1);
+        match (flag, value) {
+            ("--docket", Some(v)) => run_arguments.docket_path = Some(v.to_string()),
+            ("--data", Some(v)) => {
+                run_arguments.data_directory = v.trim_end_matches('/').to_string()
+            }
+            ("--study", Some(v)) => run_arguments.study = Some(v.to_string()),
+            ("--docket", None) | ("--data", None) | ("--study", None) => {
+                return Err(f!("Missing value for {}", flag));
+            }
+            _ => return Err(f!("Unrecognized argument {}", flag)),
+        }
+        i += 2;
+    }
+
+    return Ok(run_arguments);
+}
+
+/// Run the command line interface, returning the exit code for the process
+/// 0 - Success, 1 - A file failed to load or a study failed, 2 - Invalid arguments
+pub fn run_from_command_line(args: Vec<String>) -> i32 {
+    match args.get(1).map(|s| s.as_str()) {
+        Some("run") => (),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            return 0;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    }
+
+    let run_arguments: RunArguments;
+    match parse_run_arguments(&args[2..]) {
+        Ok(parsed) => run_arguments = parsed,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return 2;
+        }
+    }
+
+    return run_headless(run_arguments);
+}
+
+/// Load the required files and commence the docket without starting the gui
+fn run_headless(run_arguments: RunArguments) -> i32 {
+    init::create_logfile(&run_arguments.data_directory);
+
+    let mut required_files = init::get_required_files(&run_arguments.data_directory);
+    if let Some(docket_path) = &run_arguments.docket_path {
+        required_files
+            .entry(String::from("Study Docket"))
+            .and_modify(|e| *e = (docket_path.to_string(), false));
+    }
+
+    let mut sim_data: SimData = Default::default();
+    let mut docket: Docket = Default::default();
+    init::load_required_files(
+        &run_arguments.data_directory,
+        &mut required_files,
+        &mut sim_data,
+        &mut docket,
+    );
+
+    let mut all_loaded = true;
+    for (file_id, (file_location, file_loaded)) in required_files.iter() {
+        if !file_loaded {
+            error!("Failed to load {} from {}", file_id, file_location);
+            eprintln!("Failed to load {} from {}", file_id, file_location);
+            all_loaded = false;
+        }
+    }
+    if !all_loaded {
+        log::logger().flush();
+        return 1;
+    }
+
+    if let Some(study) = &run_arguments.study {
+        if !docket.get_study_names().contains(study) {
+            error!("Study {} was not found in the docket", study);
+            eprintln!("Study {} was not found in the docket", study);
+            log::logger().flush();
+            return 1;
+        }
+    }
+    docket.set_study_filter(run_arguments.study.clone());
+
+    // Progress is shown by indicatif, the gui progress messages are drained and discarded
+    let (tx, rx) = std::sync::mpsc::channel::<(String, u64, u64, Instant)>();
+    let drain = std::thread::spawn(move || for _ in rx {});
+
+    let failed_studies = commence_from_sim_data(&mut docket, &mut sim_data, tx);
+    drain.join().unwrap();
+
+    if failed_studies.len() > 0 {
+        error!("Failed Studies: {:?}", failed_studies);
+        eprintln!("Failed Studies: {:?}", failed_studies);
+        log::logger().flush();
+        return 1;
+    }
+
+    info!("All Studies Succeeded");
+    log::logger().flush();
+    return 0;
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+/// A typed event that occurred during a simulation, recorded for replay and analysis of the combat
+/// Hero events are identified by the hero identifier, the mob is implied where there is no hero
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum CombatEvent {
+    SimulationStart {
+        heroes: Vec<String>,
+        heroes_hp: Vec<f64>,
+        encounter_hp: f64,
+        barrier_hp: f64,
+    },
+    RoundStart {
+        encounter_hp: f64,
+        heroes_hp: Vec<f64>,
+    },
+    TargetChances {
+        chances: Vec<f64>,
+    },
+    NinjaBonus {
+        hero: String,
+        ninja_bonus: f64,
+        ninja_evasion: f64,
+    },
+    NinjaBonusLost {
+        hero: String,
+    },
+    ExtremeCritBonus {
+        hero: String,
+        extreme_crit_bonus: f64,
+    },
+    AoE, // The mob attacks every hero
+    MobAttack {
+        target: String,
+    },
+    Evade {
+        hero: String,
+    },
+    GuaranteedCrit {
+        hero: String,
+    },
+    HeroDamaged {
+        hero: String,
+        damage: f64,
+        hp: f64,
+        crit: bool,
+    },
+    InnateLost {
+        hero: String,
+    },
+    FatalBlowSurvived {
+        hero: String,
+    },
+    LordSave {
+        hero: String,
+        lord: String,
+        hero_hp: f64,
+        lord_hp: f64,
+    },
+    Death {
+        hero: String,
+    },
+    ChampionDrain {
+        hero: String,
+        hp: f64,
+        drain_bonus: f64,
+    },
+    BerserkerStage {
+        hero: String,
+        stage: u8,
+    },
+    Attack {
+        hero: String,
+        damage: f64,
+        encounter_hp: f64,
+        crit: bool,
+        pierced_barrier: bool,
+    },
+    Miss {
+        hero: String,
+    },
+    ConsecutiveCritBonus {
+        hero: String,
+        bonus: f64,
+    },
+    BarrierDamaged {
+        hero: String,
+        damage: f64,
+        barrier_hp: f64,
+    },
+    BarrierBroken {
+        hero: String,
+    },
+    BarrierModifier {
+        modifier: f64,
+    },
+    SharkActivated,
+    ChampionLoot {
+        loot: u8,
+        loot_cap: u8,
+    },
+    ChampionRoundBonus {
+        crit_chance: f64,
+        barrier_damage: f64,
+    },
+    ClassAttackBonus {
+        hero: String,
+        bonus: f64,
+    },
+    Heal {
+        hero: String,
+        source: HealSource,
+        amount: f64,
+        hp: f64,
+    },
+    SimulationEnd {
+        success: bool,
+    },
+}
+
+/// The source of a Heal event
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum HealSource {
+    Regeneration,
+    Champion,
+}
+
+/// A combat event along with the round it occurred in (0 before the first round)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CombatLogEntry {
+    round: i16,
+    #[serde(flatten)]
+    event: CombatEvent,
+}
+
+/// An optional sink for combat events
+/// When disabled events are never constructed, so recording costs a single branch
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CombatLog {
+    round: i16,
+    entries: Option<Vec<CombatLogEntry>>,
+}
+
+pub fn create_combat_log(enabled: bool) -> CombatLog {
+    return CombatLog {
+        round: 0,
+        entries: if enabled { Some(vec![]) } else { None },
+    };
+}
+
+impl CombatLog {
+    pub fn is_enabled(&self) -> bool {
+        return self.entries.is_some();
+    }
+
+    /// Set the round that subsequently recorded events occurred in
+    pub fn set_round(&mut self, round: i16) {
+        self.round = round;
+    }
+
+    /// Record the event returned by create_event, which is only called if the log is enabled
+    #[inline]
+    pub fn record<F: FnOnce() -> CombatEvent>(&mut self, create_event: F) {
+        if let Some(entries) = &mut self.entries {
+            entries.push(CombatLogEntry {
+                round: self.round,
+                event: create_event(),
+            });
+        }
+    }
+
+    pub fn get_entries(&self) -> &[CombatLogEntry] {
+        match &self.entries {
+            Some(entries) => return entries,
+            None => return &[],
+        }
+    }
+
+    /// Serialize the log as JSON Lines, one entry per line
+    pub fn to_json_lines(&self) -> Result<String, serde_json::Error> {
+        let mut res = String::new();
+        for entry in self.get_entries() {
+            res.push_str(&serde_json::to_string(entry)?);
+            res.push('\n');
+        }
+        return Ok(res);
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
@@ -11,14 +12,28 @@ use rayon::prelude::*;
 
 use crate::deserialize::bool_from_str;
 use crate::dungeons::create_trial_dungeon;
-use crate::dungeons::{Dungeon, TrialDungeon};
-use crate::equipment::BoosterType;
+use crate::dungeons::{BarrierSetting, Dungeon, TrialDungeon};
+use crate::equipment::{BoosterType, ElementType};
 use crate::hero_builder::Hero;
 use crate::heroes::{create_team, Team};
 use crate::inputs::save_study_docket;
+use crate::scoring::{create_default_scoring_weights, ReportSettings, SCORING_FIELDS};
 use crate::simdata::SimData;
 use crate::studies::{HeroBuilderInformation, Runnable};
-use crate::{heroes::SimHero, studies::static_duo_skill_study::create_static_duo_skill_study};
+use crate::trials::AdaptiveSampling;
+use crate::{
+    heroes::SimHero,
+    studies::gear_optimization_study::{
+        create_gear_optimization_study, GearConstraints, GEAR_QUALITIES, MAX_GEAR_SETS,
+        SPIRIT_TIERS,
+    },
+    studies::single_hero_skill_study::create_single_hero_skill_study,
+    studies::static_duo_skill_study::create_static_duo_skill_study,
+    studies::team_composition_study::create_team_composition_study,
+    studies::team_skill_study::{
+        create_team_skill_study, create_varied_hero, validate_joint_skill_variations, VariedHero,
+    },
+};
 /// Holds info for generating a study, defines format for deserialization from CSV
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct DocketStudy {
@@ -45,6 +60,30 @@ pub struct DocketStudy {
         deserialize = "Team Hero Identifiers"
     ))]
     team_hero_identifiers: String,
+    #[serde(rename(serialize = "Varied Heroes", deserialize = "Varied Heroes"))]
+    #[serde(default)]
+    varied_heroes: String, // The team heroes whose skills a TeamSkillStudy varies, empty to vary every hero
+    #[serde(rename(serialize = "Team Size", deserialize = "Team Size"))]
+    #[serde(default)]
+    team_size: usize, // The number of heroes on each team of a TeamCompositionStudy, 0 to fill the smallest dungeon
+    #[serde(rename(serialize = "Gear Max Tier", deserialize = "Gear Max Tier"))]
+    #[serde(default)]
+    gear_max_tier: u8, // The highest blueprint tier a GearOptimizationStudy may equip, 0 for no limit
+    #[serde(rename(serialize = "Gear Qualities", deserialize = "Gear Qualities"))]
+    #[serde(default)]
+    gear_qualities: String, // The qualities a GearOptimizationStudy may equip, empty to keep the hero's quality in each slot
+    #[serde(rename(serialize = "Gear Elements", deserialize = "Gear Elements"))]
+    #[serde(default)]
+    gear_elements: String, // The 6 elements a GearOptimizationStudy sockets into the gear, empty to use the hero's
+    #[serde(rename(serialize = "Gear Spirits", deserialize = "Gear Spirits"))]
+    #[serde(default)]
+    gear_spirits: String, // The 6 spirits a GearOptimizationStudy sockets into the gear, empty to use the hero's
+    #[serde(rename(
+        serialize = "Gear Candidates Per Slot",
+        deserialize = "Gear Candidates Per Slot"
+    ))]
+    #[serde(default = "default_gear_candidates_per_slot")]
+    gear_candidates_per_slot: usize, // The number of items per slot a GearOptimizationStudy varies upon after prefiltering
     #[serde(rename(serialize = "Team Booster", deserialize = "Team Booster"))]
     team_booster: String,
     #[serde(rename(
@@ -63,8 +102,69 @@ pub struct DocketStudy {
     ))]
     #[serde(deserialize_with = "bool_from_str")]
     automatic_rank_difficulty_optimization: bool,
+    #[serde(rename(
+        serialize = "Rank Difficulty Ceiling",
+        deserialize = "Rank Difficulty Ceiling"
+    ))]
+    #[serde(default = "default_rank_difficulty_ceiling")]
+    rank_difficulty_ceiling: f64,
     #[serde(rename(serialize = "Excluded Skills", deserialize = "Excluded Skills"))]
     excluded_skills: String,
+    #[serde(rename(
+        serialize = "Prune Unequippable Skills",
+        deserialize = "Prune Unequippable Skills"
+    ))]
+    #[serde(deserialize_with = "bool_from_str")]
+    #[serde(default)]
+    prune_unequippable_skills: bool, // Whether to exclude item-conditional skills that none of the hero's equipment or alternates can satisfy
+    #[serde(rename(serialize = "Ordered Skill Slots", deserialize = "Ordered Skill Slots"))]
+    #[serde(deserialize_with = "bool_from_str")]
+    #[serde(default)]
+    ordered_skill_slots: bool, // Whether a StaticDuoSkillStudy places each skillset into ordered slots and ranks by slot weighted score
+    #[serde(rename(serialize = "Slot Weights", deserialize = "Slot Weights"))]
+    #[serde(default)]
+    slot_weights: String, // The 4 semi-colon separated skill slot weights used when slots are ordered, empty to weight every slot 1
+    #[serde(rename(serialize = "Seed", deserialize = "Seed"))]
+    #[serde(default)]
+    seed: u64,
+    #[serde(rename(
+        serialize = "Adaptive Interval Width",
+        deserialize = "Adaptive Interval Width"
+    ))]
+    #[serde(default)]
+    adaptive_interval_width: f64, // The target width (as a percent) of the success rate interval, 0 disables adaptive sampling
+    #[serde(rename(serialize = "Adaptive Batch Size", deserialize = "Adaptive Batch Size"))]
+    #[serde(default = "default_adaptive_batch_size")]
+    adaptive_batch_size: usize,
+    #[serde(rename(serialize = "Combat Log Cap", deserialize = "Combat Log Cap"))]
+    #[serde(default = "default_combat_log_cap")]
+    combat_log_cap: usize, // The maximum number of failed simulations per trial to save combat logs for
+    #[serde(rename(serialize = "Scoring Weights", deserialize = "Scoring Weights"))]
+    #[serde(default)]
+    scoring_weights: String, // The semi-colon separated Field:Weight pairs of the composite score, empty to score by success rate alone
+    #[serde(rename(serialize = "Summary Top N", deserialize = "Summary Top N"))]
+    #[serde(default = "default_summary_top_n")]
+    summary_top_n: usize, // The number of builds listed in the markdown summary of the study
+}
+
+fn default_rank_difficulty_ceiling() -> f64 {
+    return 95.0;
+}
+
+fn default_adaptive_batch_size() -> usize {
+    return 1000;
+}
+
+fn default_combat_log_cap() -> usize {
+    return 0;
+}
+
+fn default_gear_candidates_per_slot() -> usize {
+    return 3;
+}
+
+fn default_summary_top_n() -> usize {
+    return 10;
 }
 
 impl DocketStudy {
@@ -87,6 +187,27 @@ impl DocketStudy {
             );
             return false;
         }
+        if self.rank_difficulty_ceiling < 0.0 || self.rank_difficulty_ceiling > 100.0 {
+            info!(
+                "\tSkipping Record {}: Rank Difficulty Ceiling Must Be In Range [0,100]",
+                result_index
+            );
+            return false;
+        }
+        if self.adaptive_interval_width < 0.0 || self.adaptive_interval_width > 100.0 {
+            info!(
+                "\tSkipping Record {}: Adaptive Interval Width Must Be In Range [0,100]",
+                result_index
+            );
+            return false;
+        }
+        if self.adaptive_interval_width > 0.0 && self.adaptive_batch_size == 0 {
+            info!(
+                "\tSkipping Record {}: Adaptive Batch Size Must Be At Least 1",
+                result_index
+            );
+            return false;
+        }
         if self.team_hero_identifiers.len() == 0 {
             info!(
                 "\tSkipping Record {}: Team Hero Identifiers Are Required",
@@ -113,6 +234,34 @@ impl DocketStudy {
 
         return true;
     }
+
+    /// Returns a hash of the study's docket row, ignoring the Completed column (FNV-1a over the row serialized as yaml)
+    /// The hash is stable across runs, so a checkpoint can tell whether the row has changed since it was saved
+    pub fn get_row_hash(&self) -> u64 {
+        let row = DocketStudy {
+            completed: false,
+            ..self.clone()
+        };
+        let serialized_row = serde_yaml::to_string(&row).unwrap();
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in serialized_row.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        return hash;
+    }
+
+    /// Returns the adaptive sampling settings for the study, or None if adaptive sampling is disabled
+    pub fn get_adaptive_sampling(&self) -> Option<AdaptiveSampling> {
+        if self.adaptive_interval_width <= 0.0 {
+            return None;
+        }
+        return Some(AdaptiveSampling {
+            batch_size: self.adaptive_batch_size,
+            target_interval_width: self.adaptive_interval_width / 100.0,
+            elimination_threshold: None,
+        });
+    }
 }
 
 /// Defines available study types for DocketStudy
@@ -132,6 +281,14 @@ pub enum DocketStudyType {
     #[strum(serialize = "StaticDuoSkillStudy")]
     #[default]
     StaticDuoSkillStudy,
+    #[strum(serialize = "SingleHeroSkillStudy")]
+    SingleHeroSkillStudy,
+    #[strum(serialize = "TeamSkillStudy")]
+    TeamSkillStudy,
+    #[strum(serialize = "TeamCompositionStudy")]
+    TeamCompositionStudy,
+    #[strum(serialize = "GearOptimizationStudy")]
+    GearOptimizationStudy,
 }
 
 /// Defines valid skill name types for DocketStudy
@@ -160,11 +317,12 @@ pub enum DocketStudySkillNameFormat {
     FullAnyTier,
 }
 
-pub fn commence_from_gui(
+/// Commence the docket using the loaded sim_data, returning the identifiers of any studies that failed
+pub fn commence_from_sim_data(
     docket: &mut Docket,
     sim_data: &mut SimData,
-    tx: Sender<(String, u32, u32, Instant)>,
-) {
+    tx: Sender<(String, u64, u64, Instant)>,
+) -> Vec<String> {
     let loaded_hero_builder_information = HeroBuilderInformation {
         bp_map: sim_data.bp_map.clone(),
         hero_classes: sim_data.hero_classes.clone(),
@@ -181,7 +339,7 @@ pub fn commence_from_gui(
             .clone(),
         innate_skill_map: sim_data.innate_skill_map.clone(),
     };
-    docket.commence(
+    return docket.commence(
         sim_data.loaded_heroes.clone(),
         sim_data.loaded_dungeons.clone(),
         sim_data.loaded_valid_skills.clone(),
@@ -196,12 +354,30 @@ pub fn commence_from_gui(
 pub struct Docket {
     studies: Vec<DocketStudy>,
     path: String,
+    output_directory: String, // Study output is written to output_directory/simulations/<study identifier>
+    study_filter: Option<String>, // When set, only the study with this identifier is run
 }
 
 impl Docket {
     pub fn set_path(&mut self, path: String) {
         self.path = path;
     }
+
+    pub fn set_output_directory(&mut self, output_directory: String) {
+        self.output_directory = output_directory;
+    }
+
+    pub fn get_output_directory(&self) -> String {
+        if self.output_directory.len() == 0 {
+            return String::from("adept_data/output");
+        }
+        return self.output_directory.to_string();
+    }
+
+    pub fn set_study_filter(&mut self, study_filter: Option<String>) {
+        self.study_filter = study_filter;
+    }
+
     pub fn add_study(&mut self, study: DocketStudy) {
         self.studies.push(study);
     }
@@ -218,10 +394,19 @@ impl Docket {
         return res;
     }
 
+    fn mark_study_completed(&mut self, identifier: &String) {
+        for study in self.studies.iter_mut() {
+            if study.identifier == *identifier {
+                study.completed = true;
+            }
+        }
+    }
+
     pub fn get_num_studies(&self) -> usize {
         return self.studies.len();
     }
 
+    /// Run each incomplete study in the docket (or only the filtered study), returning the identifiers of any studies that failed
     pub fn commence(
         &mut self,
         loaded_heroes: HashMap<String, SimHero>,
@@ -229,8 +414,8 @@ impl Docket {
         loaded_valid_skills: Vec<String>,
         loaded_heroes_from_builder: HashMap<String, Hero>,
         loaded_hero_builder_information: HeroBuilderInformation,
-        tx: Sender<(String, u32, u32, Instant)>,
-    ) {
+        tx: Sender<(String, u64, u64, Instant)>,
+    ) -> Vec<String> {
         info!("Commencing Docket");
         let num_dockets: usize = self.get_num_studies();
         let m = MultiProgress::new();
@@ -247,15 +432,21 @@ impl Docket {
 
         tx.send((
             String::from("DOCKET OVERALL PROGRESS"),
-            0u32,
-            num_dockets as u32,
+            0u64,
+            num_dockets as u64,
             docket_start_instant,
         ))
         .unwrap();
 
-        let completed_study_count: Arc<Mutex<u32>> = Arc::new(Mutex::new(0u32));
+        let completed_study_count: Arc<Mutex<u64>> = Arc::new(Mutex::new(0u64));
+        let failed_studies: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+        let study_filter = self.study_filter.clone();
+        let output_directory = self.get_output_directory();
+        // Completed studies are recorded in a copy of the docket that is saved as each study completes,
+        // so an interrupted docket does not re-run studies that already finished
+        let docket_progress: Mutex<Docket> = Mutex::new(self.clone());
 
-        let mut studies_with_tx: Vec<(&mut DocketStudy, Sender<(String, u32, u32, Instant)>)> =
+        let mut studies_with_tx: Vec<(&mut DocketStudy, Sender<(String, u64, u64, Instant)>)> =
             self.studies
                 .iter_mut()
                 .map(|study| (study, tx.clone()))
@@ -279,28 +470,63 @@ impl Docket {
                 *completed_study_count.lock().unwrap() += 1;
                 tx.send((
                     String::from("DOCKET OVERALL PROGRESS"),
-                    *completed_study_count.lock().unwrap(), num_dockets as u32, docket_start_instant
+                    *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
                 )).unwrap();
                 warn!("Skipping study {} since it is already completed.", docket_study.identifier);
                 return;
             }
 
-            // Parse Team
+            // Skip studies excluded by the filter
+            if let Some(filter) = &study_filter {
+                if *filter != docket_study.identifier {
+                    *completed_study_count.lock().unwrap() += 1;
+                    tx.send((
+                        String::from("DOCKET OVERALL PROGRESS"),
+                        *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                    )).unwrap();
+                    info!("Skipping study {} since it does not match the study filter.", docket_study.identifier);
+                    return;
+                }
+            }
+
+            // Parse Team Heroes and Booster
             let parse_team_option = parse_team(&docket_study, &loaded_heroes);
-            let team: Team;
+            let team_heroes: Vec<SimHero>;
+            let team_booster: Option<BoosterType>;
             match parse_team_option {
-                Some(parsed_team) => team = parsed_team,
+                Some((parsed_heroes, parsed_booster)) => {
+                    team_heroes = parsed_heroes;
+                    team_booster = parsed_booster;
+                }
                 None => {
                     *completed_study_count.lock().unwrap() += 1;
                     tx.send((
                         String::from("DOCKET OVERALL PROGRESS"),
-                        *completed_study_count.lock().unwrap(), num_dockets as u32, docket_start_instant
+                        *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
                     )).unwrap();
-                    warn!("\tFailed to Parse Team: Skipping to Next Study");
+                    error!("\tFailed to Parse Team: Skipping to Next Study");
+                    failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
                     return;
                 }
             }
-            let team_heroes = team.get_heroes();
+
+            // Form the Team, a TeamCompositionStudy forms a team from the heroes for each composition instead
+            let mut team: Option<Team> = None;
+            if docket_study.type_ != DocketStudyType::TeamCompositionStudy {
+                match create_team(team_heroes.clone(), team_booster.clone()) {
+                    Ok(created_team) => team = Some(created_team),
+                    Err(e) => {
+                        *completed_study_count.lock().unwrap() += 1;
+                        tx.send((
+                            String::from("DOCKET OVERALL PROGRESS"),
+                            *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                        )).unwrap();
+                        error!("\tFailed to Form Team ({}): Skipping to Next Study", e);
+                        failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                        return;
+                    }
+                }
+            }
             // info!("\tParsed Team");
 
             // Parse Dungeons
@@ -312,122 +538,365 @@ impl Docket {
                     *completed_study_count.lock().unwrap() += 1;
                     tx.send((
                         String::from("DOCKET OVERALL PROGRESS"),
-                        *completed_study_count.lock().unwrap(), num_dockets as u32, docket_start_instant
+                        *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
                     )).unwrap();
-                    warn!("\tFailed to Parse Dungeons: Skipping to Next Study");
+                    error!("\tFailed to Parse Dungeons: Skipping to Next Study");
+                    failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
                     return;
                 }
             }
             // info!("\tParsed Dungeons");
 
-            // Parse Excluded/Valid Skills
-            let parse_valid_skills_option = parse_valid_skills(
-                &docket_study,
-                &loaded_valid_skills,
-                &loaded_hero_builder_information,
-                &team_heroes,
-            );
-            let valid_skills: Vec<String>;
-            match parse_valid_skills_option {
-                Some(parsed_vs) => valid_skills = parsed_vs,
+            // Parse Report Settings
+            let report_settings: ReportSettings;
+            match parse_report_settings(&docket_study) {
+                Some(parsed_report_settings) => report_settings = parsed_report_settings,
                 None => {
                     *completed_study_count.lock().unwrap() += 1;
                     tx.send((
                         String::from("DOCKET OVERALL PROGRESS"),
-                        *completed_study_count.lock().unwrap(), num_dockets as u32, docket_start_instant
+                        *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
                     )).unwrap();
-                    warn!("\tFailed to Parse Excluded Skills (Did not conform to expected format): Skipping to Next Study");
+                    error!("\tFailed to Parse Scoring Weights: Skipping to Next Study");
+                    failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
                     return;
                 }
             }
-            // info!("\tParsed Valid/Excluded Skills");
-
-            // Parse Static/Preset Skills
-            let mut preset_skills: Vec<String> = Default::default();
-            for skill in docket_study
-                .preset_skills
-                .split(";")
-                .map(|s| s.trim())
-                .collect::<Vec<&str>>()
-            {
-                preset_skills.push(skill.to_string());
+
+            // Parse Varied Heroes
+            let parse_varied_heroes_option = parse_varied_heroes(&docket_study, &team_heroes);
+            let varied_heroes: Vec<SimHero>;
+            match parse_varied_heroes_option {
+                Some(parsed_varied_heroes) => varied_heroes = parsed_varied_heroes,
+                None => {
+                    *completed_study_count.lock().unwrap() += 1;
+                    tx.send((
+                        String::from("DOCKET OVERALL PROGRESS"),
+                        *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                    )).unwrap();
+                    error!("\tFailed to Parse Varied Heroes (Each must be a hero on the team other than the champion): Skipping to Next Study");
+                    failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                    return;
+                }
             }
-            let parse_static_skills_option = translate_skillset_based_on_skill_name_format(
-                &docket_study.skill_name_format,
-                preset_skills,
-                &loaded_hero_builder_information,
-            );
-            let static_skills: Vec<String>;
-            match parse_static_skills_option {
-                Some(parsed_static_skills) => static_skills = parsed_static_skills,
+            // info!("\tParsed Varied Heroes");
+
+            // Split Static/Preset Skills into one group per varied hero
+            let parse_preset_skill_groups_option =
+                parse_preset_skill_groups(&docket_study, varied_heroes.len());
+            let preset_skill_groups: Vec<String>;
+            match parse_preset_skill_groups_option {
+                Some(parsed_groups) => preset_skill_groups = parsed_groups,
                 None => {
                     *completed_study_count.lock().unwrap() += 1;
                     tx.send((
                         String::from("DOCKET OVERALL PROGRESS"),
-                        *completed_study_count.lock().unwrap(), num_dockets as u32, docket_start_instant
+                        *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
                     )).unwrap();
-                    warn!("\tFailed to Parse Static/Preset Skills (Did not conform to expected format): Skipping to Next Study");
+                    error!("\tFailed to Parse Static/Preset Skills (Expected one group per varied hero): Skipping to Next Study");
+                    failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
                     return;
                 }
             }
-            // info!("\tParsed Preset Skills");
+
+            // Parse Excluded/Valid Skills and Static/Preset Skills for each varied hero
+            let mut valid_skills_per_hero: Vec<Vec<String>> = vec![];
+            let mut static_skills_per_hero: Vec<Vec<String>> = vec![];
+            for (hero, preset_skill_group) in varied_heroes.iter().zip(preset_skill_groups.iter()) {
+                let parse_valid_skills_option = parse_valid_skills(
+                    &docket_study,
+                    preset_skill_group,
+                    &loaded_valid_skills,
+                    &loaded_hero_builder_information,
+                    hero,
+                    &loaded_heroes_from_builder[&hero.get_identifier()],
+                );
+                match parse_valid_skills_option {
+                    Some(parsed_vs) => valid_skills_per_hero.push(parsed_vs),
+                    None => {
+                        *completed_study_count.lock().unwrap() += 1;
+                        tx.send((
+                            String::from("DOCKET OVERALL PROGRESS"),
+                            *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                        )).unwrap();
+                        error!("\tFailed to Parse Excluded Skills (Did not conform to expected format): Skipping to Next Study");
+                        failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                        return;
+                    }
+                }
+
+                let preset_skills: Vec<String> = preset_skill_group
+                    .split(";")
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| s.len() > 0)
+                    .collect();
+                let parse_static_skills_option = translate_skillset_based_on_skill_name_format(
+                    &docket_study.skill_name_format,
+                    preset_skills,
+                    &loaded_hero_builder_information,
+                );
+                match parse_static_skills_option {
+                    Some(parsed_static_skills) => static_skills_per_hero.push(parsed_static_skills),
+                    None => {
+                        *completed_study_count.lock().unwrap() += 1;
+                        tx.send((
+                            String::from("DOCKET OVERALL PROGRESS"),
+                            *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                        )).unwrap();
+                        error!("\tFailed to Parse Static/Preset Skills (Did not conform to expected format): Skipping to Next Study");
+                        failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                        return;
+                    }
+                }
+            }
+            // info!("\tParsed Valid/Excluded Skills and Preset Skills");
 
             // Determine correct create function based on study type
-            match docket_study.type_ {
+            let mut study: Box<dyn Runnable> = match docket_study.type_ {
                 DocketStudyType::StaticDuoSkillStudy => {
-                    let mut study = create_static_duo_skill_study(
+                    let slot_weights = match parse_slot_weights(&docket_study) {
+                        Some(parsed_slot_weights) => parsed_slot_weights,
+                        None => {
+                            *completed_study_count.lock().unwrap() += 1;
+                            tx.send((
+                                String::from("DOCKET OVERALL PROGRESS"),
+                                *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                            )).unwrap();
+                            error!("\tFailed to Parse Slot Weights: Skipping to Next Study");
+                            failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                            return;
+                        }
+                    };
+                    Box::new(create_static_duo_skill_study(
                         docket_study.identifier.to_string(),
                         docket_study.description.to_string(),
                         docket_study.sim_qty,
                         docket_study.runoff_scoring_threshold,
-                        team,
-                        valid_skills.clone(),
-                        static_skills,
-                        team_heroes[0].get_identifier().to_string(),
-                        loaded_heroes_from_builder[&team_heroes[0].get_identifier()].clone(),
+                        team.unwrap(),
+                        valid_skills_per_hero[0].clone(),
+                        static_skills_per_hero[0].clone(),
+                        varied_heroes[0].get_identifier().to_string(),
+                        loaded_heroes_from_builder[&varied_heroes[0].get_identifier()].clone(),
                         dungeons,
                         docket_study.automatic_rank_difficulty_optimization,
+                        docket_study.rank_difficulty_ceiling,
                         loaded_hero_builder_information.clone(),
                         loaded_hero_builder_information
                             .hero_skill_abbreviation_map
                             .clone(),
-                    );
-
-                    // info!("\tCreated Study (StaticDuoSkillStudy)");
-
-                    // info!(
-                    //     "\tSkill Variations Remaining to Test: {}",
-                    //     study.count_skill_variations_remaining()
-                    // );
-
-                    study.run(&m, &m_sty, tx.clone());
-
-                    // docket_completion_tracker.studies[docket_index - 1].completed = true;
+                        docket_study.ordered_skill_slots,
+                        slot_weights,
+                        docket_study.seed,
+                        docket_study.get_row_hash(),
+                        f!("{}/simulations/{}", output_directory, docket_study.identifier),
+                        docket_study.get_adaptive_sampling(),
+                        docket_study.combat_log_cap,
+                        report_settings,
+                    ))
+                }
+                DocketStudyType::SingleHeroSkillStudy => {
+                    let hero_qty = team_heroes.iter().filter(|hero| !hero.is_champion()).count();
+                    if hero_qty != 1 {
+                        *completed_study_count.lock().unwrap() += 1;
+                        tx.send((
+                            String::from("DOCKET OVERALL PROGRESS"),
+                            *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                        )).unwrap();
+                        error!("\tSingleHeroSkillStudy requires exactly one hero besides the champion but the team has {}: Skipping to Next Study", hero_qty);
+                        failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                        return;
+                    }
+                    Box::new(create_single_hero_skill_study(
+                        docket_study.identifier.to_string(),
+                        docket_study.description.to_string(),
+                        docket_study.sim_qty,
+                        docket_study.runoff_scoring_threshold,
+                        team.unwrap(),
+                        valid_skills_per_hero[0].clone(),
+                        static_skills_per_hero[0].clone(),
+                        varied_heroes[0].get_identifier().to_string(),
+                        loaded_heroes_from_builder[&varied_heroes[0].get_identifier()].clone(),
+                        dungeons,
+                        docket_study.automatic_rank_difficulty_optimization,
+                        docket_study.rank_difficulty_ceiling,
+                        loaded_hero_builder_information.clone(),
+                        docket_study.seed,
+                        docket_study.get_row_hash(),
+                        f!("{}/simulations/{}", output_directory, docket_study.identifier),
+                        docket_study.get_adaptive_sampling(),
+                        docket_study.combat_log_cap,
+                        report_settings,
+                    ))
+                }
+                DocketStudyType::TeamSkillStudy => {
+                    let mut study_varied_heroes: Vec<VariedHero> = vec![];
+                    for (i, hero) in varied_heroes.iter().enumerate() {
+                        study_varied_heroes.push(create_varied_hero(
+                            hero.get_identifier().to_string(),
+                            loaded_heroes_from_builder[&hero.get_identifier()].clone(),
+                            valid_skills_per_hero[i].clone(),
+                            static_skills_per_hero[i].clone(),
+                            &loaded_hero_builder_information,
+                        ));
+                    }
+                    match validate_joint_skill_variations(&study_varied_heroes) {
+                        Ok(_) => (),
+                        Err(e) => {
+                            *completed_study_count.lock().unwrap() += 1;
+                            tx.send((
+                                String::from("DOCKET OVERALL PROGRESS"),
+                                *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                            )).unwrap();
+                            error!("\t{}: Skipping to Next Study", e);
+                            failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                            return;
+                        }
+                    }
+                    Box::new(create_team_skill_study(
+                        docket_study.identifier.to_string(),
+                        docket_study.description.to_string(),
+                        docket_study.sim_qty,
+                        docket_study.runoff_scoring_threshold,
+                        team.unwrap(),
+                        study_varied_heroes,
+                        dungeons,
+                        docket_study.automatic_rank_difficulty_optimization,
+                        docket_study.rank_difficulty_ceiling,
+                        loaded_hero_builder_information.clone(),
+                        docket_study.seed,
+                        docket_study.get_row_hash(),
+                        f!("{}/simulations/{}", output_directory, docket_study.identifier),
+                        docket_study.get_adaptive_sampling(),
+                        docket_study.combat_log_cap,
+                        report_settings,
+                    ))
+                }
+                DocketStudyType::GearOptimizationStudy => {
+                    let subject_hero_builder =
+                        loaded_heroes_from_builder[&varied_heroes[0].get_identifier()].clone();
+                    let gear_constraints = match parse_gear_constraints(
+                        &docket_study,
+                        &subject_hero_builder,
+                        &loaded_hero_builder_information,
+                    ) {
+                        Some(parsed_gear_constraints) => parsed_gear_constraints,
+                        None => {
+                            *completed_study_count.lock().unwrap() += 1;
+                            tx.send((
+                                String::from("DOCKET OVERALL PROGRESS"),
+                                *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                            )).unwrap();
+                            error!("\tFailed to Parse Gear Constraints: Skipping to Next Study");
+                            failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                            return;
+                        }
+                    };
+                    Box::new(create_gear_optimization_study(
+                        docket_study.identifier.to_string(),
+                        docket_study.description.to_string(),
+                        docket_study.sim_qty,
+                        docket_study.runoff_scoring_threshold,
+                        team.unwrap(),
+                        varied_heroes[0].get_identifier().to_string(),
+                        subject_hero_builder,
+                        static_skills_per_hero[0].clone(),
+                        gear_constraints,
+                        dungeons,
+                        docket_study.automatic_rank_difficulty_optimization,
+                        docket_study.rank_difficulty_ceiling,
+                        loaded_hero_builder_information.clone(),
+                        docket_study.seed,
+                        docket_study.get_row_hash(),
+                        f!("{}/simulations/{}", output_directory, docket_study.identifier),
+                        docket_study.get_adaptive_sampling(),
+                        docket_study.combat_log_cap,
+                        report_settings,
+                    ))
+                }
+                DocketStudyType::TeamCompositionStudy => {
+                    // Every team must fit in every dungeon of the study, and the champion leading it takes one of the places
+                    let max_team_size = dungeons
+                        .iter()
+                        .map(|td| td.dungeon.get_max_num_heroes() as usize)
+                        .min()
+                        .unwrap()
+                        .saturating_sub(1);
+                    let pool_hero_qty = team_heroes.iter().filter(|hero| !hero.is_champion()).count();
+                    let team_size = if docket_study.team_size == 0 {
+                        max_team_size.min(pool_hero_qty)
+                    } else {
+                        docket_study.team_size
+                    };
+                    if team_size > max_team_size || team_size > pool_hero_qty || pool_hero_qty == team_heroes.len() {
+                        *completed_study_count.lock().unwrap() += 1;
+                        tx.send((
+                            String::from("DOCKET OVERALL PROGRESS"),
+                            *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+                        )).unwrap();
+                        error!("\tTeam Size {} must not exceed the {} heroes in the pool or the dungeons' maximum of {} heroes besides the champion, and the pool must include a champion: Skipping to Next Study", team_size, pool_hero_qty, max_team_size);
+                        failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                        return;
+                    }
+                    Box::new(create_team_composition_study(
+                        docket_study.identifier.to_string(),
+                        docket_study.description.to_string(),
+                        docket_study.sim_qty,
+                        docket_study.runoff_scoring_threshold,
+                        team_heroes.clone(),
+                        team_booster.clone(),
+                        team_size,
+                        dungeons,
+                        docket_study.automatic_rank_difficulty_optimization,
+                        docket_study.rank_difficulty_ceiling,
+                        loaded_hero_builder_information.clone(),
+                        docket_study.seed,
+                        docket_study.get_row_hash(),
+                        f!("{}/simulations/{}", output_directory, docket_study.identifier),
+                        docket_study.get_adaptive_sampling(),
+                        docket_study.combat_log_cap,
+                        report_settings,
+                    ))
+                }
+            };
+            // info!("\tCreated Study ({})", docket_study.type_);
 
-                    *completed_study_count.lock().unwrap() += 1;
-                    tx.send((
-                        String::from("DOCKET OVERALL PROGRESS"),
-                        *completed_study_count.lock().unwrap(), num_dockets as u32, docket_start_instant
-                    )).unwrap();
-                    docket_study.completed = true;
+            // A panicking study is recorded as failed rather than taking down the rest of the docket
+            let run_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                study.run(&m, &m_sty, tx.clone());
+            }));
 
-                    info!("\n\tStudy Completed");
-                    pb.inc(1);
-                }
+            *completed_study_count.lock().unwrap() += 1;
+            tx.send((
+                String::from("DOCKET OVERALL PROGRESS"),
+                *completed_study_count.lock().unwrap(), num_dockets as u64, docket_start_instant
+            )).unwrap();
+            pb.inc(1);
+            if run_result.is_err() {
+                error!("\tStudy {} Failed", docket_study.identifier);
+                failed_studies.lock().unwrap().push(docket_study.identifier.to_string());
+                return;
             }
+            docket_study.completed = true;
+            let mut progress = docket_progress.lock().unwrap();
+            progress.mark_study_completed(&docket_study.identifier);
+            save_study_docket(&progress.path, &progress).unwrap();
+
+            info!("\n\tStudy Completed");
             info!("Docket Study Completed");
-            // TODO: Reimplement save_study_docket during execution...
-            // save_study_docket(&self.path, &self).unwrap();
         });
         save_study_docket(&self.path, &self).unwrap();
         info!("Docket Completed");
+
+        let failed = failed_studies.lock().unwrap().clone();
+        return failed;
     }
 }
 
+/// Returns the heroes listed in Team Hero Identifiers and the team booster
 fn parse_team(
     docket_study: &DocketStudy,
     loaded_heroes: &HashMap<String, SimHero>,
-) -> Option<Team> {
+) -> Option<(Vec<SimHero>, Option<BoosterType>)> {
     // Parse Team
     // Parse Heroes
     let mut team_heroes: Vec<SimHero> = vec![];
@@ -468,7 +937,7 @@ fn parse_team(
         }
     }
 
-    return Some(create_team(team_heroes.clone(), team_booster).unwrap());
+    return Some((team_heroes, team_booster));
 }
 
 fn parse_dungeons(
@@ -537,17 +1006,290 @@ fn parse_dungeons(
                 return None;
             }
         }
-        dungeons.push(create_trial_dungeon(dun, dundiff, dunmb));
+        // The barrier setting is optional, as it only applies to Extreme encounters
+        let dunbarrier: BarrierSetting;
+        match dungeon_str_split.get(3).copied() {
+            None | Some("Random Barrier") => dunbarrier = BarrierSetting::Random,
+            Some("All Barriers") => dunbarrier = BarrierSetting::Sweep,
+            Some(dungeon_barrier_setting) => {
+                let barrier_element = dungeon_barrier_setting
+                    .strip_suffix(" Barrier")
+                    .and_then(|element| ElementType::from_str(element).ok());
+                match barrier_element {
+                    Some(element) => {
+                        if !dun.get_barrier_types().contains(&element) {
+                            error!(
+                                "{} has no {} barrier, its barrier types are {:?}",
+                                dungeon_name,
+                                element,
+                                dun.get_barrier_types()
+                            );
+                            return None;
+                        }
+                        dunbarrier = BarrierSetting::Pinned(element);
+                    }
+                    None => {
+                        error!(
+                            "Could not load dungeon with barrier setting {}",
+                            dungeon_barrier_setting
+                        );
+                        return None;
+                    }
+                }
+            }
+        }
+        dungeons.push(create_trial_dungeon(dun, dundiff, dunmb, dunbarrier));
     }
 
     return Some(dungeons);
 }
 
+/// Returns the team heroes whose skills are varied by the study
+/// Only a TeamSkillStudy varies more than one hero, other study types vary the first hero on the team
+fn parse_varied_heroes(
+    docket_study: &DocketStudy,
+    team_heroes: &Vec<SimHero>,
+) -> Option<Vec<SimHero>> {
+    // A TeamCompositionStudy keeps every hero's skills, so its pool may list the heroes and champions in any order
+    if docket_study.type_ == DocketStudyType::TeamCompositionStudy {
+        return Some(vec![]);
+    }
+    if docket_study.type_ != DocketStudyType::TeamSkillStudy {
+        if team_heroes[0].is_champion() {
+            error!(
+                "The first hero on the team is varied, so it cannot be the champion {}",
+                team_heroes[0].get_identifier()
+            );
+            return None;
+        }
+        return Some(vec![team_heroes[0].clone()]);
+    }
+    if docket_study.varied_heroes.trim().len() == 0 {
+        return Some(
+            team_heroes
+                .iter()
+                .filter(|hero| !hero.is_champion())
+                .cloned()
+                .collect(),
+        );
+    }
+
+    let mut res: Vec<SimHero> = vec![];
+    for identifier in docket_study.varied_heroes.split(";").map(|s| s.trim()) {
+        match team_heroes
+            .iter()
+            .find(|hero| hero.get_identifier() == identifier)
+        {
+            Some(hero) if hero.is_champion() => {
+                error!(
+                    "Varied hero {} is a champion, which has no skills to vary",
+                    identifier
+                );
+                return None;
+            }
+            Some(hero) => res.push(hero.clone()),
+            None => {
+                error!("Varied hero {} is not on the team", identifier);
+                return None;
+            }
+        }
+    }
+    return Some(res);
+}
+
+/// Parse the weights of the composite score from Field:Weight pairs, scoring by success rate alone when none are given
+/// Fields that are not listed are weighted 0
+fn parse_report_settings(docket_study: &DocketStudy) -> Option<ReportSettings> {
+    let mut scoring_weights = create_default_scoring_weights();
+    if docket_study.scoring_weights.trim().len() > 0 {
+        scoring_weights.success_rate = 0.0;
+        for pair in docket_study.scoring_weights.split(";") {
+            let split_vec: Vec<&str> = pair.split(":").map(|s| s.trim()).collect();
+            if split_vec.len() != 2 {
+                error!(
+                    "Scoring weight {} must conform to format [field]:[weight]",
+                    pair.trim()
+                );
+                return None;
+            }
+            let weight = match split_vec[1].parse::<f64>() {
+                Ok(weight) if weight >= 0.0 => weight,
+                _ => {
+                    error!(
+                        "Scoring weight for {} must be a number of at least 0",
+                        split_vec[0]
+                    );
+                    return None;
+                }
+            };
+            if !scoring_weights.set_weight(split_vec[0], weight) {
+                error!(
+                    "Unknown scoring field {}, expected one of {:?}",
+                    split_vec[0], SCORING_FIELDS
+                );
+                return None;
+            }
+        }
+        if scoring_weights.describe().len() == 0 {
+            error!("At least one scoring weight must be greater than 0");
+            return None;
+        }
+    }
+    return Some(ReportSettings {
+        scoring_weights,
+        summary_top_n: docket_study.summary_top_n,
+    });
+}
+
+/// Parse the weight of each of the 4 skill slots, weighting every slot 1 when none are given
+fn parse_slot_weights(docket_study: &DocketStudy) -> Option<[f64; 4]> {
+    if docket_study.slot_weights.trim().len() == 0 {
+        return Some([1.0; 4]);
+    }
+    let mut res = [0.0; 4];
+    let split_vec: Vec<&str> = docket_study.slot_weights.split(";").collect();
+    if split_vec.len() != 4 {
+        error!("Expected 4 slot weights but found {}", split_vec.len());
+        return None;
+    }
+    for (slot, weight_str) in split_vec.iter().enumerate() {
+        match weight_str.trim().parse::<f64>() {
+            Ok(weight) if weight > 0.0 => res[slot] = weight,
+            _ => {
+                error!(
+                    "Slot weight {} must be a number greater than 0",
+                    weight_str.trim()
+                );
+                return None;
+            }
+        }
+    }
+    return Some(res);
+}
+
+/// Returns the constraints on the gear a GearOptimizationStudy may equip on the hero
+/// Elements must be of the hero's element type, and empty elements or spirits leave the hero's own to be socketed
+fn parse_gear_constraints(
+    docket_study: &DocketStudy,
+    hero_builder: &Hero,
+    loaded_hero_builder_information: &HeroBuilderInformation,
+) -> Option<GearConstraints> {
+    let split_list = |list: &String| -> Vec<String> {
+        return list
+            .split(";")
+            .map(|s| s.trim().to_string())
+            .filter(|s| s.len() > 0)
+            .collect();
+    };
+    let qualities = split_list(&docket_study.gear_qualities);
+    let elements = split_list(&docket_study.gear_elements);
+    let spirits = split_list(&docket_study.gear_spirits);
+
+    if let Some(quality) = qualities
+        .iter()
+        .find(|quality| !GEAR_QUALITIES.contains(&quality.as_str()))
+    {
+        error!(
+            "Unknown gear quality {}, expected one of {:?}",
+            quality, GEAR_QUALITIES
+        );
+        return None;
+    }
+
+    let element_type =
+        loaded_hero_builder_information.hero_classes[&hero_builder.get_class()].get_element_type();
+    if elements.len() > 0 && elements.len() != 6 {
+        error!("Expected 6 gear elements but found {}", elements.len());
+        return None;
+    }
+    for element in elements.iter() {
+        let split_vec = element.split_whitespace().collect::<Vec<&str>>();
+        if split_vec.len() != 2
+            || (split_vec[0] != element_type && element_type != "Any")
+            || !["1", "2", "3", "4"].contains(&split_vec[1])
+        {
+            error!(
+                "Gear element {} must conform to format [{}] [grade: 1-4]",
+                element, element_type
+            );
+            return None;
+        }
+    }
+
+    if spirits.len() > 0 && spirits.len() != 6 {
+        error!("Expected 6 gear spirits but found {}", spirits.len());
+        return None;
+    }
+    for spirit in spirits.iter() {
+        let split_vec = spirit.split_whitespace().collect::<Vec<&str>>();
+        if split_vec.len() != 2 || !SPIRIT_TIERS.contains(&split_vec[1]) {
+            error!(
+                "Gear spirit {} must conform to format [name] [tier: one of {:?}]",
+                spirit, SPIRIT_TIERS
+            );
+            return None;
+        }
+    }
+
+    if docket_study.gear_candidates_per_slot == 0 {
+        error!("Gear Candidates Per Slot must be at least 1");
+        return None;
+    }
+    // Every combination of the slots' candidates is trialed
+    match (docket_study.gear_candidates_per_slot as i64).checked_pow(6) {
+        Some(gear_set_qty) if gear_set_qty <= MAX_GEAR_SETS => (),
+        _ => {
+            error!(
+                "Gear Candidates Per Slot of {} would trial more than {} gear sets (the value to the power of 6)",
+                docket_study.gear_candidates_per_slot, MAX_GEAR_SETS
+            );
+            return None;
+        }
+    }
+
+    return Some(GearConstraints {
+        max_tier: docket_study.gear_max_tier,
+        qualities,
+        elements,
+        spirits,
+        candidates_per_slot: docket_study.gear_candidates_per_slot,
+    });
+}
+
+/// Split the preset skills into one group per varied hero, with groups separated by "|" in the order of the varied heroes
+/// Empty preset skills leave every skill slot of every varied hero to be varied
+fn parse_preset_skill_groups(
+    docket_study: &DocketStudy,
+    varied_hero_count: usize,
+) -> Option<Vec<String>> {
+    if docket_study.preset_skills.trim().len() == 0 || varied_hero_count == 0 {
+        return Some(vec![String::new(); varied_hero_count]);
+    }
+    let groups: Vec<String> = docket_study
+        .preset_skills
+        .split("|")
+        .map(|s| s.trim().to_string())
+        .collect();
+    if groups.len() != varied_hero_count {
+        error!(
+            "Expected {} groups of preset skills but found {}",
+            varied_hero_count,
+            groups.len()
+        );
+        return None;
+    }
+    return Some(groups);
+}
+
+/// Returns the skills that may be varied upon for the hero, given the hero's preset skills
+/// When the study prunes unequippable skills, item-conditional skills the hero's equipment can never satisfy are excluded and logged
 fn parse_valid_skills(
     docket_study: &DocketStudy,
+    preset_skills: &String,
     loaded_valid_skills: &Vec<String>,
     loaded_hero_builder_information: &HeroBuilderInformation,
-    team_heroes: &Vec<SimHero>,
+    hero: &SimHero,
+    hero_builder: &Hero,
 ) -> Option<Vec<String>> {
     let translated_excluded_skills_option = translate_skillset_based_on_skill_name_format(
         &docket_study.skill_name_format,
@@ -567,8 +1309,7 @@ fn parse_valid_skills(
 
     let translated_preset_skills_option = translate_skillset_based_on_skill_name_format(
         &docket_study.skill_name_format,
-        docket_study
-            .preset_skills
+        preset_skills
             .split(";")
             .map(|s| s.trim().to_string())
             .collect::<Vec<String>>(),
@@ -584,7 +1325,7 @@ fn parse_valid_skills(
     let incompatible_skills =
         loaded_hero_builder_information.get_incompatible_skills(&translated_preset_skills);
 
-    let hero_class = team_heroes[0].get_class();
+    let hero_class = hero.get_class();
     let skills_incompatible_with_hero_class =
         loaded_hero_builder_information.get_skills_incompatible_with_hero(hero_class);
 
@@ -606,7 +1347,27 @@ fn parse_valid_skills(
         .difference(&hero_incompatible_skillset)
         .cloned()
         .collect();
-    return Some(diff_4);
+
+    if !docket_study.prune_unequippable_skills {
+        return Some(diff_4);
+    }
+    let mut unequippable_skills =
+        loaded_hero_builder_information.get_skills_without_matching_equipment(hero_builder);
+    unequippable_skills.retain(|(skill, _)| diff_4.contains(skill));
+    unequippable_skills.sort();
+    for (skill, item_types) in unequippable_skills.iter() {
+        info!(
+            "\tPruned {} for {}: Requires {} but no equipped or alternate item is of those types",
+            skill,
+            hero.get_identifier(),
+            item_types.join(" or ")
+        );
+    }
+    let res: Vec<String> = diff_4
+        .into_iter()
+        .filter(|skill| !unequippable_skills.iter().any(|(s, _)| s == skill))
+        .collect();
+    return Some(res);
 }
 
 fn translate_skillset_based_on_skill_name_format(
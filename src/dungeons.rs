@@ -4,6 +4,7 @@ use crate::inputs::{create_dungeon_input, DungeonInput};
 use super::equipment::ElementType;
 
 use rand::distributions::{Distribution, Standard};
+use rand::rngs::StdRng;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
@@ -116,7 +117,7 @@ impl Encounter {
         }
     }
 
-    pub fn round_floats_for_display(&self) -> Encounter {
+    pub fn _round_floats_for_display(&self) -> Encounter {
         let mut e2 = self.clone();
         e2.hp = round_to_2(e2.hp);
         e2.hp_max = round_to_2(e2.hp_max);
@@ -220,11 +221,24 @@ pub fn create_encounter(
 ///
 /// force_minibosses:
 /// false - No Minibosses, true - Only Minibosses, none - Random Chance of Minibosses
+///
+/// barrier_setting:
+/// The barrier element of Extreme encounters, see BarrierSetting
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TrialDungeon {
     pub dungeon: Dungeon,
     pub difficulty: usize,
     pub force_minibosses: Option<bool>,
+    pub barrier_setting: BarrierSetting,
+}
+
+/// Defines how the barrier element of Extreme (non-boss) encounters is chosen
+/// Boss Extreme encounters always have the dungeon's boss barrier type
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum BarrierSetting {
+    Random,              // Each encounter draws one of the dungeon's barrier types
+    Pinned(ElementType), // Every encounter has the given barrier type
+    Sweep,               // Encounters cycle through the dungeon's barrier types by simulation index
 }
 
 /// Difficulty settings (choose one):
@@ -237,11 +251,13 @@ pub fn create_trial_dungeon(
     dungeon: Dungeon,
     difficulty: usize,
     force_minibosses: Option<bool>,
+    barrier_setting: BarrierSetting,
 ) -> TrialDungeon {
     return TrialDungeon {
         dungeon,
         difficulty,
         force_minibosses,
+        barrier_setting,
     };
 }
 
@@ -281,16 +297,47 @@ impl Dungeon {
         return self.zone.clone();
     }
 
+    pub fn get_max_num_heroes(&self) -> u8 {
+        return self.max_num_heroes;
+    }
+
+    pub fn get_barrier_types(&self) -> [ElementType; 3] {
+        return self.barrier_types;
+    }
+
+    /// Returns the barrier type of an Extreme encounter under the barrier setting
+    /// Only a Random setting draws from rng
+    fn select_barrier_type(
+        &self,
+        barrier_setting: BarrierSetting,
+        simulation_index: usize,
+        rng: &mut StdRng,
+    ) -> ElementType {
+        match barrier_setting {
+            BarrierSetting::Random => return self.barrier_types[rng.gen_range(0..3)],
+            BarrierSetting::Pinned(barrier_type) => return barrier_type,
+            BarrierSetting::Sweep => return self.barrier_types[simulation_index % 3],
+        }
+    }
+
     /// Difficulty settings (include all that should apply):
     /// 1 - Easy, 2 - Medium, 3 - Hard, 4 - Extreme,
     /// 5 - Boss Easy, 6 - Boss Medium, 7 - Boss Hard, 8 - Boss Extreme
     ///
     /// force_minibosses:
     /// false - No Minibosses, true - Only Minibosses, none - Random Chance of Minibosses
+    ///
+    /// barrier_setting:
+    /// How the barrier type of Extreme encounters is chosen, a Sweep uses simulation_index
+    ///
+    /// All random selections are drawn from rng
     pub fn generate_encounter_from_dungeon(
         &self,
         difficulty_settings: &Vec<usize>,
         force_minibosses: Option<bool>,
+        barrier_setting: BarrierSetting,
+        simulation_index: usize,
+        rng: &mut StdRng,
     ) -> Result<Encounter, &'static str> {
         // Check for out of bounds
         for &difficulty in difficulty_settings {
@@ -299,7 +346,6 @@ impl Dungeon {
             }
         }
 
-        let mut rng = rand::thread_rng();
         let diff_rand = rng.gen_range(0..difficulty_settings.len());
         let mut sel_diff = difficulty_settings[diff_rand];
         let encounter: Encounter;
@@ -313,14 +359,14 @@ impl Dungeon {
             match force_minibosses {
                 Some(setting) => {
                     miniboss = if setting {
-                        Some(rand::random::<MiniBossType>())
+                        Some(rng.gen::<MiniBossType>())
                     } else {
                         None
                     }
                 }
                 _ => {
                     if rng.gen_range(0..2) == 1 {
-                        miniboss = Some(rand::random::<MiniBossType>());
+                        miniboss = Some(rng.gen::<MiniBossType>());
                     } else {
                         miniboss = None;
                     }
@@ -335,10 +381,10 @@ impl Dungeon {
                 self.aoe_damage[sel_diff],
                 self.aoe_chance[sel_diff],
                 false,
-                sel_diff == 4,
+                sel_diff == 3,
                 miniboss,
-                if sel_diff == 4 {
-                    Some(self.barrier_types[rng.gen_range(0..3)])
+                if sel_diff == 3 {
+                    Some(self.select_barrier_type(barrier_setting, simulation_index, rng))
                 } else {
                     None
                 },
@@ -359,9 +405,9 @@ impl Dungeon {
                 self.boss_aoe_damage[sel_diff],
                 self.boss_aoe_chance[sel_diff],
                 true,
-                sel_diff == 4,
+                sel_diff == 3,
                 None,
-                if sel_diff == 4 {
+                if sel_diff == 3 {
                     Some(self.boss_barrier_type)
                 } else {
                     None
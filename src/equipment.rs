@@ -114,6 +114,10 @@ impl Blueprint {
         return self.type_.to_string();
     }
 
+    pub fn get_tier(&self) -> u8 {
+        return self.tier;
+    }
+
     pub fn get_unlock_prerequisite(&self) -> String {
         return self.unlock_prerequisite.to_string();
     }
@@ -73,6 +73,17 @@ pub fn _create_hero_class(
     };
 }
 
+impl HeroClass {
+    pub fn get_element_type(&self) -> String {
+        return self.element_type.to_string();
+    }
+
+    /// Returns the item types allowed in each equipment slot
+    pub fn get_equipment_allowed(&self) -> [Vec<String>; 6] {
+        return self.equipment_allowed.clone();
+    }
+}
+
 /// Defines a Hero that contains info on base stats, equipment, and skills
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Hero {
@@ -107,6 +118,9 @@ pub struct Hero {
     equipment_quality: [String; 6],
     elements_socketed: [String; 6],
     spirits_socketed: [String; 6],
+
+    alternate_weapons: [String; 4], // Substituted for the weapon when a skillset has a mastery skill it does not match, empty if unused
+    alternate_off_hands: [String; 4], // Substituted for the off-hand (e.g. shield) likewise
 }
 
 pub fn create_hero(
@@ -141,6 +155,9 @@ pub fn create_hero(
     equipment_quality: [String; 6],
     elements_socketed: [String; 6],
     spirits_socketed: [String; 6],
+
+    alternate_weapons: [String; 4],
+    alternate_off_hands: [String; 4],
 ) -> Hero {
     return Hero {
         identifier,
@@ -174,6 +191,9 @@ pub fn create_hero(
         equipment_quality,
         elements_socketed,
         spirits_socketed,
+
+        alternate_weapons,
+        alternate_off_hands,
     };
 }
 
@@ -185,6 +205,179 @@ impl Hero {
         self.skills[3] = new_skills.get(3).unwrap_or(&String::from("")).to_string();
     }
 
+    pub fn get_class(&self) -> String {
+        return self.class.to_string();
+    }
+
+    pub fn get_hp(&self) -> f64 {
+        return self.hp;
+    }
+
+    pub fn get_atk(&self) -> f64 {
+        return self.atk;
+    }
+
+    pub fn get_def(&self) -> f64 {
+        return self.def;
+    }
+
+    pub fn get_equipment_equipped(&self) -> [String; 6] {
+        return self.equipment_equipped.clone();
+    }
+
+    pub fn get_equipment_quality(&self) -> [String; 6] {
+        return self.equipment_quality.clone();
+    }
+
+    pub fn get_elements_socketed(&self) -> [String; 6] {
+        return self.elements_socketed.clone();
+    }
+
+    pub fn get_spirits_socketed(&self) -> [String; 6] {
+        return self.spirits_socketed.clone();
+    }
+
+    /// Replace the hero's equipment along with the quality, element and spirit of each item, recalculating the element qty
+    pub fn set_equipment(
+        &mut self,
+        equipment_equipped: [String; 6],
+        equipment_quality: [String; 6],
+        elements_socketed: [String; 6],
+        spirits_socketed: [String; 6],
+        bp_map: &HashMap<String, Blueprint>,
+        hero_classes: &HashMap<String, HeroClass>,
+    ) {
+        self.equipment_equipped = equipment_equipped;
+        self.equipment_quality = equipment_quality;
+        self.elements_socketed = elements_socketed;
+        self.spirits_socketed = spirits_socketed;
+        self.validate_equipment(bp_map, hero_classes);
+    }
+
+    /// Returns the item types of the equipped items along with those of the alternates that may be substituted in
+    pub fn get_available_item_types(&self, bp_map: &HashMap<String, Blueprint>) -> Vec<String> {
+        let mut res: Vec<String> = vec![];
+        for equipment in self
+            .equipment_equipped
+            .iter()
+            .chain(self.alternate_weapons.iter())
+            .chain(self.alternate_off_hands.iter())
+        {
+            if let Some(bp) = bp_map.get(equipment) {
+                if !res.contains(&bp.get_type()) {
+                    res.push(bp.get_type());
+                }
+            }
+        }
+        return res;
+    }
+
+    /// Swap in an alternate weapon or off-hand for each item-conditional skill (e.g. Sword Master) whose item types none of the equipped items match
+    /// Alternates take the quality, element and spirit of the item they replace, and each slot is only substituted for the first skill needing it
+    /// Returns a description of each substitution made
+    pub fn substitute_alternate_equipment_for_skills(
+        &mut self,
+        bp_map: &HashMap<String, Blueprint>,
+        hero_classes: &HashMap<String, HeroClass>,
+        hero_skill_map: &HashMap<String, HeroSkill>,
+    ) -> Vec<String> {
+        let mut res: Vec<String> = vec![];
+        let class = hero_classes.get(&self.class).unwrap();
+        let skill_item_types: Vec<(String, Vec<String>)> = self
+            .skills
+            .iter()
+            .filter_map(|skill_name| {
+                let item_types: Vec<String> = hero_skill_map
+                    .get(skill_name)?
+                    .get_item_types()
+                    .into_iter()
+                    .filter(|itype| itype.len() > 0)
+                    .collect();
+                if item_types.len() == 0 {
+                    return None;
+                }
+                return Some((skill_name.to_string(), item_types));
+            })
+            .collect();
+        let get_matching_slots = |equipment_equipped: &[String; 6], item_types: &Vec<String>| {
+            return equipment_equipped
+                .iter()
+                .enumerate()
+                .filter(|(_, equipment)| {
+                    bp_map
+                        .get(*equipment)
+                        .map_or(false, |bp| item_types.contains(&bp.get_type()))
+                })
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>();
+        };
+
+        // Slots whose item already satisfies a skill in the set are never substituted, nor is any slot substituted twice
+        let mut locked_slots: Vec<usize> = vec![];
+        for (_, item_types) in skill_item_types.iter() {
+            locked_slots.append(&mut get_matching_slots(
+                &self.equipment_equipped,
+                item_types,
+            ));
+        }
+
+        for (skill_name, item_types) in skill_item_types {
+            if get_matching_slots(&self.equipment_equipped, &item_types).len() > 0 {
+                continue;
+            }
+
+            let alternates = self
+                .alternate_weapons
+                .iter()
+                .map(|alternate| (alternate, true))
+                .chain(
+                    self.alternate_off_hands
+                        .iter()
+                        .map(|alternate| (alternate, false)),
+                );
+            let mut substitution: Option<(usize, String)> = None;
+            for (alternate, is_weapon) in alternates {
+                let blueprint = match bp_map.get(alternate) {
+                    Some(bp) => bp,
+                    None => continue,
+                };
+                if !item_types.contains(&blueprint.get_type()) {
+                    continue;
+                }
+                let slot = if is_weapon {
+                    Some(0)
+                } else {
+                    get_off_hand_slot_index(class, &blueprint.get_type())
+                };
+                match slot {
+                    Some(i) if !locked_slots.contains(&i) => {
+                        substitution = Some((i, alternate.to_string()));
+                        break;
+                    }
+                    _ => continue,
+                }
+            }
+
+            if let Some((i, alternate)) = substitution {
+                res.push(f!(
+                    "{}: {} -> {} ({})",
+                    self.identifier,
+                    self.equipment_equipped[i],
+                    alternate,
+                    skill_name
+                ));
+                self.equipment_equipped[i] = alternate;
+                locked_slots.push(i);
+            }
+        }
+
+        if res.len() > 0 {
+            // Recalculate the element qty for the new items
+            self.validate_equipment(bp_map, hero_classes);
+        }
+        return res;
+    }
+
     pub fn validate_equipment(
         &mut self,
         bp_map: &HashMap<String, Blueprint>,
@@ -286,6 +479,36 @@ impl Hero {
                 );
             }
         }
+        for alternate in self.alternate_weapons.iter().filter(|a| a.len() > 0) {
+            match bp_map.get(alternate) {
+                Some(blueprint) => {
+                    if !class.equipment_allowed[0].contains(&blueprint.get_type()) {
+                        equipment_that_is_not_allowed_on_the_specified_class.push((
+                            alternate.to_string(),
+                            blueprint.get_type(),
+                            class.class.to_string(),
+                            0,
+                        ));
+                    }
+                }
+                None => equipment_that_could_not_be_found.push(alternate.to_string()),
+            }
+        }
+        for alternate in self.alternate_off_hands.iter().filter(|a| a.len() > 0) {
+            match bp_map.get(alternate) {
+                Some(blueprint) => {
+                    if get_off_hand_slot_index(class, &blueprint.get_type()).is_none() {
+                        equipment_that_is_not_allowed_on_the_specified_class.push((
+                            alternate.to_string(),
+                            blueprint.get_type(),
+                            class.class.to_string(),
+                            class.equipment_allowed.len() - 1,
+                        ));
+                    }
+                }
+                None => equipment_that_could_not_be_found.push(alternate.to_string()),
+            }
+        }
         let mut do_panic = false;
         if equipment_that_could_not_be_found.len() > 0 {
             do_panic = true;
@@ -1219,25 +1442,6 @@ impl Hero {
         self.threat_rating = final_threat_rating;
         // log::info!("final_threat_rating: {}", final_threat_rating);
 
-        let mut geo_astramancer_element_qty_or_chieftain_threat_bonus: f64 = 0.0;
-        match self.class.as_str() {
-            "Geomancer" => {
-                geo_astramancer_element_qty_or_chieftain_threat_bonus = f64::from(self.element_qty)
-            }
-            "Astramancer" => {
-                geo_astramancer_element_qty_or_chieftain_threat_bonus = f64::from(self.element_qty)
-            }
-            "Chieftain" => {
-                geo_astramancer_element_qty_or_chieftain_threat_bonus =
-                    0.4 * f64::from(self.threat_rating)
-            }
-            _ => (),
-        }
-        // log::info!(
-        //     "geo_astramancer_element_qty_or_chieftain_threat_bonus: {}",
-        //     geo_astramancer_element_qty_or_chieftain_threat_bonus
-        // );
-
         // println!("--{}--", self.identifier);
         // ATK calc
         let base_atk = self.atk;
@@ -1256,15 +1460,12 @@ impl Hero {
         //     skill_bonus_atk_value,
         //     summarized_base_atk_value,
         // );
-        let final_atk_mod = 1.0
-            + skill_bonus_atk_percent
-            + geo_astramancer_element_qty_or_chieftain_threat_bonus
-            + spirit_bonus_atk_percent;
+        // The Chieftain's threat and Geomancer's element bonuses are applied in the simulation, see ClassEffect
+        let final_atk_mod = 1.0 + skill_bonus_atk_percent + spirit_bonus_atk_percent;
         // log::info!(
-        //     "\nCalculate final_atk_mod:\n\tstarting_mod: {}\n\tskill_bonus_atk_percent: {}\n\tgeo_astramancer_element_qty_or_chieftain_threat_bonus: {}\n\tspirit_bonus_atk_percent: {}\n\tfinal_atk_mod: {}",
+        //     "\nCalculate final_atk_mod:\n\tstarting_mod: {}\n\tskill_bonus_atk_percent: {}\n\tspirit_bonus_atk_percent: {}\n\tfinal_atk_mod: {}",
         //     1.0,
         //     skill_bonus_atk_percent,
-        //     geo_astramancer_element_qty_or_chieftain_threat_bonus,
         //     spirit_bonus_atk_percent,
         //     final_atk_mod,
         // );
@@ -1291,7 +1492,7 @@ impl Hero {
         //     final_atk,
         // );
         // println!("final_atk: {}", final_atk);
-        // ((seeded_atk + gear_spirit_bonus_atk_value + sum(skill_bonus_atk_value)) * (1 + ((skill_atk_percent + geo_astramancer_element_qty_or_chieftain_threat_bonus) + bonus_spirit_atk_percent)/100)) + (bonus_atk_value * (1 + ((skill_atk_percent + geo_astramancer_element_qty_or_chieftain_threat_bonus) + bonus_spirit_atk_percent)/100)))
+        // ((seeded_atk + gear_spirit_bonus_atk_value + sum(skill_bonus_atk_value)) * (1 + (skill_atk_percent + bonus_spirit_atk_percent)/100)) + (bonus_atk_value * (1 + (skill_atk_percent + bonus_spirit_atk_percent)/100)))
 
         // ATK mod
         self.atk_modifier = final_atk_mod;
@@ -1400,6 +1601,14 @@ impl From<Hero> for SimHero {
     }
 }
 
+/// Returns the slot an off-hand item of the given type is equipped in for the class, searching from the last slot
+/// The weapon slot is never an off-hand slot
+fn get_off_hand_slot_index(class: &HeroClass, item_type: &String) -> Option<usize> {
+    return (1..class.equipment_allowed.len())
+        .rev()
+        .find(|i| class.equipment_allowed[*i].contains(item_type));
+}
+
 impl From<Hero> for HeroInput {
     fn from(item: Hero) -> Self {
         return create_hero_input(
@@ -1416,6 +1625,8 @@ impl From<Hero> for HeroInput {
             item.equipment_quality,
             item.elements_socketed,
             item.spirits_socketed,
+            item.alternate_weapons,
+            item.alternate_off_hands,
         );
     }
 }
@@ -1,4 +1,7 @@
 use crate::{
+    champions::{AuraArchetype, AuraEffect, AuraModifier},
+    class_effects::{get_class_effects, ClassEffect},
+    combat_events::{CombatEvent, CombatLog, HealSource},
     decimals::round_to_2,
     inputs::{create_sim_hero_input, SimHeroInput},
 };
@@ -8,7 +11,8 @@ use std::string::ToString;
 
 use crate::equipment::{BoosterType, ElementType};
 
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 /// One or more Heroes fighting together in a dungeon and what booster they have
@@ -20,8 +24,57 @@ pub struct Team {
     num_rogues: u8,
     num_spellcasters: u8,
     num_tricksters: u8,
-    champion: String,
-    champion_innate_tier: u8,
+    champion_index: usize, // The index in heroes of the team's champion
+}
+
+/// Counts of a hero's attacks and of the attacks against it, so that each rate can be taken over the attempts it applies to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct HeroAccuracyStats {
+    pub attacks_made: u32,
+    pub attacks_missed: u32,
+    pub crits_dealt: u32,      // Counted among the attacks made that hit
+    pub attacks_received: u32, // Each single target attack on the hero and each AoE that reaches it
+    pub dodges: u32,
+    pub crits_taken: u32, // Counted among the attacks received that were not dodged
+}
+
+impl HeroAccuracyStats {
+    pub fn add(&mut self, other: &HeroAccuracyStats) {
+        self.attacks_made += other.attacks_made;
+        self.attacks_missed += other.attacks_missed;
+        self.crits_dealt += other.crits_dealt;
+        self.attacks_received += other.attacks_received;
+        self.dodges += other.dodges;
+        self.crits_taken += other.crits_taken;
+    }
+
+    /// Returns the share of attacks made that hit
+    pub fn get_atk_hit_rate(&self) -> f64 {
+        return get_rate(self.attacks_made - self.attacks_missed, self.attacks_made);
+    }
+
+    /// Returns the share of hits landed that were crits
+    pub fn get_crit_dealt_rate(&self) -> f64 {
+        return get_rate(self.crits_dealt, self.attacks_made - self.attacks_missed);
+    }
+
+    /// Returns the share of attacks received that were dodged
+    pub fn get_dodge_rate(&self) -> f64 {
+        return get_rate(self.dodges, self.attacks_received);
+    }
+
+    /// Returns the share of hits taken that were crits
+    pub fn get_crit_taken_rate(&self) -> f64 {
+        return get_rate(self.crits_taken, self.attacks_received - self.dodges);
+    }
+}
+
+/// Returns count / attempts, or 0 when there were no attempts
+fn get_rate(count: u32, attempts: u32) -> f64 {
+    if attempts == 0 {
+        return 0.0;
+    }
+    return count as f64 / attempts as f64;
 }
 
 /// Defines valid hero archetypes
@@ -65,11 +118,11 @@ impl Team {
         self.heroes[index] = new_hero;
     }
 
-    pub fn round_floats_for_display(&self) -> Team {
+    pub fn _round_floats_for_display(&self) -> Team {
         let mut t2 = self.clone();
         let mut heroes: Vec<SimHero> = vec![];
         for hero in &t2.heroes {
-            heroes.push(hero.round_floats_for_display());
+            heroes.push(hero._round_floats_for_display());
         }
         t2.heroes = heroes;
         return t2;
@@ -108,137 +161,51 @@ impl Team {
         }
     }
 
-    pub fn apply_champion_and_booster_bonuses(&mut self, is_boss: bool) -> (f64, bool, f64, u8) {
-        let (champion, champion_innate_tier) = self.get_champion_info();
-        let (num_spellcasters, num_rogues, num_fighters, num_tricksters) =
-            self.get_num_archetypes();
-
-        let mut champion_attack_bonus = 0f64;
-        let mut champion_defense_bonus = 0f64;
-
-        let mut hemma_mult = 0f64;
-        let mut count_loot = false;
-        let mut loot_chance = 0f64;
-        let mut polonia_loot_cap = 20;
+    /// Apply the champion's aura and the booster to the heroes' stats, returning whether to count bonus loot, the loot chance and the loot cap
+    pub fn apply_champion_and_booster_bonuses(&mut self, is_boss: bool) -> (bool, f64, u8) {
+        let mut champion_attack_bonuses: Vec<f64> = vec![];
+        let mut champion_defense_bonuses: Vec<f64> = vec![];
 
         let mut booster_attack_bonus = 0f64;
         let mut booster_defense_bonus = 0f64;
 
-        match champion.as_str() {
-            "Argon" => {
-                champion_attack_bonus = 0.1f64 * f64::from(champion_innate_tier);
-                champion_defense_bonus = champion_attack_bonus;
-            }
-            "Ashley" => {
-                champion_attack_bonus = 0.05 + 0.05 * f64::from(champion_innate_tier);
-                if is_boss {
-                    champion_attack_bonus = champion_attack_bonus * 2.0;
-                }
-                champion_defense_bonus = champion_attack_bonus;
-            }
-            "Donovan" => {
-                match champion_innate_tier {
-                    1u8 => {
-                        champion_attack_bonus = 0.05 * f64::from(num_spellcasters);
-                    }
-                    2u8 => {
-                        champion_attack_bonus = 0.08 * f64::from(num_spellcasters);
-                    }
-                    3u8 => {
-                        champion_attack_bonus = 0.10 * f64::from(num_spellcasters);
-                    }
-                    4u8 => {
-                        champion_attack_bonus = 0.14 * f64::from(num_spellcasters);
-                    }
-                    _ => (),
-                }
-
-                for hero in &mut self.heroes {
-                    hero.hp = hero.hp
-                        * (1.0
-                            + (0.04
-                                + 0.01 * f64::from(champion_innate_tier)
-                                + 0.02 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)))
-                                * f64::from(num_fighters));
-                    hero.critical_chance = hero.critical_chance
-                        + (0.02
-                            + 0.01 * f64::from(champion_innate_tier)
-                            + 0.01 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)))
-                            * f64::from(num_rogues);
-                    hero.evasion = hero.evasion
-                        + (0.02
-                            + 0.01 * f64::from(champion_innate_tier)
-                            + 0.01 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)))
-                            * f64::from(num_rogues);
-                    if hero.class == "Mercenary" {
-                        // it looks like mercenaries get an extra 1.25x cause of the +25% effect from champ skills
-                        hero.hp *= 1.25;
-                        hero.critical_chance *= 1.25;
-                        hero.evasion *= 1.25;
-                    }
-                }
-            }
-            "Hemma" => {
-                for hero in &mut self.heroes {
-                    hero.hp = hero.hp
-                        * (1.0
-                            + 0.05 * f64::from(champion_innate_tier)
-                            + 0.05 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)));
-                    if hero.class == "Mercenary" {
-                        hero.hp *= 1.328;
-                    }
-                }
-                hemma_mult = 0.04 + f64::from(champion_innate_tier) * 0.02;
-            }
-            "Lilu" => {
-                for hero in &mut self.heroes {
-                    hero.hp = hero.hp * (1.05 + 0.05 * f64::from(champion_innate_tier));
-                    if hero.class == "Mercenary" {
-                        hero.hp *= 1.25;
-                    }
-                }
-            }
-            "Polonia" => {
-                champion_defense_bonus = 0.05 + 0.05 * f64::from(champion_innate_tier);
-                for hero in &mut self.heroes {
-                    if champion_innate_tier < 3 {
-                        hero.evasion = hero.evasion + 0.05 * 1.25;
-                    } else {
-                        hero.evasion = hero.evasion + 0.1 * 1.25;
-                    }
-
-                    if hero.class == "Mercenary" {
-                        hero.evasion *= 1.25;
-                    }
-                }
-                count_loot = true;
-                match champion_innate_tier {
-                    1u8 => loot_chance = 0.3,
-                    2u8 => loot_chance = 0.35,
-                    3u8 => loot_chance = 0.4,
-                    4u8 => loot_chance = 0.5,
-                    _ => (),
-                }
-                loot_chance = loot_chance + f64::from(num_tricksters) * 0.02;
-                polonia_loot_cap = polonia_loot_cap + num_tricksters * 2;
-            }
-            "Sia" => {
-                champion_attack_bonus = 0.05 + 0.05 * f64::from(champion_innate_tier);
-            }
-            "Yami" => {
-                for hero in &mut self.heroes {
-                    hero.critical_chance =
-                        hero.critical_chance + 0.05 * f64::from(champion_innate_tier);
-                    hero.evasion = hero.evasion + 0.05 * f64::from(champion_innate_tier);
-                    if hero.class == "Mercenary" {
-                        hero.critical_chance *= 1.25;
-                        hero.evasion *= 1.25;
-                    }
-                }
-            }
-            _ => (),
+        // Calculate Champion Bonuses
+        for i in 0..self.heroes.len() {
+            let is_mercenary = self.heroes[i].has_class_effect(ClassEffect::ChampionAuraBoost);
+            champion_attack_bonuses.push(self.get_champion_aura_value(
+                AuraEffect::Attack,
+                is_boss,
+                is_mercenary,
+            ));
+            champion_defense_bonuses.push(self.get_champion_aura_value(
+                AuraEffect::Defense,
+                is_boss,
+                is_mercenary,
+            ));
+            let hp_bonus = self.get_champion_aura_value(AuraEffect::HP, is_boss, is_mercenary);
+            let crit_chance_bonus =
+                self.get_champion_aura_value(AuraEffect::CritChance, is_boss, is_mercenary);
+            let evasion_bonus =
+                self.get_champion_aura_value(AuraEffect::Evasion, is_boss, is_mercenary);
+            let hp_multiplier =
+                self.get_champion_mercenary_stat_multiplier(AuraEffect::HP, is_mercenary);
+            let crit_chance_multiplier =
+                self.get_champion_mercenary_stat_multiplier(AuraEffect::CritChance, is_mercenary);
+            let evasion_multiplier =
+                self.get_champion_mercenary_stat_multiplier(AuraEffect::Evasion, is_mercenary);
+
+            let hero = &mut self.heroes[i];
+            hero.hp = hero.hp * (1.0 + hp_bonus) * hp_multiplier;
+            hero.hp_max = hero.hp_max * (1.0 + hp_bonus) * hp_multiplier;
+            hero.critical_chance =
+                (hero.critical_chance + crit_chance_bonus) * crit_chance_multiplier;
+            hero.evasion = (hero.evasion + evasion_bonus) * evasion_multiplier;
         }
 
+        let count_loot = self.has_champion_aura_effect(AuraEffect::LootChance);
+        let loot_chance = self.get_champion_aura_value(AuraEffect::LootChance, is_boss, false);
+        let loot_cap = self.get_champion_aura_value(AuraEffect::LootCap, is_boss, false) as u8;
+
         // Calculate Booster Bonuses
         match self.booster {
             Some(booster_type) => match booster_type {
@@ -266,57 +233,34 @@ impl Team {
         }
 
         // Apply Champion and Booster Bonuses
-        for hero in &mut self.heroes {
-            if hero.class == "Mercenary" {
-                champion_attack_bonus *= 1.25;
-                champion_defense_bonus *= 1.25;
-            }
-            hero.attack = hero.attack * (1.0 + champion_attack_bonus + booster_attack_bonus);
-            hero.defense = hero.defense * (1.0 + champion_defense_bonus + booster_defense_bonus);
+        for (i, hero) in self.heroes.iter_mut().enumerate() {
+            hero.attack = hero.attack * (1.0 + champion_attack_bonuses[i] + booster_attack_bonus);
+            hero.defense =
+                hero.defense * (1.0 + champion_defense_bonuses[i] + booster_defense_bonus);
         }
 
-        return (hemma_mult, count_loot, loot_chance, polonia_loot_cap);
+        return (count_loot, loot_chance, loot_cap);
     }
 
-    pub fn initialize_survive_chance_hemma_guaranteed_crit_and_berserker_stage(&mut self) {
+    pub fn initialize_survive_chance_drain_bonus_guaranteed_crit_and_berserker_stage(&mut self) {
         for hero in &mut self.heroes {
             hero.survive_chance = f64::from(hero.armadillo_qty) * 15.0 / 100.0;
-            if hero.class == "Cleric" || hero.class == "Bishop" {
-                hero.survive_chance = 1.2;
-            }
 
-            if hero.class == "Hemma" {
-                hero.hemma_bonus = 0.0;
-            }
+            hero.drain_bonus = 0.0;
 
             hero.berserker_stage = 0;
             hero.guaranteed_crit = false;
         }
     }
 
-    pub fn apply_class_special_effects(&mut self) {
+    pub fn apply_class_special_effects(&mut self, combat_log: &mut CombatLog) {
         // Check team for certain classes with special effects
         for hero in &mut self.heroes {
-            // Ninja Check
-            if hero.class == "Ninja" || hero.class == "Sensei" {
-                hero.ninja_bonus = 0.1 + f64::from(std::cmp::min(hero.innate_tier, 4)) * 0.1;
-                hero.ninja_evasion = 0.15;
-                if hero.innate_tier == 3 {
-                    hero.ninja_evasion = 0.20;
-                }
-                if hero.innate_tier == 4 {
-                    hero.ninja_evasion = 0.25;
-                }
-            }
-
-            // Daimyo Check
-            if hero.class == "Daimyo" {
-                hero.guaranteed_evade = true;
-            }
-
             hero.lost_innate = -5;
 
             hero.consecutive_crit_bonus = 0.0;
+
+            hero.on_fight_start(combat_log);
         }
     }
 
@@ -359,29 +303,14 @@ impl Team {
         return target_chance_heroes;
     }
 
-    pub fn update_ninja_bonus_and_extreme_crit_bonus(
+    pub fn apply_round_start_class_effects_and_extreme_crit_bonus(
         &mut self,
         round: i16,
         is_extreme: bool,
-    ) -> Vec<String> {
-        let mut log_queue: Vec<String> = vec![];
+        combat_log: &mut CombatLog,
+    ) {
         for hero in &mut self.heroes {
-            if hero.class == "Sensei" && hero.lost_innate == round - 2 {
-                hero.ninja_bonus = 0.1 + f64::from(std::cmp::min(hero.innate_tier, 4)) * 0.1;
-                hero.ninja_evasion = 0.15;
-                if hero.innate_tier == 3 {
-                    hero.ninja_evasion = 0.20;
-                }
-                if hero.innate_tier == 4 {
-                    hero.ninja_evasion = 0.25;
-                }
-                log_queue.push(f!(
-                    "Setting ninja_bonus to {} and ninja_evasion to {} for hero {}",
-                    hero.ninja_bonus,
-                    hero.ninja_evasion,
-                    hero.identifier
-                ));
-            }
+            hero.on_round_start(round, combat_log);
 
             hero.extreme_crit_bonus = 0.0;
 
@@ -390,14 +319,12 @@ impl Team {
             {
                 hero.extreme_crit_bonus = -25.0
                     * (hero.evasion + f64::from(hero.berserker_stage) * 0.1 + hero.ninja_evasion);
-                log_queue.push(f!(
-                    "Setting extreme_crit_bonus to {} for hero {}",
-                    hero.extreme_crit_bonus,
-                    hero.identifier
-                ));
+                combat_log.record(|| CombatEvent::ExtremeCritBonus {
+                    hero: hero.identifier.to_string(),
+                    extreme_crit_bonus: hero.extreme_crit_bonus,
+                });
             }
         }
-        return log_queue;
     }
 
     pub fn calculate_mob_attack(
@@ -411,44 +338,41 @@ impl Team {
         target_chance_heroes: Vec<f64>,
         crit_chance: f64,
         crit_chance_modifier: f64,
-    ) -> (usize, bool, bool, Vec<String>, String) {
+        rng: &mut StdRng,
+        combat_log: &mut CombatLog,
+    ) -> (usize, bool, bool, String) {
         let mut panic_msg: String = String::from("");
-        let mut log_queue: Vec<String> = vec![];
-        let mut rng = thread_rng();
 
         let lord_present: bool;
         let lord_alive: bool;
         let lord_index: usize;
 
-        log_queue.push("Calculating Mob Attack".to_string());
-        match self.get_class_index("Lord".to_string()) {
+        match self.get_class_effect_index(ClassEffect::ProtectAlly) {
             Some(index) => {
                 if self.heroes[index].hp <= 0.0 {
                     lord_present = true;
                     lord_alive = false;
                     lord_index = index;
-                    log_queue.push(f!("Found Lord but not Alive in Team at {}", index));
                 } else {
                     lord_present = true;
                     lord_alive = true;
                     lord_index = index;
-                    log_queue.push(f!("Found Lord Alive in Team at {}", index));
                 }
             }
             _ => {
                 lord_present = false;
                 lord_alive = false;
                 lord_index = 0;
-                log_queue.push("No Lord Found Alive in Team".to_string());
             }
         }
 
         if rng.gen::<f64>() < aoe_chance && heroes_alive > 1 {
-            log_queue.push("Mob Attempting AOE Attack".to_string());
+            combat_log.record(|| CombatEvent::AoE);
             // Have to do two iterations through self.heroes, the first is to apply damage, the second is to check for death / lord save. This is because I can't borrow a mutable reference to the lord while also borrowing heroes itself
             // Apply Damage
             for hero in &mut self.heroes {
                 if hero.hp > 0.0 {
+                    hero.accuracy_stats.attacks_received += 1;
                     if hero.guaranteed_evade
                         || rng.gen::<f64>()
                             < f64::min(
@@ -458,36 +382,23 @@ impl Team {
                                 hero.evasion_cap,
                             )
                     {
-                        log_queue.push(f!("Hero {} evades AOE attack", hero.identifier));
-                        hero.dodges += 1;
-                        if hero.class == "Dancer" || hero.class == "Acrobat" {
-                            log_queue.push(f!("Hero {} gains guaranteed crit", hero.identifier));
-                            hero.guaranteed_crit = true;
-                        }
+                        combat_log.record(|| CombatEvent::Evade {
+                            hero: hero.identifier.to_string(),
+                        });
+                        hero.accuracy_stats.dodges += 1;
+                        hero.on_evade(combat_log);
                     } else {
                         let damage = (hero.damage_taken_when_hit * aoe_damage).ceil();
-                        log_queue.push(f!(
-                            "Hero {} is hit by AOE, takes damage {:.2}, new health: {:.2}. checking if Hero is sensei and didn't already lost innate last round",
-                            hero.identifier,
-                            damage,
-                            hero.hp - damage
-                        ));
                         hero.hp -= damage;
+                        combat_log.record(|| CombatEvent::HeroDamaged {
+                            hero: hero.identifier.to_string(),
+                            damage,
+                            hp: hero.hp,
+                            crit: false,
+                        });
 
-                        // Check if innate lost
-                        if hero.class == "Sensei" && hero.lost_innate != round - 1 {
-                            log_queue.push(f!(
-                                "Hero {} is sensei and lost innate due to taking damage",
-                                hero.identifier
-                            ));
-                            hero.lost_innate = round;
-                        }
+                        hero.on_hit_taken(round, combat_log);
                     }
-                } else {
-                    log_queue.push(f!(
-                        "Hero {} is already at 0 hp so not damaged by AOE",
-                        hero.identifier
-                    ));
                 }
             }
 
@@ -503,44 +414,33 @@ impl Team {
             // Check for Death / Lord Save
             for hero in &mut self.heroes {
                 if hero.hp <= 0.0 {
-                    log_queue.push(f!("Hero {} hp reduced to 0", hero.identifier));
-                    if rng.gen::<f64>() >= hero.survive_chance {
+                    if !hero.on_fatal_blow(rng, combat_log) {
                         // Surviving Fatal Blow did not activate
-                        log_queue.push(f!(
-                            "Hero {} did not survive fatal blow, checking for Lord save",
-                            hero.identifier
-                        ));
                         if lord_present
                             && lord_alive
                             && lord_save
-                            && hero.class != "Lord"
+                            && !hero.has_class_effect(ClassEffect::ProtectAlly)
                             && lord_hp > 0.0
                         {
                             // Lord Saves
-                            log_queue.push(f!("Hero {} saved by Lord", hero.identifier));
                             lord_save = false;
                             hero.hp += (hero.damage_taken_when_hit * aoe_damage).ceil();
                             lord_hp -= (lord_dmg_taken_when_hit * aoe_damage).ceil();
-                            log_queue.push(f!(
-                                "Hero {} now has HP of {:.2}, Lord {} now has HP of {:.2}",
-                                hero.identifier,
-                                hero.hp,
-                                lord_identifier,
-                                lord_hp
-                            ));
+                            combat_log.record(|| CombatEvent::LordSave {
+                                hero: hero.identifier.to_string(),
+                                lord: lord_identifier.to_string(),
+                                hero_hp: hero.hp,
+                                lord_hp,
+                            });
                         } else {
                             // lord doesnt save
-                            log_queue.push(f!("Hero {} dies", hero.identifier));
+                            combat_log.record(|| CombatEvent::Death {
+                                hero: hero.identifier.to_string(),
+                            });
                             hero.hp = 0.0;
                             heroes_alive -= 1;
                             update_target = true;
                         }
-                    } else {
-                        // Surviving Fatal Blow Activated
-                        log_queue
-                            .push(f!("Hero {} survived fatal blow with 1 HP", hero.identifier));
-                        hero.hp = 1.0;
-                        hero.survive_chance = 0.0;
                     }
                 }
             }
@@ -548,18 +448,14 @@ impl Team {
             // Check if Lord Died while Saving
             if lord_hp <= 0.0 && lord_hp != self.heroes[lord_index].hp {
                 // Lord Dies in Saving
-                log_queue.push(f!("Lord {} dies while saving a hero", lord_identifier));
-                if rng.gen::<f64>() >= self.heroes[lord_index].survive_chance {
+                if !self.heroes[lord_index].on_fatal_blow(rng, combat_log) {
                     // Surviving Fatal Blow did not activate
-                    log_queue.push(f!("Lord {} did not survive fatal blow", lord_identifier));
+                    combat_log.record(|| CombatEvent::Death {
+                        hero: lord_identifier.to_string(),
+                    });
                     self.heroes[lord_index].hp = 0.0;
                     heroes_alive -= 1;
                     update_target = true;
-                } else {
-                    // Surviving Fatal Blow Activated
-                    log_queue.push(f!("Lord {} survived fatal blow with 1 HP", lord_identifier));
-                    self.heroes[lord_index].hp = 1.0;
-                    self.heroes[lord_index].survive_chance = 0.0;
                 }
             } else {
                 // Lord survived, save HP out
@@ -567,7 +463,6 @@ impl Team {
             }
         } else {
             // Mob attacks only one hero
-            log_queue.push("Mob Attempting Single Target Attack".to_string());
             let mut target_opt: Option<usize> = None;
             let mut target_rng: f64 = rng.gen::<f64>();
             for i in 0..target_chance_heroes.len() {
@@ -582,11 +477,10 @@ impl Team {
                 Some(target) => {
                     // check hit/evade
                     // let hero = &mut self.heroes[target];
-                    log_queue.push(f!(
-                        "Mob is targeting hero at index {}: Hero {} ",
-                        target,
-                        self.heroes[target].identifier
-                    ));
+                    combat_log.record(|| CombatEvent::MobAttack {
+                        target: self.heroes[target].identifier.to_string(),
+                    });
+                    self.heroes[target].accuracy_stats.attacks_received += 1;
                     if self.heroes[target].guaranteed_evade
                         || rng.gen::<f64>()
                             < f64::min(
@@ -596,25 +490,12 @@ impl Team {
                                 self.heroes[target].evasion_cap,
                             )
                     {
-                        log_queue.push(f!(
-                            "Hero {} evades single target attack",
-                            self.heroes[target].identifier
-                        ));
-                        self.heroes[target].dodges += 1;
-                        if self.heroes[target].class == "Danger"
-                            || self.heroes[target].class == "Acrobat"
-                        {
-                            log_queue.push(f!(
-                                "Hero {} gains guaranteed crit",
-                                self.heroes[target].identifier
-                            ));
-                            self.heroes[target].guaranteed_crit = true;
-                        }
+                        combat_log.record(|| CombatEvent::Evade {
+                            hero: self.heroes[target].identifier.to_string(),
+                        });
+                        self.heroes[target].accuracy_stats.dodges += 1;
+                        self.heroes[target].on_evade(combat_log);
                     } else {
-                        log_queue.push(f!(
-                            "Hero {} is hit by single target attack, checking for crit damage",
-                            self.heroes[target].identifier
-                        ));
                         // Hit, check crit
                         let was_crit: bool;
                         if rng.gen::<f64>()
@@ -624,47 +505,36 @@ impl Team {
                             // not crit
                             was_crit = false;
                             self.heroes[target].hp -= self.heroes[target].damage_taken_when_hit;
-                            log_queue.push(f!(
-                                "Hero {} is hit with a NORMAL attack, takes {:.2} damage bringing hp to {:.2}",
-                                self.heroes[target].identifier,
-                                self.heroes[target].damage_taken_when_hit,
-                                self.heroes[target].hp
-                            ));
+                            combat_log.record(|| CombatEvent::HeroDamaged {
+                                hero: self.heroes[target].identifier.to_string(),
+                                damage: self.heroes[target].damage_taken_when_hit,
+                                hp: self.heroes[target].hp,
+                                crit: false,
+                            });
                         } else {
                             was_crit = true;
                             self.heroes[target].hp -=
                                 self.heroes[target].crit_damage_taken_when_hit;
-                            self.heroes[target].crits_taken += 1;
-                            log_queue.push(f!(
-                                "Hero {} is hit with a CRITICAL attack, takes {:.2} damage bringing hp to {:.2}",
-                                self.heroes[target].identifier,
-                                self.heroes[target].crit_damage_taken_when_hit,
-                                self.heroes[target].hp
-                            ));
+                            self.heroes[target].accuracy_stats.crits_taken += 1;
+                            combat_log.record(|| CombatEvent::HeroDamaged {
+                                hero: self.heroes[target].identifier.to_string(),
+                                damage: self.heroes[target].crit_damage_taken_when_hit,
+                                hp: self.heroes[target].hp,
+                                crit: true,
+                            });
                         }
 
                         if self.heroes[target].hp <= 0.0 {
-                            log_queue.push(f!(
-                                "Hero {} hp reduced to 0",
-                                self.heroes[target].identifier
-                            ));
-                            if rng.gen::<f64>() >= self.heroes[target].survive_chance {
+                            if !self.heroes[target].on_fatal_blow(rng, combat_log) {
                                 // surviving fatal blow did not activate
-                                log_queue.push(f!(
-                                    "Hero {} did not survive fatal blow, checking for lord save",
-                                    self.heroes[target].identifier
-                                ));
                                 if lord_present
                                     && lord_alive
                                     && lord_save
-                                    && self.heroes[target].class != "Lord"
+                                    && !self.heroes[target]
+                                        .has_class_effect(ClassEffect::ProtectAlly)
                                     && self.heroes[lord_index].hp > 0.0
                                 {
                                     // Lord Saves
-                                    log_queue.push(f!(
-                                        "Hero {} is saved by lord",
-                                        self.heroes[target].identifier
-                                    ));
                                     lord_save = false;
                                     let damage_transfered = if was_crit {
                                         self.heroes[target].crit_damage_taken_when_hit
@@ -673,74 +543,39 @@ impl Team {
                                     };
                                     self.heroes[target].hp += damage_transfered;
                                     self.heroes[lord_index].hp -= damage_transfered;
-                                    log_queue.push(f!(
-                                        "Hero {} now has HP of {:.2}, Lord {} now has HP of {:.2}",
-                                        self.heroes[target].identifier,
-                                        self.heroes[target].hp,
-                                        self.heroes[lord_index].identifier,
-                                        self.heroes[lord_index].hp
-                                    ));
+                                    combat_log.record(|| CombatEvent::LordSave {
+                                        hero: self.heroes[target].identifier.to_string(),
+                                        lord: self.heroes[lord_index].identifier.to_string(),
+                                        hero_hp: self.heroes[target].hp,
+                                        lord_hp: self.heroes[lord_index].hp,
+                                    });
                                     if self.heroes[lord_index].hp <= 0.0 {
                                         // lord dies in saving
-                                        log_queue.push(f!(
-                                            "Lord {} dies while saving hero {}",
-                                            self.heroes[lord_index].identifier,
-                                            self.heroes[target].identifier
-                                        ));
-                                        if rng.gen::<f64>()
-                                            >= self.heroes[lord_index].survive_chance
-                                        {
+                                        if !self.heroes[lord_index].on_fatal_blow(rng, combat_log) {
                                             // surviving fatal blow did not activate
-                                            log_queue.push(f!(
-                                                "Lord {} did not survive fatal blow",
-                                                self.heroes[lord_index].identifier
-                                            ));
+                                            combat_log.record(|| CombatEvent::Death {
+                                                hero: self.heroes[lord_index]
+                                                    .identifier
+                                                    .to_string(),
+                                            });
                                             self.heroes[lord_index].hp = 0.0;
                                             heroes_alive -= 1;
                                             update_target = true;
-                                        } else {
-                                            // survive fatal blow
-                                            log_queue.push(f!(
-                                                "Lord {} survived fatal blow with 1 HP",
-                                                self.heroes[lord_index].identifier
-                                            ));
-                                            self.heroes[lord_index].hp = 1.0;
-                                            self.heroes[lord_index].survive_chance = 0.0;
                                         }
                                     }
                                 } else {
                                     // lord doesnt save
-                                    log_queue
-                                        .push(f!("Hero {} dies", self.heroes[target].identifier));
+                                    combat_log.record(|| CombatEvent::Death {
+                                        hero: self.heroes[target].identifier.to_string(),
+                                    });
                                     self.heroes[target].hp = 0.0;
                                     heroes_alive -= 1;
                                     update_target = true;
                                 }
-                            } else {
-                                // surviving fatal blow activated
-                                log_queue.push(f!(
-                                    "Hero {} survived fatal blow with 1 HP",
-                                    self.heroes[target].identifier
-                                ));
-                                self.heroes[target].hp = 1.0;
-                                self.heroes[target].survive_chance = 0.0;
                             }
                         }
 
-                        // check sensei lost innate
-                        log_queue.push(f!(
-                            "Checking if Hero {} is sensei and didn't already lose innate last round",
-                            self.heroes[target].identifier
-                        ));
-                        if self.heroes[target].class == "Sensei"
-                            && self.heroes[target].lost_innate != round - 1
-                        {
-                            log_queue.push(f!(
-                                "Hero {} is sensei and lost innate due to taking damage",
-                                self.heroes[target].identifier
-                            ));
-                            self.heroes[target].lost_innate = round;
-                        }
+                        self.heroes[target].on_hit_taken(round, combat_log);
                     }
                 }
                 None => {
@@ -749,131 +584,109 @@ impl Team {
             };
         }
 
-        return (heroes_alive, lord_save, update_target, log_queue, panic_msg);
+        return (heroes_alive, lord_save, update_target, panic_msg);
     }
 
-    pub fn calculate_hemma_drain(
+    /// The champion drains hp from each other hero above the drain share of their max hp, gaining attack for each, then heals itself
+    pub fn calculate_champion_drain_and_self_heal(
         &mut self,
-        champion_innate_tier: u8,
-        hemma_mult: f64,
         round: i16,
-    ) -> Vec<String> {
-        let mut log_queue: Vec<String> = vec![];
-        log_queue.push("Calculating Hemma Drain".to_string());
-        let mut hemma_index = 0usize;
-        match self.get_class_index("Hemma".to_string()) {
-            Some(index) => hemma_index = index,
-            _ => (),
-        }
-        log_queue.push(f!(
-            "Hemma found at index {} and has hp {:.2}",
-            hemma_index,
-            self.heroes[hemma_index].hp
-        ));
-
-        if self.heroes[hemma_index].hp > 0.0 {
-            log_queue.push("Hemma is alive".to_string());
-            let mut hemma_hero = self.heroes[hemma_index].clone();
-            for (i, hero) in self.heroes.iter_mut().enumerate() {
-                if i != hemma_index
-                    && hero.hp > (0.11 - 0.01 * f64::from(champion_innate_tier)) * hero.hp_max
-                {
-                    log_queue.push(f!(
-                        "Hero {} is not hemma and has enough HP ({:.2}) to steal",
-                        hero.identifier,
-                        hero.hp
-                    ));
-                    hemma_hero.hemma_bonus += hemma_hero.attack * hemma_mult;
-                    hero.hp =
-                        hero.hp - (0.11 - 0.01 * f64::from(champion_innate_tier)) * hero.hp_max;
-                    if hero.class == "Sensei" && hero.lost_innate != round - 1 {
-                        log_queue.push(f!("Hero {} is sensei and loses innate", hero.identifier));
-                        hero.lost_innate = round;
+        is_boss: bool,
+        combat_log: &mut CombatLog,
+    ) {
+        let champion_index = self.champion_index;
+        let drain_hp = self.get_champion_aura_value(AuraEffect::DrainHP, is_boss, false);
+        let drain_attack = self.get_champion_aura_value(AuraEffect::DrainAttack, is_boss, false);
+        let self_heal = self.get_champion_aura_value(AuraEffect::SelfHeal, is_boss, false);
+        if self.heroes[champion_index].hp > 0.0 {
+            if self.has_champion_aura_effect(AuraEffect::DrainHP) {
+                let mut champion = self.heroes[champion_index].clone();
+                for (i, hero) in self.heroes.iter_mut().enumerate() {
+                    if i != champion_index && hero.hp > drain_hp * hero.hp_max {
+                        champion.drain_bonus += champion.attack * drain_attack;
+                        hero.hp = hero.hp - drain_hp * hero.hp_max;
+                        hero.on_hit_taken(round, combat_log);
+                        combat_log.record(|| CombatEvent::ChampionDrain {
+                            hero: hero.identifier.to_string(),
+                            hp: hero.hp,
+                            drain_bonus: champion.drain_bonus,
+                        });
                     }
-                    log_queue.push(f!(
-                        "New hemma bonus is {}, new Hero {} HP is {:.2}",
-                        hemma_hero.hemma_bonus,
-                        hero.identifier,
-                        hero.hp
-                    ));
                 }
+                self.heroes[champion_index] = champion;
             }
-            self.heroes[hemma_index] = hemma_hero;
-            self.heroes[hemma_index].hp = f64::min(
-                self.heroes[hemma_index].hp
-                    + 1.0
-                    + f64::from(champion_innate_tier)
-                    + f64::from(std::cmp::min(champion_innate_tier - 2, 0))
-                    + f64::from(std::cmp::min(champion_innate_tier - 3, 0)),
-                self.heroes[hemma_index].hp_max,
+            self.heroes[champion_index].hp = f64::min(
+                self.heroes[champion_index].hp + self_heal,
+                self.heroes[champion_index].hp_max,
             );
-            log_queue.push(f!(
-                "(Meta-Info) Hemma Hero saved back to team heroes vector, and now has hp of {:.2}",
-                self.heroes[hemma_index].hp
-            ));
         }
+    }
 
-        return log_queue;
+    /// Set each hero's crit chance and barrier damage bonuses from the champion's aura for the round, as they can last only the first rounds
+    pub fn update_round_aura_bonuses(
+        &mut self,
+        round: i16,
+        is_boss: bool,
+        combat_log: &mut CombatLog,
+    ) {
+        if !self.heroes[self.champion_index]
+            .aura
+            .iter()
+            .any(|modifier| modifier.is_timed())
+            && !self.has_champion_aura_effect(AuraEffect::BarrierDamage)
+        {
+            return;
+        }
+        for i in 0..self.heroes.len() {
+            let is_mercenary = self.heroes[i].has_class_effect(ClassEffect::ChampionAuraBoost);
+            self.heroes[i].aura_crit_bonus = self.get_champion_timed_aura_value(
+                AuraEffect::CritChance,
+                round,
+                is_boss,
+                is_mercenary,
+            );
+            self.heroes[i].aura_barrier_damage_bonus =
+                self.get_champion_aura_value(AuraEffect::BarrierDamage, is_boss, is_mercenary)
+                    + self.get_champion_timed_aura_value(
+                        AuraEffect::BarrierDamage,
+                        round,
+                        is_boss,
+                        is_mercenary,
+                    );
+        }
+        combat_log.record(|| CombatEvent::ChampionRoundBonus {
+            crit_chance: self.get_champion_timed_aura_value(
+                AuraEffect::CritChance,
+                round,
+                is_boss,
+                false,
+            ),
+            barrier_damage: self.get_champion_aura_value(AuraEffect::BarrierDamage, is_boss, false)
+                + self.get_champion_timed_aura_value(
+                    AuraEffect::BarrierDamage,
+                    round,
+                    is_boss,
+                    false,
+                ),
+        });
     }
 
-    pub fn calculate_berserker_ninja_samurai_round_effects(&mut self, round: i16) -> Vec<String> {
-        let mut log_queue: Vec<String> = vec![];
-        log_queue.push("Calculating berserker, ninja, samurai round effects".to_string());
+    pub fn apply_class_effects_before_heroes_attack(
+        &mut self,
+        round: i16,
+        combat_log: &mut CombatLog,
+    ) {
         for hero in &mut self.heroes {
-            // Check Berserker Activation
-            if hero.class == "Berserker" || hero.class == "Jarl" {
-                log_queue.push(f!("Hero {} is class {}", hero.identifier, hero.class));
-                if hero.hp >= hero.jarl_hp_stage_1 * hero.hp_max {
-                    hero.berserker_stage = 0;
-                } else if hero.hp >= hero.jarl_hp_stage_2 * hero.hp_max {
-                    hero.berserker_stage = 1;
-                } else if hero.hp >= hero.jarl_hp_stage_3 * hero.hp_max {
-                    hero.berserker_stage = 2;
-                } else if hero.hp > 0.0 {
-                    hero.berserker_stage = 3;
-                }
-                log_queue.push(f!(
-                    "Hero {} now has berserker_stage of {}",
-                    hero.identifier,
-                    hero.berserker_stage
-                ));
-            }
-
-            // Ninja Check
-            if hero.class == "Ninja" && hero.hp < hero.hp_max {
-                hero.ninja_bonus = 0.0;
-                hero.ninja_evasion = 0.0;
-                log_queue.push(f!(
-                    "Hero {} is ninja and loses bonus because hp < hp_max",
-                    hero.identifier
-                ));
-            }
-
-            if hero.class == "Sensei" && hero.lost_innate == round {
-                hero.ninja_bonus = 0.0;
-                hero.ninja_evasion = 0.0;
-                log_queue.push(f!(
-                    "Hero {} is sensei and loses bonus because they lost_innate this round",
-                    hero.identifier
-                ));
-            }
-
-            // Samurai Check
-            if round == 1 && (hero.class == "Samurai" || hero.class == "Daimyo") {
-                hero.guaranteed_crit = true;
-                hero.guaranteed_evade = false;
-                log_queue.push(f!("Hero {} is class {} and gains guaranteed_crit while losing guaranteed_evade as round = 1", hero.identifier, hero.class));
-            }
+            hero.on_before_attack(round, combat_log);
         }
-
-        return log_queue;
     }
 
+    /// Each living hero attacks the encounter in attack order
+    /// Returns (champion loot, barrier modifier, barrier hp, encounter hp, shark active, the hero that broke the barrier this round)
     pub fn calculate_heroes_attack(
         &mut self,
         attack_order: Vec<usize>,
         round: i16,
-        rudo_bonus: f64,
         mut shark_active: i32,
         dinosaur_active: i32,
         mut barrier_modifier: f64,
@@ -885,36 +698,30 @@ impl Team {
         barrier_hp_max: f64,
         encounter_hp_max: f64,
         barrier_type: Option<ElementType>,
-    ) -> (u8, f64, f64, f64, i32, Vec<String>) {
-        let mut log_queue: Vec<String> = vec![];
-        log_queue.push("Calculate Heroes Attack".to_string());
+        rng: &mut StdRng,
+        combat_log: &mut CombatLog,
+    ) -> (u8, f64, f64, f64, i32, Option<String>) {
+        let mut champion_loot: u8 = 0;
+        let mut barrier_broken_by: Option<String> = None;
 
-        let mut polonia_loot: u8 = 0;
-        let mut rng = thread_rng();
-
-        log_queue.push(f!("Attack order is {:?}", attack_order));
         for i in 0..self.get_heroes_len() {
             let jj = attack_order[i];
             let hero = &mut self.heroes[jj];
 
-            log_queue.push(f!("Hero {} has hp of {:.2}", hero.identifier, hero.hp));
             if hero.hp > 0.0 {
-                log_queue.push(f!("Hero {} attempts to attack", hero.identifier));
+                hero.accuracy_stats.attacks_made += 1;
                 if rng.gen::<f64>() > encounter_evasion {
                     // hit mob, check crit
-                    log_queue.push(f!("Hero {} hits mob, checking crit", hero.identifier));
                     if hero.guaranteed_crit
-                        || rng.gen::<f64>() < hero.critical_chance + hero.ninja_bonus + rudo_bonus
+                        || rng.gen::<f64>()
+                            < hero.critical_chance + hero.ninja_bonus + hero.aura_crit_bonus
                     {
                         // crit, if samurai variant ignore barrier else reduce damage by barrier mod
-                        hero.crits_dealt += 1;
-                        log_queue.push(f!(
-                            "Hero {} hits with a CRITICAL hit, calculating damage",
-                            hero.identifier
-                        ));
+                        hero.accuracy_stats.crits_dealt += 1;
                         let mut damage = (hero.attack
                             * (hero.attack_modifier
                                 + 0.2 * f64::from(hero.mundra_qty)
+                                + hero.class_attack_bonus
                                 + f64::from(shark_active)
                                     * 0.01
                                     * f64::from(hero.shark_qty)
@@ -926,52 +733,31 @@ impl Team {
                                 + 0.1
                                     * f64::from(1 + hero.berserker_level)
                                     * f64::from(hero.berserker_stage))
-                            + hero.hemma_bonus)
+                            + hero.drain_bonus)
                             * (hero.critical_multiplier + hero.consecutive_crit_bonus);
-                        if round != 1 || (hero.class != "Samurai" && hero.class != "Daimyo") {
-                            log_queue.push(f!(
-                                "Hero {} is class {} and round is not 1 so do not pierce elemental barrier. damage *= barrier_modifier: {} * {} = {}",
-                                hero.identifier,
-                                hero.class,
-                                damage,
-                                barrier_modifier,
-                                damage * barrier_modifier
-                            ));
+                        let pierced_barrier: bool;
+                        if !hero.pierces_barrier_on_crit(round) {
+                            pierced_barrier = false;
                             damage *= barrier_modifier;
                         } else {
-                            log_queue.push(f!(
-                                "Hero {} is class {} and round is 1 so pierce elemental barrier. damage = damage: {}",
-                                hero.identifier,
-                                hero.class,
-                                damage
-                            ));
+                            pierced_barrier = true;
                         }
                         encounter_hp -= damage;
                         hero.damage_dealt += damage;
-                        log_queue.push(f!(
-                            "Hero {} deals damage {:.2} to mob, bringing hp to {:.2}",
-                            hero.identifier,
+                        combat_log.record(|| CombatEvent::Attack {
+                            hero: hero.identifier.to_string(),
                             damage,
-                            encounter_hp
-                        ));
-                        if hero.class == "Conquistador" {
-                            hero.consecutive_crit_bonus =
-                                f64::min(hero.consecutive_crit_bonus + 0.25, 1.0);
-                            log_queue.push(f!(
-                                "Hero {} is conquistador, new consecutive crit bonus is {}",
-                                hero.identifier,
-                                hero.consecutive_crit_bonus
-                            ));
-                        }
+                            encounter_hp,
+                            crit: true,
+                            pierced_barrier,
+                        });
+                        hero.on_crit(combat_log);
                     } else {
                         // not crit, deal damage
-                        log_queue.push(f!(
-                            "Hero {} misses crit, does NORMAL attack",
-                            hero.identifier
-                        ));
                         let damage = (hero.attack
                             * (hero.attack_modifier
                                 + 0.2 * f64::from(hero.mundra_qty)
+                                + hero.class_attack_bonus
                                 + f64::from(shark_active)
                                     * 0.01
                                     * f64::from(hero.shark_qty)
@@ -983,62 +769,58 @@ impl Team {
                                 + 0.1
                                     * f64::from(1 + hero.berserker_level)
                                     * f64::from(hero.berserker_stage))
-                            + hero.hemma_bonus)
+                            + hero.drain_bonus)
                             * barrier_modifier;
                         encounter_hp -= damage;
                         hero.damage_dealt += damage;
-                        log_queue.push(f!(
-                            "Hero {} deals damage {:.2} to mob, bringing hp to {:.2}",
-                            hero.identifier,
+                        combat_log.record(|| CombatEvent::Attack {
+                            hero: hero.identifier.to_string(),
                             damage,
-                            encounter_hp
-                        ));
-                        if hero.class == "Conquistador" {
-                            hero.consecutive_crit_bonus = 0.0;
-                            log_queue.push(f!(
-                                "Hero {} is conquistador, consecutive crit bonus reset to 0",
-                                hero.identifier
-                            ));
-                        }
+                            encounter_hp,
+                            crit: false,
+                            pierced_barrier: false,
+                        });
+                        hero.on_normal_hit(combat_log);
                         if count_loot {
                             if rng.gen::<f64>() < loot_chance {
-                                polonia_loot += 1;
+                                champion_loot += 1;
                             }
-                            log_queue.push(f!("Polonia loot is now {}", polonia_loot));
                         }
 
                         // Damage Barrier
-                        log_queue.push(f!(
-                            "Checking for barrier (type: {:#?}) damage",
-                            barrier_type
-                        ));
+                        let barrier_hp_before = barrier_hp;
                         match barrier_type {
                             Some(barrier_element) => {
-                                if barrier_hp > 0.0 && barrier_element == hero.element_type {
-                                    barrier_hp -= f64::from(hero.element_qty);
-                                    log_queue.push(f!(
-                                        "Hero Matches Element Type, New Barrier HP is {:.2}",
-                                        barrier_hp
-                                    ));
-                                } else if barrier_hp > 0.0 && hero.element_type == ElementType::Any
-                                {
-                                    barrier_hp -= f64::from(hero.element_qty) * 0.3;
-                                    log_queue.push(f!(
-                                        "Hero Has Any Element Type, New Barrier HP is {:.2}",
-                                        barrier_hp
-                                    ));
+                                if barrier_hp > 0.0 {
+                                    barrier_hp -= hero.calculate_barrier_damage(barrier_element);
                                 }
                             }
                             _ => (),
                         }
+                        if barrier_hp != barrier_hp_before {
+                            combat_log.record(|| CombatEvent::BarrierDamaged {
+                                hero: hero.identifier.to_string(),
+                                damage: barrier_hp_before - barrier_hp,
+                                barrier_hp,
+                            });
+                            if barrier_hp <= 0.0 {
+                                barrier_broken_by = Some(hero.identifier.to_string());
+                                combat_log.record(|| CombatEvent::BarrierBroken {
+                                    hero: hero.identifier.to_string(),
+                                });
+                            }
+                        }
                     }
                 } else {
                     // Missed
-                    hero.attacks_missed += 1;
-                    log_queue.push(f!("Hero {} missed attack", hero.identifier));
+                    hero.accuracy_stats.attacks_missed += 1;
+                    combat_log.record(|| CombatEvent::Miss {
+                        hero: hero.identifier.to_string(),
+                    });
                 }
             }
 
+            let barrier_modifier_before = barrier_modifier;
             if barrier_hp <= 0.0 {
                 barrier_modifier = 1.0;
             } else if barrier_hp <= 0.25 * barrier_hp_max {
@@ -1048,114 +830,82 @@ impl Team {
             } else if barrier_hp <= 0.75 * barrier_hp_max {
                 barrier_modifier = 0.4;
             }
-            log_queue.push(f!("Barrier Modifier is now {}", barrier_modifier));
+            if barrier_modifier != barrier_modifier_before {
+                combat_log.record(|| CombatEvent::BarrierModifier {
+                    modifier: barrier_modifier,
+                });
+            }
 
             if encounter_hp < encounter_hp_max / 2.0 {
+                if shark_active == 0 {
+                    combat_log.record(|| CombatEvent::SharkActivated);
+                }
                 shark_active = 1;
-                log_queue.push("Mob now below 50% HP, Shark Activating".to_string());
             }
 
             hero.guaranteed_crit = false;
         }
 
         return (
-            polonia_loot,
+            champion_loot,
             barrier_modifier,
             barrier_hp,
             encounter_hp,
             shark_active,
-            log_queue,
+            barrier_broken_by,
         );
     }
 
-    pub fn calculate_healing(&mut self, champion: String, champion_innate_tier: u8) -> Vec<String> {
-        let mut log_queue: Vec<String> = vec![];
-        log_queue.push("Calculating Healing".to_string());
-        for hero in &mut self.heroes {
+    pub fn calculate_healing(&mut self, is_boss: bool, combat_log: &mut CombatLog) {
+        let has_aura_heal = self.has_champion_aura_effect(AuraEffect::Heal);
+        let aura_heals: Vec<f64> = self
+            .heroes
+            .iter()
+            .map(|hero| {
+                self.get_champion_aura_value(
+                    AuraEffect::Heal,
+                    is_boss,
+                    hero.has_class_effect(ClassEffect::ChampionAuraBoost),
+                )
+            })
+            .collect();
+        for (i, hero) in self.heroes.iter_mut().enumerate() {
             if hero.hp > 0.0 {
-                log_queue.push(f!(
-                    "Hero {} is alive, hp is {:.2}",
-                    hero.identifier,
-                    hero.hp
-                ));
                 let mut before_hp = hero.hp;
 
                 hero.hp = f64::min(before_hp + hero.hp_regen, hero.hp_max);
-                log_queue.push(f!(
-                    "Hero {} regenerates for {:.2}",
-                    hero.identifier,
-                    hero.hp - before_hp
-                ));
+                combat_log.record(|| CombatEvent::Heal {
+                    hero: hero.identifier.to_string(),
+                    source: HealSource::Regeneration,
+                    amount: hero.hp - before_hp,
+                    hp: hero.hp,
+                });
 
                 before_hp = hero.hp;
 
-                if champion == "Lilu" {
-                    match champion_innate_tier {
-                        1 => hero.hp = f64::min(hero.hp + 3.0, hero.hp_max),
-                        2 => hero.hp = f64::min(hero.hp + 5.0, hero.hp_max),
-                        3 => hero.hp = f64::min(hero.hp + 10.0, hero.hp_max),
-                        4 => hero.hp = f64::min(hero.hp + 20.0, hero.hp_max),
-                        _ => (),
-                    }
-                    log_queue.push(f!(
-                        "Hero {} is healed by Lilu for {:.2}",
-                        hero.identifier,
-                        hero.hp - before_hp
-                    ));
-                    // running_hp = hero.hp;
+                if has_aura_heal {
+                    hero.hp = f64::min(hero.hp + aura_heals[i], hero.hp_max);
+                    combat_log.record(|| CombatEvent::Heal {
+                        hero: hero.identifier.to_string(),
+                        source: HealSource::Champion,
+                        amount: hero.hp - before_hp,
+                        hp: hero.hp,
+                    });
                 }
-                log_queue.push(f!(
-                    "Hero {} has new hp of {:.2} total",
-                    hero.identifier,
-                    hero.hp
-                ));
             }
         }
-        return log_queue;
     }
 
-    pub fn check_berserker_activation(&mut self) -> Vec<String> {
-        let mut log_queue: Vec<String> = vec![];
-        log_queue.push("Checking Berserker Activation".to_string());
+    pub fn apply_round_end_class_effects(&mut self, combat_log: &mut CombatLog) {
         for hero in &mut self.heroes {
-            if hero.class == "Berserker" || hero.class == "Jarl" {
-                if hero.hp >= hero.jarl_hp_stage_1 * hero.hp_max {
-                    hero.berserker_stage = 0;
-                } else if hero.hp >= hero.jarl_hp_stage_2 * hero.hp_max {
-                    hero.berserker_stage = 1;
-                } else if hero.hp >= hero.jarl_hp_stage_3 * hero.hp_max {
-                    hero.berserker_stage = 2;
-                } else if hero.hp > 0.0 {
-                    hero.berserker_stage = 3;
-                }
-                log_queue.push(f!(
-                    "Hero {} is class {} and new berserker stage is {}",
-                    hero.identifier,
-                    hero.class,
-                    hero.berserker_stage
-                ));
-            }
+            hero.on_round_end(combat_log);
         }
-        return log_queue;
     }
 
     pub fn get_heroes_len(&self) -> usize {
         return self.heroes.len();
     }
 
-    pub fn get_heroes_hp_as_strings(&self) -> StringVec {
-        let mut res = StringVec(vec![]);
-        for hero in &self.heroes {
-            res.push(f!(
-                "{}: {:.2} ({:.2}%)",
-                hero.identifier,
-                hero.hp,
-                hero.hp / hero.hp_max * 100.0
-            ));
-        }
-        return res;
-    }
-
     pub fn get_heroes_hp(&self) -> Vec<f64> {
         let mut res: Vec<f64> = vec![];
         for hero in &self.heroes {
@@ -1164,33 +914,111 @@ impl Team {
         return res;
     }
 
-    pub fn get_heroes_accuracy_stats(&self) -> (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) {
-        let mut res: (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) = (vec![], vec![], vec![], vec![]);
+    pub fn get_heroes_accuracy_stats(&self) -> Vec<HeroAccuracyStats> {
+        return self.heroes.iter().map(|hero| hero.accuracy_stats).collect();
+    }
 
-        for hero in &self.heroes {
-            res.0.push(hero.crits_taken);
-            res.1.push(hero.crits_dealt);
-            res.2.push(hero.dodges);
-            res.3.push(hero.attacks_missed);
-        }
+    /// Returns the index of the first hero whose class has the effect
+    pub fn get_class_effect_index(&self, effect: ClassEffect) -> Option<usize> {
+        return self
+            .heroes
+            .iter()
+            .position(|hero| hero.has_class_effect(effect));
+    }
 
-        return res;
+    pub fn _get_champion_info(&self) -> (String, u8) {
+        let champion = &self.heroes[self.champion_index];
+        return (champion.class.to_string(), champion.innate_tier);
     }
 
-    pub fn get_class_index(&self, class_name: String) -> Option<usize> {
-        for (i, hero) in self.heroes.iter().enumerate() {
-            if hero.class == class_name {
-                return Some(i);
-            }
+    /// Returns the total value for one hero of the champion's aura modifiers with the effect that last the whole fight, or 0 if its aura does not have the effect
+    pub fn get_champion_aura_value(
+        &self,
+        effect: AuraEffect,
+        is_boss: bool,
+        is_mercenary: bool,
+    ) -> f64 {
+        let champion = &self.heroes[self.champion_index];
+        return champion
+            .aura
+            .iter()
+            .filter(|modifier| modifier.get_effect() == effect && !modifier.is_timed())
+            .map(|modifier| {
+                modifier.get_value(
+                    champion.innate_tier,
+                    self.get_archetype_qty(modifier.get_per_archetype()),
+                    is_boss,
+                    is_mercenary,
+                )
+            })
+            .sum();
+    }
+
+    /// Returns the product of the mercenary stat multipliers of the champion's untimed aura modifiers with the effect, or 1 for a hero that is not a Mercenary
+    fn get_champion_mercenary_stat_multiplier(
+        &self,
+        effect: AuraEffect,
+        is_mercenary: bool,
+    ) -> f64 {
+        if !is_mercenary {
+            return 1.0;
         }
-        return None;
+        let champion = &self.heroes[self.champion_index];
+        return champion
+            .aura
+            .iter()
+            .filter(|modifier| modifier.get_effect() == effect && !modifier.is_timed())
+            .map(|modifier| modifier.get_mercenary_stat_multiplier())
+            .product();
     }
 
-    pub fn get_champion_info(&self) -> (String, u8) {
-        return (self.champion.to_string(), self.champion_innate_tier);
+    /// Returns the total value for one hero of the champion's aura modifiers with the effect that only last the first rounds and still apply in the round
+    fn get_champion_timed_aura_value(
+        &self,
+        effect: AuraEffect,
+        round: i16,
+        is_boss: bool,
+        is_mercenary: bool,
+    ) -> f64 {
+        let champion = &self.heroes[self.champion_index];
+        return champion
+            .aura
+            .iter()
+            .filter(|modifier| {
+                modifier.get_effect() == effect
+                    && modifier.is_timed()
+                    && modifier.is_active_in_round(champion.innate_tier, round)
+            })
+            .map(|modifier| {
+                modifier.get_value(
+                    champion.innate_tier,
+                    self.get_archetype_qty(modifier.get_per_archetype()),
+                    is_boss,
+                    is_mercenary,
+                )
+            })
+            .sum();
     }
 
-    pub fn get_num_archetypes(&self) -> (u8, u8, u8, u8) {
+    pub fn has_champion_aura_effect(&self, effect: AuraEffect) -> bool {
+        return self.heroes[self.champion_index]
+            .aura
+            .iter()
+            .any(|modifier| modifier.get_effect() == effect);
+    }
+
+    /// Returns the number of heroes of the archetype on the team, or 0 if there is no archetype
+    fn get_archetype_qty(&self, archetype: Option<AuraArchetype>) -> u8 {
+        return match archetype {
+            Some(AuraArchetype::Fighter) => self.num_fighters,
+            Some(AuraArchetype::Rogue) => self.num_rogues,
+            Some(AuraArchetype::Spellcaster) => self.num_spellcasters,
+            Some(AuraArchetype::Trickster) => self.num_tricksters,
+            None => 0,
+        };
+    }
+
+    pub fn _get_num_archetypes(&self) -> (u8, u8, u8, u8) {
         return (
             self.num_spellcasters,
             self.num_rogues,
@@ -1216,31 +1044,28 @@ pub fn create_team(
     if heroes.len() < 1 {
         return Err("cannot form team with < 1 hero");
     }
+    let champion_qty = heroes.iter().filter(|hero| hero.is_champion()).count();
+    if champion_qty < 1 {
+        return Err("cannot form team without a champion");
+    }
+    if champion_qty > 1 {
+        return Err("cannot form team with > 1 champion");
+    }
 
     let mut num_fighters = 0u8;
     let mut num_rogues = 0u8;
     let mut num_spellcasters = 0u8;
     let mut num_tricksters = 0u8;
-    let mut champion = "None".to_string();
-    let mut champion_innate_tier = 1u8;
+    let champion_index = heroes.iter().position(|hero| hero.is_champion()).unwrap();
 
     for hero in &heroes {
         match hero.archetype {
             HeroArchetype::RedFighter => num_fighters += 1,
             HeroArchetype::GreenRogue => num_rogues += 1,
             HeroArchetype::BlueSpellcaster => num_spellcasters += 1,
-            HeroArchetype::Champion => {
-                champion = hero.class.to_string();
-                if hero.rank >= 11 {
-                    champion_innate_tier = 4u8;
-                } else if hero.rank >= 7 {
-                    champion_innate_tier = 3u8;
-                } else if hero.rank >= 4 {
-                    champion_innate_tier = 2u8;
-                }
-            }
+            HeroArchetype::Champion => (),
         }
-        if hero.class == "Trickster" {
+        if hero.has_class_effect(ClassEffect::ChampionLootBoost) {
             num_tricksters += 1;
         }
     }
@@ -1252,8 +1077,7 @@ pub fn create_team(
         num_rogues,
         num_spellcasters,
         num_tricksters,
-        champion,
-        champion_innate_tier,
+        champion_index,
     };
 
     return Ok(team);
@@ -1302,17 +1126,18 @@ pub struct SimHero {
     ninja_bonus: f64,
     ninja_evasion: f64,
     evasion_cap: f64,
-    hemma_bonus: f64,
+    drain_bonus: f64,
     // line 451
     damage_taken_when_hit: f64,
     crit_damage_taken_when_hit: f64,
     damage_dealt: f64,
     // skills: Vec<Skill>,
-    // accuracy_tracking
-    crits_taken: u8,
-    crits_dealt: u8,
-    dodges: u8,
-    attacks_missed: u8,
+    accuracy_stats: HeroAccuracyStats,
+    class_effects: Vec<ClassEffect>, // The special effects of the hero's class
+    class_attack_bonus: f64,         // % attack from the class, e.g. the Chieftain's threat
+    aura_crit_bonus: f64, // Crit chance from the champion's aura modifiers that only last the first rounds
+    aura_barrier_damage_bonus: f64, // % bonus to barrier damage from the champion's aura this round
+    aura: Vec<AuraModifier>, // The modifiers of a champion's aura, empty for heroes
 }
 
 impl SimHero {
@@ -1324,6 +1149,189 @@ impl SimHero {
         return self.identifier.to_string();
     }
 
+    pub fn is_champion(&self) -> bool {
+        return self.archetype == HeroArchetype::Champion;
+    }
+
+    pub fn set_aura(&mut self, aura: Vec<AuraModifier>) {
+        self.aura = aura;
+    }
+
+    pub fn has_class_effect(&self, effect: ClassEffect) -> bool {
+        return self.class_effects.contains(&effect);
+    }
+
+    /// Apply the class effects that hold from the start of each fight
+    fn on_fight_start(&mut self, combat_log: &mut CombatLog) {
+        for effect in self.class_effects.clone() {
+            match effect {
+                ClassEffect::SurviveFatalBlow => self.survive_chance = 1.2,
+                ClassEffect::NinjaBonus | ClassEffect::SenseiBonus => self.set_ninja_bonus(),
+                ClassEffect::OpeningEvade => self.guaranteed_evade = true,
+                _ => (),
+            }
+        }
+        if self.class_attack_bonus != 0.0 {
+            combat_log.record(|| CombatEvent::ClassAttackBonus {
+                hero: self.identifier.to_string(),
+                bonus: self.class_attack_bonus,
+            });
+        }
+    }
+
+    /// Apply the class effects at the start of each round, before the mob attacks
+    fn on_round_start(&mut self, round: i16, combat_log: &mut CombatLog) {
+        if self.has_class_effect(ClassEffect::SenseiBonus) && self.lost_innate == round - 2 {
+            self.set_ninja_bonus();
+            combat_log.record(|| CombatEvent::NinjaBonus {
+                hero: self.identifier.to_string(),
+                ninja_bonus: self.ninja_bonus,
+                ninja_evasion: self.ninja_evasion,
+            });
+        }
+    }
+
+    fn on_evade(&mut self, combat_log: &mut CombatLog) {
+        if self.has_class_effect(ClassEffect::CritAfterEvade) {
+            combat_log.record(|| CombatEvent::GuaranteedCrit {
+                hero: self.identifier.to_string(),
+            });
+            self.guaranteed_crit = true;
+        }
+    }
+
+    /// Apply the class effects of losing hp to the mob or the champion's drain
+    fn on_hit_taken(&mut self, round: i16, combat_log: &mut CombatLog) {
+        if self.has_class_effect(ClassEffect::SenseiBonus) && self.lost_innate != round - 1 {
+            combat_log.record(|| CombatEvent::InnateLost {
+                hero: self.identifier.to_string(),
+            });
+            self.lost_innate = round;
+        }
+    }
+
+    /// Roll to survive a fatal blow at 1 hp, returning whether the hero survived
+    fn on_fatal_blow(&mut self, rng: &mut StdRng, combat_log: &mut CombatLog) -> bool {
+        if rng.gen::<f64>() >= self.survive_chance {
+            return false;
+        }
+        combat_log.record(|| CombatEvent::FatalBlowSurvived {
+            hero: self.identifier.to_string(),
+        });
+        self.hp = 1.0;
+        self.survive_chance = 0.0;
+        return true;
+    }
+
+    /// Apply the class effects after the mob attacks, before the heroes attack
+    fn on_before_attack(&mut self, round: i16, combat_log: &mut CombatLog) {
+        for effect in self.class_effects.clone() {
+            match effect {
+                ClassEffect::BerserkerStages => self.update_berserker_stage(combat_log),
+                ClassEffect::NinjaBonus => {
+                    if self.hp < self.hp_max {
+                        self.lose_ninja_bonus(combat_log);
+                    }
+                }
+                ClassEffect::SenseiBonus => {
+                    if self.lost_innate == round {
+                        self.lose_ninja_bonus(combat_log);
+                    }
+                }
+                ClassEffect::OpeningCrit => {
+                    if round == 1 {
+                        self.guaranteed_crit = true;
+                        self.guaranteed_evade = false;
+                        combat_log.record(|| CombatEvent::GuaranteedCrit {
+                            hero: self.identifier.to_string(),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Returns the damage the hero's element deals to a barrier of the element on a hit
+    /// Heroes of the Any element (e.g. some champions) and AnyElement classes damage any barrier at 30% power
+    fn calculate_barrier_damage(&self, barrier_element: ElementType) -> f64 {
+        let mut damage = 0.0;
+        if barrier_element == self.element_type {
+            damage = f64::from(self.element_qty);
+        } else if self.element_type == ElementType::Any
+            || self.has_class_effect(ClassEffect::AnyElement)
+        {
+            damage = f64::from(self.element_qty) * 0.3;
+        }
+        return damage * (1.0 + self.aura_barrier_damage_bonus);
+    }
+
+    fn pierces_barrier_on_crit(&self, round: i16) -> bool {
+        return round == 1 && self.has_class_effect(ClassEffect::OpeningCrit);
+    }
+
+    fn on_crit(&mut self, combat_log: &mut CombatLog) {
+        if self.has_class_effect(ClassEffect::ConsecutiveCrits) {
+            self.consecutive_crit_bonus = f64::min(self.consecutive_crit_bonus + 0.25, 1.0);
+            combat_log.record(|| CombatEvent::ConsecutiveCritBonus {
+                hero: self.identifier.to_string(),
+                bonus: self.consecutive_crit_bonus,
+            });
+        }
+    }
+
+    fn on_normal_hit(&mut self, combat_log: &mut CombatLog) {
+        if self.has_class_effect(ClassEffect::ConsecutiveCrits) {
+            self.consecutive_crit_bonus = 0.0;
+            combat_log.record(|| CombatEvent::ConsecutiveCritBonus {
+                hero: self.identifier.to_string(),
+                bonus: self.consecutive_crit_bonus,
+            });
+        }
+    }
+
+    /// Apply the class effects at the end of each round, after healing
+    fn on_round_end(&mut self, combat_log: &mut CombatLog) {
+        if self.has_class_effect(ClassEffect::BerserkerStages) {
+            self.update_berserker_stage(combat_log);
+        }
+    }
+
+    fn set_ninja_bonus(&mut self) {
+        self.ninja_bonus = 0.1 + f64::from(std::cmp::min(self.innate_tier, 4)) * 0.1;
+        self.ninja_evasion = 0.15;
+        if self.innate_tier == 3 {
+            self.ninja_evasion = 0.20;
+        }
+        if self.innate_tier == 4 {
+            self.ninja_evasion = 0.25;
+        }
+    }
+
+    fn lose_ninja_bonus(&mut self, combat_log: &mut CombatLog) {
+        self.ninja_bonus = 0.0;
+        self.ninja_evasion = 0.0;
+        combat_log.record(|| CombatEvent::NinjaBonusLost {
+            hero: self.identifier.to_string(),
+        });
+    }
+
+    fn update_berserker_stage(&mut self, combat_log: &mut CombatLog) {
+        if self.hp >= self.jarl_hp_stage_1 * self.hp_max {
+            self.berserker_stage = 0;
+        } else if self.hp >= self.jarl_hp_stage_2 * self.hp_max {
+            self.berserker_stage = 1;
+        } else if self.hp >= self.jarl_hp_stage_3 * self.hp_max {
+            self.berserker_stage = 2;
+        } else if self.hp > 0.0 {
+            self.berserker_stage = 3;
+        }
+        combat_log.record(|| CombatEvent::BerserkerStage {
+            hero: self.identifier.to_string(),
+            stage: self.berserker_stage,
+        });
+    }
+
     fn modify_for_extreme_encounter(&mut self) {
         self.evasion -= 0.2;
     }
@@ -1331,7 +1339,7 @@ impl SimHero {
         self.defense = (self.defense / self.defense_modifier)
             * (self.defense_modifier + 0.2 * f64::from(self.mundra_qty));
     }
-    pub fn round_floats_for_display(&self) -> SimHero {
+    pub fn _round_floats_for_display(&self) -> SimHero {
         let mut h2 = self.clone();
         h2.hp = round_to_2(h2.hp);
         h2.hp_max = round_to_2(h2.hp_max);
@@ -1352,7 +1360,7 @@ impl SimHero {
         h2.ninja_bonus = round_to_2(h2.ninja_bonus);
         h2.ninja_evasion = round_to_2(h2.ninja_evasion);
         h2.evasion_cap = round_to_2(h2.evasion_cap);
-        h2.hemma_bonus = round_to_2(h2.hemma_bonus);
+        h2.drain_bonus = round_to_2(h2.drain_bonus);
         h2.damage_taken_when_hit = round_to_2(h2.damage_taken_when_hit);
         h2.crit_damage_taken_when_hit = round_to_2(h2.crit_damage_taken_when_hit);
         h2.damage_dealt = round_to_2(h2.damage_dealt);
@@ -1501,6 +1509,7 @@ pub fn create_sim_hero(
     //     _ => return Err("Unknown Element Type, Could Not Create Hero"),
     // }
 
+    let class_effects = get_class_effects(&class);
     let mut hero = SimHero {
         identifier,
         class,
@@ -1541,14 +1550,16 @@ pub fn create_sim_hero(
         ninja_bonus: 0.0,
         ninja_evasion: 0.0,
         evasion_cap: 0.75,
-        hemma_bonus: 0.0,
+        drain_bonus: 0.0,
         damage_taken_when_hit: 0.0,
         crit_damage_taken_when_hit: 0.0,
         damage_dealt: 0.0,
-        crits_taken: 0,
-        crits_dealt: 0,
-        dodges: 0,
-        attacks_missed: 0,
+        accuracy_stats: Default::default(),
+        class_effects,
+        class_attack_bonus: 0.0,
+        aura_crit_bonus: 0.0,
+        aura_barrier_damage_bonus: 0.0,
+        aura: Default::default(),
     };
 
     if hero.rank == 4 {
@@ -1557,33 +1568,182 @@ pub fn create_sim_hero(
         hero.jarl_hp_stage_3 = 0.3;
     }
 
-    if hero.class == "Berserker" || hero.class == "Jarl" {
+    if hero.has_class_effect(ClassEffect::BerserkerStages) {
         hero.berserker_level = std::cmp::min(hero.rank, 4);
-    } else if hero.class == "Pathfinder" {
+    }
+    if hero.has_class_effect(ClassEffect::RaisedEvasionCap) {
         hero.evasion_cap = 0.78;
     }
+    if hero.has_class_effect(ClassEffect::ThreatToAttack) {
+        hero.class_attack_bonus += 0.4 * f64::from(hero.threat) / 100.0;
+    }
+    if hero.has_class_effect(ClassEffect::AttackPerElement) {
+        hero.class_attack_bonus += 0.01 * f64::from(hero.element_qty);
+    }
 
     return Ok(hero);
 }
 
-pub struct StringVec(Vec<String>);
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::champions::Champion;
+    use crate::combat_events::create_combat_log;
+
+    fn create_test_hero(class: &str, threat: u16, element_qty: u16, element_type: &str) -> SimHero {
+        return create_sim_hero(
+            f!("{}_Test", class),
+            class.to_string(),
+            40,
+            1,
+            1,
+            1000.0,
+            0.0,
+            1000.0,
+            1000.0,
+            threat,
+            0.05,
+            2.0,
+            0.05,
+            0.0,
+            element_qty,
+            element_type.to_string(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            0.0,
+            0.0,
+        )
+        .unwrap();
+    }
 
-impl StringVec {
-    fn push(&mut self, new_entry: String) {
-        self.0.push(new_entry);
+    fn create_test_champion(champion_name: &str, rank: u8) -> SimHero {
+        let path = f!("{}/bundle/champions.yaml", env!("CARGO_MANIFEST_DIR"));
+        let reader = std::fs::File::open(path).unwrap();
+        let champions: HashMap<String, Champion> = serde_yaml::from_reader(reader).unwrap();
+        return champions[champion_name]
+            .create_sim_hero(f!("{}_Test", champion_name), 40, rank)
+            .unwrap();
+    }
+
+    #[test]
+    fn chieftain_gains_40_percent_of_threat_as_attack() {
+        let chieftain = create_test_hero("Chieftain", 150, 0, "Fire");
+        let soldier = create_test_hero("Soldier", 150, 0, "Fire");
+        assert_eq!(chieftain.class_attack_bonus, 0.6);
+        assert_eq!(soldier.class_attack_bonus, 0.0);
     }
-}
 
-impl std::fmt::Display for StringVec {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        let mut comma_separated = String::new();
+    #[test]
+    fn geomancer_and_astramancer_gain_1_percent_attack_per_element() {
+        let geomancer = create_test_hero("Geomancer", 100, 75, "Earth");
+        let astramancer = create_test_hero("Astramancer", 100, 120, "Light");
+        assert_eq!(geomancer.class_attack_bonus, 0.75);
+        assert_eq!(astramancer.class_attack_bonus, 1.2);
+    }
 
-        for num in &self.0[0..self.0.len() - 1] {
-            comma_separated.push_str(&num.to_string());
-            comma_separated.push_str(", ");
-        }
+    #[test]
+    fn spellknight_damages_off_element_barrier_at_30_percent() {
+        let spellblade = create_test_hero("Spellblade", 100, 100, "Water");
+        let spellknight = create_test_hero("Spellknight", 100, 100, "Water");
+        let mage = create_test_hero("Mage", 100, 100, "Water");
+        assert_eq!(spellblade.calculate_barrier_damage(ElementType::Fire), 30.0);
+        assert_eq!(
+            spellknight.calculate_barrier_damage(ElementType::Fire),
+            30.0
+        );
+        assert_eq!(
+            spellknight.calculate_barrier_damage(ElementType::Water),
+            100.0
+        );
+        assert_eq!(mage.calculate_barrier_damage(ElementType::Fire), 0.0);
+        assert_eq!(mage.calculate_barrier_damage(ElementType::Water), 100.0);
+    }
+
+    #[test]
+    fn rudo_boosts_barrier_damage_from_tier_3_for_the_first_rounds() {
+        let mut combat_log = create_combat_log(false);
+        // Rank 5 is innate tier 2, rank 7 is innate tier 3
+        let mut tier_2_team = create_team(
+            vec![
+                create_test_hero("Spellknight", 100, 100, "Water"),
+                create_test_champion("Rudo", 5),
+            ],
+            None,
+        )
+        .unwrap();
+        let mut tier_3_team = create_team(
+            vec![
+                create_test_hero("Spellknight", 100, 100, "Water"),
+                create_test_champion("Rudo", 7),
+            ],
+            None,
+        )
+        .unwrap();
+
+        tier_2_team.update_round_aura_bonuses(1, false, &mut combat_log);
+        tier_3_team.update_round_aura_bonuses(1, false, &mut combat_log);
+        assert_eq!(
+            tier_2_team.heroes[0].calculate_barrier_damage(ElementType::Fire),
+            30.0
+        );
+        assert_eq!(
+            tier_3_team.heroes[0].calculate_barrier_damage(ElementType::Fire),
+            45.0
+        );
+
+        // At tier 3 the bonus lasts 3 rounds
+        tier_3_team.update_round_aura_bonuses(4, false, &mut combat_log);
+        assert_eq!(
+            tier_3_team.heroes[0].calculate_barrier_damage(ElementType::Fire),
+            30.0
+        );
+    }
+
+    #[test]
+    fn hemma_multiplies_a_mercenarys_whole_hp() {
+        let mut team = create_team(
+            vec![
+                create_test_hero("Soldier", 100, 0, "Fire"),
+                create_test_hero("Mercenary", 100, 0, "Fire"),
+                create_test_champion("Hemma", 11),
+            ],
+            None,
+        )
+        .unwrap();
+        team.apply_champion_and_booster_bonuses(false);
+        let soldier_hp = team.heroes[0].hp;
+        assert!(soldier_hp > 1000.0);
+        assert_eq!(team.heroes[1].hp, soldier_hp * 1.328);
+        assert_eq!(team.heroes[1].hp_max, team.heroes[1].hp);
+    }
+
+    #[test]
+    fn tricksters_raise_polonias_loot_chance() {
+        let create_polonia_team = |class: &str| {
+            return create_team(
+                vec![
+                    create_test_hero(class, 100, 0, "Fire"),
+                    create_test_champion("Polonia", 11),
+                ],
+                None,
+            )
+            .unwrap();
+        };
+        let (_, thief_loot_chance, _) =
+            create_polonia_team("Thief").apply_champion_and_booster_bonuses(false);
+        let (_, trickster_loot_chance, _) =
+            create_polonia_team("Trickster").apply_champion_and_booster_bonuses(false);
+        assert!(trickster_loot_chance > thief_loot_chance);
+    }
 
-        comma_separated.push_str(&self.0[self.0.len() - 1].to_string());
-        write!(f, "[{}]", comma_separated)
+    #[test]
+    fn any_element_hero_damages_barriers_at_30_percent() {
+        let hero = create_test_hero("Soldier", 100, 100, "Any");
+        assert_eq!(hero.calculate_barrier_damage(ElementType::Fire), 30.0);
     }
 }
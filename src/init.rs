@@ -1,9 +1,23 @@
-pub fn create_logfile() {
+use indexmap::IndexMap;
+use log::{error, info};
+
+use crate::{
+    dockets::Docket,
+    inputs::{
+        load_champions_from_yaml, load_dungeons_from_yaml, load_hero_classes_from_yaml,
+        load_heroes_as_sim_heroes_from_tsv, load_heroes_from_tsv, load_skill_abbreviation_map,
+        load_study_docket,
+    },
+    sheet_processing::{get_hero_equipment_data, get_hero_skills_data, get_innate_skills_data},
+    simdata::SimData,
+};
+
+pub fn create_logfile(data_directory: &str) {
     // Create new log file each run
     let mut trial_logs_path: String;
     let mut i = 0;
     loop {
-        trial_logs_path = f!("adept_data/logs/log_{}.log", i);
+        trial_logs_path = f!("{}/logs/log_{}.log", data_directory, i);
         // Increment until file not exist, then create and break
         if std::path::Path::new(&trial_logs_path).exists() {
             i += 1;
@@ -14,3 +28,210 @@ pub fn create_logfile() {
     fast_log::init(fast_log::Config::new().file(&trial_logs_path)).unwrap();
     log::info!("Start of Log File");
 }
+
+/// Returns the files required to run a docket in the order they must be loaded
+/// Each entry maps the file identifier to (path, loaded?), with paths relative to data_directory
+pub fn get_required_files(data_directory: &str) -> IndexMap<String, (String, bool)> {
+    return IndexMap::<String, (String, bool)>::from([
+        (
+            String::from("Dungeons"),
+            (f!("{}/bundle/dungeons.yaml", data_directory), false),
+        ),
+        (
+            String::from("Blueprints"),
+            (f!("{}/bundle/blueprints.tsv", data_directory), false),
+        ),
+        (
+            String::from("Innate Skills"),
+            (f!("{}/bundle/innate_skills.tsv", data_directory), false),
+        ),
+        (
+            String::from("Hero Skills"),
+            (f!("{}/bundle/hero_skills.tsv", data_directory), false),
+        ),
+        (
+            String::from("Hero Classes"),
+            (f!("{}/bundle/hero_classes.yaml", data_directory), false),
+        ),
+        (
+            String::from("Champions"),
+            (f!("{}/bundle/champions.yaml", data_directory), false),
+        ),
+        (
+            String::from("Skill Abbreviations"),
+            (
+                f!("{}/bundle/skill_abbreviation_map.csv", data_directory),
+                false,
+            ),
+        ),
+        (
+            String::from("Hero Builder"),
+            (f!("{}/config/hero_builder.tsv", data_directory), false),
+        ),
+        (
+            String::from("Study Docket"),
+            (f!("{}/config/study_docket.tsv", data_directory), false),
+        ),
+    ]);
+}
+
+/// Attempts to load each of the required files into sim_data and docket, marking each file that loaded successfully
+/// Study output is written under data_directory/output
+pub fn load_required_files(
+    data_directory: &str,
+    required_files: &mut IndexMap<String, (String, bool)>,
+    sim_data: &mut SimData,
+    docket: &mut Docket,
+) {
+    for (key, (path, _)) in required_files.clone() {
+        let mut load_success = false;
+        match key.as_str() {
+            "Dungeons" => {
+                info!("Loading Dungeons");
+                let loaded_dungeons = load_dungeons_from_yaml(&path);
+                if loaded_dungeons.len() > 0 {
+                    sim_data.loaded_dungeons = loaded_dungeons;
+                    load_success = true;
+                }
+            }
+            "Hero Builder" => {
+                info!("Loading Hero Builder");
+                if sim_data.bp_map.len() == 0
+                    || sim_data.hero_classes.len() == 0
+                    || sim_data.champions.len() == 0
+                    || sim_data.hero_skill_tier_1_name_map.len() == 0
+                    || sim_data.hero_skill_map.len() == 0
+                    || sim_data.class_innate_skill_names_map.len() == 0
+                    || sim_data.innate_skill_map.len() == 0
+                {
+                    // Left marked as not loaded, so the failure is reported with the other files that failed to load
+                    error!("Hero builder could not be loaded because one or more of the files it depends on was not loaded");
+                    log::logger().flush();
+                    continue;
+                }
+                let loaded_heroes = load_heroes_as_sim_heroes_from_tsv(
+                    &path,
+                    sim_data.bp_map.clone(),
+                    sim_data.hero_classes.clone(),
+                    &sim_data.champions,
+                    sim_data.hero_skill_tier_1_name_map.clone(),
+                    sim_data.hero_skill_map.clone(),
+                    sim_data.class_innate_skill_names_map.clone(),
+                    sim_data.innate_skill_map.clone(),
+                );
+                let loaded_heroes_from_builder = load_heroes_from_tsv(
+                    &path,
+                    sim_data.bp_map.clone(),
+                    sim_data.hero_classes.clone(),
+                    &sim_data.champions,
+                );
+                if loaded_heroes.len() > 0 && loaded_heroes_from_builder.len() > 0 {
+                    sim_data.loaded_heroes = loaded_heroes;
+                    sim_data.loaded_heroes_from_builder = loaded_heroes_from_builder;
+                    load_success = true;
+                }
+            }
+            "Blueprints" => {
+                info!("Loading Blueprints");
+                let bp_map = get_hero_equipment_data(&path);
+                if bp_map.len() > 0 {
+                    sim_data.bp_map = bp_map;
+                    load_success = true;
+                }
+            }
+            "Innate Skills" => {
+                info!("Loading Innate Skills");
+                let (
+                    innate_skill_tier_1_name_map,
+                    innate_skill_any_tier_to_tier_1_name_nap,
+                    class_innate_skill_names_map,
+                    innate_skill_map,
+                ) = get_innate_skills_data(&path);
+                if innate_skill_tier_1_name_map.len() > 0
+                    && innate_skill_any_tier_to_tier_1_name_nap.len() > 0
+                    && class_innate_skill_names_map.len() > 0
+                    && innate_skill_map.len() > 0
+                {
+                    sim_data.innate_skill_tier_1_name_map = innate_skill_tier_1_name_map;
+                    sim_data.innate_skill_any_tier_to_tier_1_name_nap =
+                        innate_skill_any_tier_to_tier_1_name_nap;
+                    sim_data.class_innate_skill_names_map = class_innate_skill_names_map;
+                    sim_data.innate_skill_map = innate_skill_map;
+                    load_success = true;
+                }
+            }
+            "Hero Skills" => {
+                info!("Loading Hero Skills");
+                let (
+                    hero_skill_tier_1_name_map,
+                    hero_skill_any_tier_to_tier_1_name_map,
+                    hero_skill_map,
+                ) = get_hero_skills_data(&path);
+                let mut loaded_valid_skills: Vec<String> = Default::default();
+                for (k, v) in &hero_skill_tier_1_name_map {
+                    let ksplit: Vec<&str> = k.split(' ').collect();
+                    if ksplit[ksplit.len() - 1] == "T4" {
+                        loaded_valid_skills.push(v.to_string());
+                    }
+                }
+                if hero_skill_tier_1_name_map.len() > 0
+                    && hero_skill_any_tier_to_tier_1_name_map.len() > 0
+                    && hero_skill_map.len() > 0
+                    && loaded_valid_skills.len() > 0
+                {
+                    sim_data.hero_skill_tier_1_name_map = hero_skill_tier_1_name_map;
+                    sim_data.hero_skill_any_tier_to_tier_1_name_map =
+                        hero_skill_any_tier_to_tier_1_name_map;
+                    sim_data.hero_skill_map = hero_skill_map;
+                    sim_data.loaded_valid_skills = loaded_valid_skills;
+                    load_success = true;
+                }
+            }
+            "Hero Classes" => {
+                info!("Loading Hero Classes");
+                let hero_classes = load_hero_classes_from_yaml(&path);
+                if hero_classes.len() > 0 {
+                    sim_data.hero_classes = hero_classes;
+                    load_success = true;
+                }
+            }
+            "Champions" => {
+                info!("Loading Champions");
+                let champions = load_champions_from_yaml(&path);
+                if champions.len() > 0 {
+                    sim_data.champions = champions;
+                    load_success = true;
+                }
+            }
+            "Skill Abbreviations" => {
+                info!("Loading Skill Abbreviations");
+                let (hero_skill_abbreviation_map, hero_abbreviation_skill_map) =
+                    load_skill_abbreviation_map(&path);
+                if hero_skill_abbreviation_map.len() > 0 && hero_abbreviation_skill_map.len() > 0 {
+                    sim_data.hero_skill_abbreviation_map = hero_skill_abbreviation_map;
+                    sim_data.hero_abbreviation_skill_map = hero_abbreviation_skill_map;
+                    load_success = true;
+                }
+            }
+            "Study Docket" => {
+                info!("Loading Docket");
+                let loaded_docket = load_study_docket(&path);
+                info!(
+                    "Docket Loaded with {} Studies",
+                    loaded_docket.get_num_studies()
+                );
+                if loaded_docket.get_num_studies() > 0 {
+                    *docket = loaded_docket;
+                    docket.set_output_directory(f!("{}/output", data_directory));
+                    load_success = true;
+                }
+            }
+            _ => info!("Unhandled Required Files Key: {}", key),
+        }
+        if load_success {
+            required_files
+                .entry(key)
+                .and_modify(|e| *e = (path.to_string(), true));
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use log::{error, warn};
 use serde::{Deserialize, Serialize};
 
+use crate::champions::Champion;
+use crate::class_effects::validate_class_effects;
 use crate::decimals::{_round_array_of_len_4_to_2, round_to_2};
 use crate::dockets::{Docket, DocketStudy};
 use crate::equipment::{Blueprint, ElementType};
@@ -367,6 +370,24 @@ pub struct HeroInput {
     equipment_quality_6: String,
     elements_socketed_6: String,
     spirits_socketed_6: String,
+
+    // Alternates substituted for the weapon or off-hand when a skillset has a mastery skill the equipped item does not match
+    #[serde(default)]
+    alternate_weapon_1: String,
+    #[serde(default)]
+    alternate_weapon_2: String,
+    #[serde(default)]
+    alternate_weapon_3: String,
+    #[serde(default)]
+    alternate_weapon_4: String,
+    #[serde(default)]
+    alternate_off_hand_1: String,
+    #[serde(default)]
+    alternate_off_hand_2: String,
+    #[serde(default)]
+    alternate_off_hand_3: String,
+    #[serde(default)]
+    alternate_off_hand_4: String,
 }
 
 // impl HeroInput {
@@ -418,6 +439,18 @@ impl From<HeroInput> for Hero {
             item.spirits_socketed_5,
             item.spirits_socketed_6,
         ];
+        let alternate_weapons: [String; 4] = [
+            item.alternate_weapon_1,
+            item.alternate_weapon_2,
+            item.alternate_weapon_3,
+            item.alternate_weapon_4,
+        ];
+        let alternate_off_hands: [String; 4] = [
+            item.alternate_off_hand_1,
+            item.alternate_off_hand_2,
+            item.alternate_off_hand_3,
+            item.alternate_off_hand_4,
+        ];
 
         return create_hero(
             item.identifier,
@@ -446,6 +479,8 @@ impl From<HeroInput> for Hero {
             equipment_quality,
             elements_socketed,
             spirits_socketed,
+            alternate_weapons,
+            alternate_off_hands,
         );
     }
 }
@@ -468,6 +503,8 @@ pub fn create_hero_input(
     equipment_quality: [String; 6],
     elements_socketed: [String; 6],
     spirits_socketed: [String; 6],
+    alternate_weapons: [String; 4],
+    alternate_off_hands: [String; 4],
 ) -> HeroInput {
     return HeroInput {
         identifier,
@@ -513,13 +550,25 @@ pub fn create_hero_input(
         spirits_socketed_4: spirits_socketed[3].clone(),
         spirits_socketed_5: spirits_socketed[4].clone(),
         spirits_socketed_6: spirits_socketed[5].clone(),
+
+        alternate_weapon_1: alternate_weapons[0].clone(),
+        alternate_weapon_2: alternate_weapons[1].clone(),
+        alternate_weapon_3: alternate_weapons[2].clone(),
+        alternate_weapon_4: alternate_weapons[3].clone(),
+
+        alternate_off_hand_1: alternate_off_hands[0].clone(),
+        alternate_off_hand_2: alternate_off_hands[1].clone(),
+        alternate_off_hand_3: alternate_off_hands[2].clone(),
+        alternate_off_hand_4: alternate_off_hands[3].clone(),
     };
 }
 
+/// Champions are skipped, as they have no gear or skills to build
 pub fn load_heroes_from_tsv(
     path: &String,
     bp_map: HashMap<String, Blueprint>,
     hero_classes: HashMap<String, HeroClass>,
+    champions: &HashMap<String, Champion>,
 ) -> HashMap<String, Hero> {
     let mut heroes: HashMap<String, Hero> = Default::default();
 
@@ -531,6 +580,9 @@ pub fn load_heroes_from_tsv(
 
     for result in reader.deserialize() {
         let hero_in: HeroInput = result.unwrap();
+        if champions.contains_key(&hero_in.class) {
+            continue;
+        }
         let identifier = hero_in.identifier.to_string();
         let mut hero = Hero::from(hero_in);
         hero.validate_equipment(&bp_map, &hero_classes);
@@ -563,10 +615,12 @@ pub fn convert_loaded_heroes_to_sim_heroes(
     return result;
 }
 
+/// Champions are created from their stats at the given rank in champions, ignoring any gear or skills listed for them
 pub fn load_heroes_as_sim_heroes_from_tsv(
     path: &String,
     bp_map: HashMap<String, Blueprint>,
     hero_classes: HashMap<String, HeroClass>,
+    champions: &HashMap<String, Champion>,
     hero_skill_tier_1_name_map: HashMap<String, String>,
     hero_skill_map: HashMap<String, HeroSkill>,
     class_innate_skill_names_map: HashMap<String, String>,
@@ -582,6 +636,10 @@ pub fn load_heroes_as_sim_heroes_from_tsv(
     for result in reader.deserialize() {
         let hero_in: HeroInput = result.unwrap();
         let identifier = hero_in.identifier.to_string();
+        if let Some(champion) = champions.get(&hero_in.class) {
+            heroes.insert(identifier, create_champion_sim_hero(champion, hero_in));
+            continue;
+        }
         let mut hero = Hero::from(hero_in);
         hero.validate_equipment(&bp_map, &hero_classes);
         hero.scale_by_class(&hero_classes);
@@ -600,6 +658,42 @@ pub fn load_heroes_as_sim_heroes_from_tsv(
     return heroes;
 }
 
+fn create_champion_sim_hero(champion: &Champion, hero_in: HeroInput) -> SimHero {
+    let has_skills = [
+        &hero_in.skill_1,
+        &hero_in.skill_2,
+        &hero_in.skill_3,
+        &hero_in.skill_4,
+    ]
+    .iter()
+    .any(|skill| skill.len() > 0);
+    let has_equipment = [
+        &hero_in.equipment_equipped_1,
+        &hero_in.equipment_equipped_2,
+        &hero_in.equipment_equipped_3,
+        &hero_in.equipment_equipped_4,
+        &hero_in.equipment_equipped_5,
+        &hero_in.equipment_equipped_6,
+    ]
+    .iter()
+    .any(|equipment| equipment.len() > 0);
+    if has_skills || has_equipment {
+        warn!(
+            "Champion {} does not learn skills or equip gear, so those listed for it are ignored",
+            hero_in.identifier
+        );
+    }
+
+    match champion.create_sim_hero(hero_in.identifier.to_string(), hero_in.level, hero_in.rank) {
+        Ok(sim_hero) => return sim_hero,
+        Err(e) => {
+            error!("Could not create champion {}: {}", hero_in.identifier, e);
+            log::logger().flush();
+            panic!("Could not create champion {}: {}", hero_in.identifier, e);
+        }
+    }
+}
+
 pub fn _save_heroes_to_tsv(
     path: String,
     heroes: HashMap<String, Hero>,
@@ -634,6 +728,14 @@ pub fn load_hero_classes_from_yaml(path: &String) -> HashMap<String, HeroClass>
     {
         hero_classes.insert(class_name, hero_class);
     }
+    match validate_class_effects(&hero_classes) {
+        Ok(_) => (),
+        Err(e) => {
+            error!("Invalid hero classes in {}: {}", path, e);
+            log::logger().flush();
+            panic!("Invalid hero classes in {}: {}", path, e);
+        }
+    }
     return hero_classes;
 }
 
@@ -662,6 +764,48 @@ pub fn _save_hero_classes_to_yaml(
     return Ok(());
 }
 
+/// Load the champions, returning none if the file cannot be read so it is reported as not loaded
+pub fn load_champions_from_yaml(path: &String) -> HashMap<String, Champion> {
+    let reader = match std::fs::File::open(path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            error!("Could not open champions file {}: {}", path, e);
+            return HashMap::new();
+        }
+    };
+    let champions =
+        match serde_yaml::from_reader::<std::fs::File, HashMap<String, Champion>>(reader) {
+            Ok(champions) => champions,
+            Err(e) => {
+                error!("Could not read champions from {}: {}", path, e);
+                return HashMap::new();
+            }
+        };
+    for champion in champions.values() {
+        match champion.validate_aura() {
+            Ok(_) => (),
+            Err(e) => {
+                error!("Invalid champion in {}: {}", path, e);
+                log::logger().flush();
+                panic!("Invalid champion in {}: {}", path, e);
+            }
+        }
+    }
+    let mut provisional_champions: Vec<&String> = champions
+        .iter()
+        .filter(|(_, champion)| champion.has_provisional_stats())
+        .map(|(name, _)| name)
+        .collect();
+    provisional_champions.sort();
+    if provisional_champions.len() > 0 {
+        warn!(
+            "The rank stats of {:?} in {} are provisional estimates, so results for teams they lead may not match the game",
+            provisional_champions, path
+        );
+    }
+    return champions;
+}
+
 /// Defines SkillAbbreviationMap format for deserialization from CSV
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SkillAbbreviationMapInput {
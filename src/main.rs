@@ -10,10 +10,16 @@ mod equipment;
 
 mod heroes;
 
+mod champions;
+
+mod class_effects;
+
 mod dungeons;
 
 mod simulations;
 
+mod combat_events;
+
 mod trials;
 
 mod inputs;
@@ -40,6 +46,12 @@ mod simdata;
 
 mod deserialize;
 
+mod statistics;
+
+mod scoring;
+
+mod cli;
+
 use std::ops::Deref;
 use std::panic;
 
@@ -69,6 +81,14 @@ fn main() -> Result<(), eframe::Error> {
         log::logger().flush();
     }));
 
+    // Any arguments run the command line interface instead of the gui
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 {
+        #[cfg(all(windows, not(debug_assertions)))]
+        attach_parent_console();
+        std::process::exit(cli::run_from_command_line(args));
+    }
+
     let rt = tokio::runtime::Runtime::new().expect("Unable to create Runtime");
 
     // Enter the runtime so that `tokio::spawn` is available immediately.
@@ -98,3 +118,17 @@ fn main() -> Result<(), eframe::Error> {
     warn!("Main Thread Exiting - Unless you intentionally closed the app this is an issue.");
     Ok(())
 }
+
+/// Release builds on windows use the windows subsystem and start without a console,
+/// so the command line interface attaches to the console of the shell it was run from to show its output
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_parent_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(dw_process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = 0xFFFFFFFF;
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
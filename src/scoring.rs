@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::trials::TrialResult;
+
+/// The names of the trial result fields that can be weighted in a composite score, as given in the study docket
+pub const SCORING_FIELDS: [&str; 5] = [
+    "Success Rate",
+    "Survival Rate",
+    "Average Rounds",
+    "Damage",
+    "HP Remaining",
+];
+
+/// Weights of the trial result fields combined into a build's composite score
+/// Each field is normalized to [0,1] before weighting, so the weights are comparable regardless of the field's scale
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ScoringWeights {
+    pub success_rate: f64,
+    pub survival_rate: f64, // The average survival rate of the heroes on the team
+    pub average_rounds: f64, // Rewards fewer rounds, as the fastest result scores 1
+    pub damage: f64,        // The average damage of the heroes on the team
+    pub hp_remaining: f64,  // The average hp remaining of the heroes on the team
+}
+
+/// Weights only the success rate, so the composite score is the success rate
+pub fn create_default_scoring_weights() -> ScoringWeights {
+    return ScoringWeights {
+        success_rate: 1.0,
+        survival_rate: 0.0,
+        average_rounds: 0.0,
+        damage: 0.0,
+        hp_remaining: 0.0,
+    };
+}
+
+impl ScoringWeights {
+    /// Set the weight of the field with the given docket name, returning false if there is no such field
+    pub fn set_weight(&mut self, field: &str, weight: f64) -> bool {
+        match field {
+            "Success Rate" => self.success_rate = weight,
+            "Survival Rate" => self.survival_rate = weight,
+            "Average Rounds" => self.average_rounds = weight,
+            "Damage" => self.damage = weight,
+            "HP Remaining" => self.hp_remaining = weight,
+            _ => return false,
+        }
+        return true;
+    }
+
+    /// Returns each field with a nonzero weight as "Field Weight", for describing the scoring in reports
+    pub fn describe(&self) -> Vec<String> {
+        return SCORING_FIELDS
+            .iter()
+            .zip([
+                self.success_rate,
+                self.survival_rate,
+                self.average_rounds,
+                self.damage,
+                self.hp_remaining,
+            ])
+            .filter(|(_, weight)| *weight != 0.0)
+            .map(|(field, weight)| f!("{} {}", field, weight))
+            .collect();
+    }
+
+    /// Returns the weighted average of the result's normalized fields
+    /// The success rate is scaled by success_rate_factor, e.g. a StaticDuoSkillStudy's slot weighting
+    pub fn calculate_composite_score(
+        &self,
+        result: &TrialResult,
+        normalization: &ScoreNormalization,
+        success_rate_factor: f64,
+    ) -> f64 {
+        let weight_total = self.success_rate
+            + self.survival_rate
+            + self.average_rounds
+            + self.damage
+            + self.hp_remaining;
+        if weight_total == 0.0 {
+            return 0.0;
+        }
+        let weighted_total = self.success_rate * result.get_success_rate() * success_rate_factor
+            + self.survival_rate * get_team_avg_survival_rate(result)
+            + self.average_rounds
+                * normalize_inverse(
+                    result.get_average_rounds(),
+                    normalization.min_average_rounds,
+                )
+            + self.damage * normalize(get_team_avg_dmg(result), normalization.max_damage)
+            + self.hp_remaining
+                * normalize(
+                    get_team_avg_hp_remaining(result),
+                    normalization.max_hp_remaining,
+                );
+        return weighted_total / weight_total;
+    }
+}
+
+/// Settings for the ranked results and summary written when a study finishes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReportSettings {
+    pub scoring_weights: ScoringWeights,
+    pub summary_top_n: usize, // The number of builds listed in the markdown summary
+}
+
+/// The best value of each unbounded field among a group of compared results, used to normalize them to [0,1]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ScoreNormalization {
+    min_average_rounds: f64,
+    max_damage: f64,
+    max_hp_remaining: f64,
+}
+
+pub fn create_score_normalization(results: &Vec<&TrialResult>) -> ScoreNormalization {
+    return ScoreNormalization {
+        min_average_rounds: results
+            .iter()
+            .map(|result| result.get_average_rounds())
+            .filter(|rounds| *rounds > 0.0)
+            .fold(f64::MAX, f64::min),
+        max_damage: results
+            .iter()
+            .map(|result| get_team_avg_dmg(result))
+            .fold(0.0, f64::max),
+        max_hp_remaining: results
+            .iter()
+            .map(|result| get_team_avg_hp_remaining(result))
+            .fold(0.0, f64::max),
+    };
+}
+
+fn normalize(value: f64, max: f64) -> f64 {
+    if max <= 0.0 {
+        return 0.0;
+    }
+    return value / max;
+}
+
+/// Normalize a field where lower is better, so the minimum scores 1
+fn normalize_inverse(value: f64, min: f64) -> f64 {
+    if value <= 0.0 || min == f64::MAX {
+        return 0.0;
+    }
+    return min / value;
+}
+
+pub fn get_team_avg_survival_rate(result: &TrialResult) -> f64 {
+    return get_team_avg(result, |hero_index| {
+        result.get_hero_survival_rate(hero_index)
+    });
+}
+
+pub fn get_team_avg_dmg(result: &TrialResult) -> f64 {
+    return get_team_avg(result, |hero_index| result.get_hero_avg_dmg(hero_index));
+}
+
+pub fn get_team_avg_hp_remaining(result: &TrialResult) -> f64 {
+    return get_team_avg(result, |hero_index| {
+        result.get_hero_avg_hp_remaining(hero_index)
+    });
+}
+
+fn get_team_avg(result: &TrialResult, hero_value: impl Fn(usize) -> f64) -> f64 {
+    let hero_qty = result.get_hero_qty();
+    if hero_qty == 0 {
+        return 0.0;
+    }
+    return (0..hero_qty).map(hero_value).sum::<f64>() / hero_qty as f64;
+}
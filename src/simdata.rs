@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
+    champions::Champion,
     dungeons::Dungeon,
     equipment::Blueprint,
     hero_builder::{Hero, HeroClass},
@@ -11,6 +12,7 @@ use crate::{
 #[derive(Default, Clone)]
 pub struct SimData {
     pub hero_classes: HashMap<String, HeroClass>,
+    pub champions: HashMap<String, Champion>,
     pub hero_skill_tier_1_name_map: HashMap<String, String>,
     pub hero_skill_any_tier_to_tier_1_name_map: HashMap<String, String>,
     pub hero_skill_map: HashMap<String, HeroSkill>,
@@ -1,10 +1,12 @@
+use super::combat_events::{create_combat_log, CombatEvent, CombatLog};
 use super::dungeons::Encounter;
-use super::heroes::Team;
+use super::equipment::ElementType;
+use super::heroes::{HeroAccuracyStats, Team};
 
 use serde::{Deserialize, Serialize};
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 
 use log::{error, info};
 
@@ -15,12 +17,13 @@ pub struct Simulation {
     encounter: Encounter,
     metrics: Vec<String>,
     log_all: bool,
+    combat_log: CombatLog, // The events of the simulation, only recorded if log_all is true
 }
 
 impl Simulation {
-    pub fn run(&mut self) -> Result<SimResult, &'static str> {
-        let mut log_queue: Vec<String> = vec![];
-        log_queue.push("Start of Simulation".to_string());
+    /// Run the simulation, drawing every random value from rng so that a given seed always yields the same result
+    pub fn run(&mut self, rng: &mut StdRng) -> Result<SimResult, &'static str> {
+        let mut combat_log = create_combat_log(self.log_all);
         // If encounter.is_boss then ignore Mundra
         // Error if more heroes in team than encounter allows
 
@@ -28,13 +31,11 @@ impl Simulation {
         let (is_extreme, is_boss) = self.encounter.is_extreme_or_boss();
         self.team.normalize_percents(is_extreme, is_boss);
 
-        // Polonia Loot
-        let mut polonia_loot_cap_hit = 0;
-        let mut polonia_loot_total = 0;
+        // Champion Loot
+        let mut champion_loot_cap_hit = 0;
+        let mut champion_loot_total = 0;
 
-        let (champion, champion_innate_tier) = self.team.get_champion_info();
-
-        let (hemma_mult, count_loot, loot_chance, polonia_loot_cap) =
+        let (count_loot, loot_chance, champion_loot_cap) =
             self.team.apply_champion_and_booster_bonuses(is_boss);
 
         let encounter_defense_cap = self.encounter.get_defense_cap();
@@ -49,31 +50,19 @@ impl Simulation {
         let mut won_fight = false;
 
         self.team
-            .initialize_survive_chance_hemma_guaranteed_crit_and_berserker_stage();
+            .initialize_survive_chance_drain_bonus_guaranteed_crit_and_berserker_stage();
 
         let mut update_target = true;
         let mut round = 0;
         let mut shark_active = 0;
         let mut dinosaur_active = 1;
         let mut lord_save = true;
-        let mut rudo_bonus = 0f64;
-
-        if champion == "Rudo" {
-            match champion_innate_tier {
-                1u8 => rudo_bonus = 0.3,
-                2u8 => rudo_bonus = 0.4,
-                3u8 => rudo_bonus = 0.4,
-                4u8 => rudo_bonus = 0.5,
-                _ => (),
-            }
-        }
-
-        self.team.apply_class_special_effects();
+        let mut barrier_broken_round: Option<i16> = None;
+        let mut barrier_broken_by: Option<String> = None;
 
         // Generate Random Attack Order
         let mut attack_order: Vec<usize> = (0..self.team.get_heroes_len()).collect();
-        let mut rng = thread_rng();
-        attack_order.shuffle(&mut rng);
+        attack_order.shuffle(rng);
 
         self.encounter.init_barrier_modifier();
 
@@ -83,42 +72,50 @@ impl Simulation {
         // Define heroes alive
         let mut heroes_alive = self.team.get_heroes_len();
 
-        log_queue.push("Ready to start quest with:".to_string());
-        log_queue.push(f!("{:#?}", self.encounter.round_floats_for_display()));
-        log_queue.push(f!("{:#?}", self.team.round_floats_for_display()));
+        combat_log.record(|| {
+            let (encounter_hp, _) = self.encounter.get_hp_info();
+            let (barrier_hp, _, _, _) = self.encounter.get_barrier_info();
+            return CombatEvent::SimulationStart {
+                heroes: self.team.get_team_hero_names(),
+                heroes_hp: self.team.get_heroes_hp(),
+                encounter_hp,
+                barrier_hp,
+            };
+        });
+
+        self.team.apply_class_special_effects(&mut combat_log);
 
         // START QUEST
         while cont_fight {
             round += 1;
-            let heroes_hp_strings = self.team.get_heroes_hp_as_strings();
-            let (temp_ehp, temp_mehp) = self.encounter.get_hp_info();
-            log_queue.push(f!(
-                "\n\n--Round #{:#?}--\nEncounter HP: {:.2} ({:.2}%)  Heroes HP: {}\n",
-                round,
-                temp_ehp,
-                temp_ehp / temp_mehp * 100.0,
-                heroes_hp_strings
-            ));
+            combat_log.set_round(round);
+            combat_log.record(|| CombatEvent::RoundStart {
+                encounter_hp: self.encounter.get_hp_info().0,
+                heroes_hp: self.team.get_heroes_hp(),
+            });
 
             if update_target {
                 target_chance_heroes = self.team.calculate_targeting_chances();
                 update_target = false;
+                combat_log.record(|| CombatEvent::TargetChances {
+                    chances: target_chance_heroes.clone(),
+                });
             }
-            log_queue.push(f!("Team Target Chances: {:?}", target_chance_heroes));
 
             // Check for sensei bonus and extreme crit bonus
-            log_queue.push("Updating Ninja Bonus and Extreme Crit Bonus for Team".to_string());
-            let update_ninja_extreme_bonuses_logs = self
-                .team
-                .update_ninja_bonus_and_extreme_crit_bonus(round, is_extreme);
-            log_queue.extend(update_ninja_extreme_bonuses_logs);
+            self.team
+                .apply_round_start_class_effects_and_extreme_crit_bonus(
+                    round,
+                    is_extreme,
+                    &mut combat_log,
+                );
 
             // Mob Attacks
 
             // Mob AOE
             let (aoe_chance, aoe_damage) = self.encounter.get_aoe_info();
             let (crit_chance, crit_chance_modifier) = self.encounter.get_crit_info();
-            let (temp1, temp2, temp3, temp4, panic_string) = self.team.calculate_mob_attack(
+            let (temp1, temp2, temp3, panic_string) = self.team.calculate_mob_attack(
                 aoe_chance,
                 aoe_damage,
                 heroes_alive,
@@ -128,10 +125,12 @@ impl Simulation {
                 target_chance_heroes.clone(),
                 crit_chance,
                 crit_chance_modifier,
+                rng,
+                &mut combat_log,
             );
             if panic_string.len() > 0 {
-                for item in log_queue {
-                    info!("{}", item);
+                if combat_log.is_enabled() {
+                    info!("{}", combat_log.to_json_lines().unwrap_or_default());
                 }
                 error!("PANICKING: {}", panic_string);
                 log::logger().flush();
@@ -140,55 +139,47 @@ impl Simulation {
             heroes_alive = temp1;
             lord_save = temp2;
             update_target = temp3;
-            log_queue.extend(temp4);
 
-            if champion == "Hemma" {
-                let hemma_log_queue =
-                    self.team
-                        .calculate_hemma_drain(champion_innate_tier, hemma_mult, round);
-                log_queue.extend(hemma_log_queue);
-            }
+            self.team
+                .calculate_champion_drain_and_self_heal(round, is_boss, &mut combat_log);
 
-            let bnsroundeffects_log_queue = self
-                .team
-                .calculate_berserker_ninja_samurai_round_effects(round);
-            log_queue.extend(bnsroundeffects_log_queue);
+            self.team
+                .apply_class_effects_before_heroes_attack(round, &mut combat_log);
 
             // Heroes Attack
+            self.team
+                .update_round_aura_bonuses(round, is_boss, &mut combat_log);
             let (barrier_hp, barrier_hp_max, barrier_modifier, barrier_type) =
                 self.encounter.get_barrier_info();
             let encounter_evasion = self.encounter.get_evasion();
             let (encounter_hp, encounter_hp_max) = self.encounter.get_hp_info();
-            let (
-                polonia_loot,
-                barrier_modifier,
-                barrier_hp,
-                encounter_hp,
-                temp1,
-                hero_attack_log_queue,
-            ) = self.team.calculate_heroes_attack(
-                attack_order.clone(),
-                round,
-                rudo_bonus,
-                shark_active,
-                dinosaur_active,
-                barrier_modifier,
-                count_loot,
-                loot_chance,
-                encounter_evasion,
-                encounter_hp,
-                barrier_hp,
-                barrier_hp_max,
-                encounter_hp_max,
-                barrier_type,
-            );
+            let (champion_loot, barrier_modifier, barrier_hp, encounter_hp, temp1, temp2) =
+                self.team.calculate_heroes_attack(
+                    attack_order.clone(),
+                    round,
+                    shark_active,
+                    dinosaur_active,
+                    barrier_modifier,
+                    count_loot,
+                    loot_chance,
+                    encounter_evasion,
+                    encounter_hp,
+                    barrier_hp,
+                    barrier_hp_max,
+                    encounter_hp_max,
+                    barrier_type,
+                    rng,
+                    &mut combat_log,
+                );
             shark_active = temp1;
-            log_queue.extend(hero_attack_log_queue);
+            if temp2.is_some() {
+                barrier_broken_round = Some(round);
+                barrier_broken_by = temp2;
+            }
 
             self.encounter
                 .set_barrier_hp_and_modifier(barrier_hp, barrier_modifier);
             self.encounter.set_hp(encounter_hp);
-            log_queue.push("(Meta-Info) Barrier HP, Modifier and Encounter HP have been applied back to their objects".to_string());
 
             dinosaur_active = 0;
 
@@ -196,63 +187,39 @@ impl Simulation {
             if encounter_hp <= 0.0 {
                 cont_fight = false;
                 won_fight = true;
-                log_queue.push("Mob reduced to 0 HP".to_string());
             }
 
             // Check lost
             if heroes_alive == 0 {
                 // cont_fight = false; // Break instead
-                log_queue.push("No heroes remain alive".to_string());
                 break;
             }
 
-            // Calculate polonia loot
+            // Calculate champion loot
             if cont_fight == false {
-                polonia_loot_total += std::cmp::min(polonia_loot, polonia_loot_cap);
-                if polonia_loot >= polonia_loot_cap {
-                    polonia_loot_cap_hit += 1;
+                champion_loot_total += std::cmp::min(champion_loot, champion_loot_cap);
+                if champion_loot >= champion_loot_cap {
+                    champion_loot_cap_hit += 1;
                 }
-                log_queue.push(f!(
-                    "Polonia loot received {} of {}",
-                    polonia_loot,
-                    polonia_loot_cap
-                ));
+                combat_log.record(|| CombatEvent::ChampionLoot {
+                    loot: champion_loot,
+                    loot_cap: champion_loot_cap,
+                });
             }
 
-            if champion_innate_tier == 1 && round == 2 {
-                rudo_bonus = 0.0;
-            }
-            if (champion_innate_tier == 2 || champion_innate_tier == 3) && round == 3 {
-                rudo_bonus = 0.0;
-            }
-            if champion_innate_tier == 4 && round == 4 {
-                rudo_bonus = 0.0;
-            }
-            if champion == "Rudo" {
-                log_queue.push(f!(
-                    "Round is {}, Rudo bonus to break chance is: {}",
-                    round,
-                    rudo_bonus
-                ));
-            }
-
-            // Healing from Lizard, Cleric, and Lilo
+            // Healing from Lizard, Cleric, and the champion's aura
             if cont_fight {
-                let healing_log_queue = self
-                    .team
-                    .calculate_healing(champion.clone(), champion_innate_tier);
-                log_queue.extend(healing_log_queue);
+                self.team.calculate_healing(is_boss, &mut combat_log);
             }
 
-            // Check Berserker Activation
-            let berserker_log_queue = self.team.check_berserker_activation();
-            log_queue.extend(berserker_log_queue);
+            // Class effects at the end of the round, e.g. Berserker stage
+            self.team.apply_round_end_class_effects(&mut combat_log);
         }
 
         // TODO If key in metrics then add else skip
         let (ehprem, emaxhp) = self.encounter.get_hp_info();
-        let (team_crits_taken, team_crits_dealt, team_dodges, team_attacks_missed) =
-            self.team.get_heroes_accuracy_stats();
+        let team_accuracy_stats = self.team.get_heroes_accuracy_stats();
+        let (_, _, _, barrier_type) = self.encounter.get_barrier_info();
         let res = SimResult {
             success: won_fight,
             rounds_elapsed: round,
@@ -271,33 +238,28 @@ impl Simulation {
             hp_remaining_min: vec![0u32],
             team: self.team.clone(),
             encounter: self.encounter.clone(),
-            polonia_loot_total,
-            polonia_loot_cap_hit,
+            champion_loot_total,
+            champion_loot_cap_hit,
             encounter_hp_remaining: ehprem,
             encounter_max_hp: emaxhp,
-            team_crits_taken,
-            team_crits_dealt,
-            team_dodges,
-            team_attacks_missed,
+            team_accuracy_stats,
+            barrier_type,
+            barrier_broken_round,
+            barrier_broken_by,
         };
 
-        if won_fight {
-            log_queue.push("Won Simulation".to_string());
-        } else {
-            log_queue.push("ALERT: Lost Simulation".to_string());
-        }
-
-        if self.log_all || !won_fight {
-            for item in log_queue {
-                info!("{}", item);
-            }
-        }
+        combat_log.record(|| CombatEvent::SimulationEnd { success: won_fight });
+        self.combat_log = combat_log;
         return Ok(res);
     }
+
+    pub fn get_combat_log(&self) -> &CombatLog {
+        return &self.combat_log;
+    }
 }
 
 /// Create a simulation performing type validation and calculating certain fields
-/// If log_all is false simulation actions are only logged when the simulation fails, else all actions are logged
+/// If log_all is true every simulation event is recorded to the combat log, else no events are recorded
 pub fn create_simulation(
     team: &Team,
     encounter: Encounter,
@@ -309,6 +271,7 @@ pub fn create_simulation(
         encounter,
         metrics,
         log_all,
+        combat_log: Default::default(),
     };
 
     return Ok(simulation);
@@ -336,15 +299,14 @@ pub struct SimResult {
     // other
     team: Team,
     encounter: Encounter,
-    polonia_loot_total: u8,
-    polonia_loot_cap_hit: i32,
+    champion_loot_total: u8,
+    champion_loot_cap_hit: i32,
     encounter_hp_remaining: f64,
     encounter_max_hp: f64,
-    // team accuracy stats
-    team_crits_taken: Vec<u8>,
-    team_crits_dealt: Vec<u8>,
-    team_dodges: Vec<u8>,
-    team_attacks_missed: Vec<u8>,
+    team_accuracy_stats: Vec<HeroAccuracyStats>,
+    barrier_type: Option<ElementType>, // The barrier element of an Extreme encounter, None if it had no barrier
+    barrier_broken_round: Option<i16>, // None if the barrier was never broken
+    barrier_broken_by: Option<String>, // The identifier of the hero whose hit broke the barrier
 }
 
 impl SimResult {
@@ -381,42 +343,30 @@ impl SimResult {
         return self.encounter_hp_remaining;
     }
 
-    pub fn get_team_hp_remaining(&self) -> [f64; 5] {
-        return convert_vec_to_max_team_sized_array(self.team.get_heroes_hp());
+    /// Returns the hp remaining of each hero on the team, in team order
+    pub fn get_team_hp_remaining(&self) -> Vec<f64> {
+        return self.team.get_heroes_hp();
     }
 
-    pub fn get_team_damage_dealt(&self) -> [f64; 5] {
-        return convert_vec_to_max_team_sized_array(self.get_damage_dealt_during_fight());
+    /// Returns the damage dealt by each hero on the team, in team order
+    pub fn get_team_damage_dealt(&self) -> Vec<f64> {
+        return self.get_damage_dealt_during_fight();
     }
 
-    pub fn get_team_crits_taken(&self) -> [u8; 5] {
-        return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().0);
+    /// Returns the attack and defense counts of each hero on the team, in team order
+    pub fn get_team_accuracy_stats(&self) -> Vec<HeroAccuracyStats> {
+        return self.team_accuracy_stats.clone();
     }
 
-    pub fn get_team_crits_dealt(&self) -> [u8; 5] {
-        return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().1);
+    pub fn get_barrier_type(&self) -> Option<ElementType> {
+        return self.barrier_type;
     }
 
-    pub fn get_team_dodges(&self) -> [u8; 5] {
-        return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().2);
+    pub fn get_barrier_broken_round(&self) -> Option<i16> {
+        return self.barrier_broken_round;
     }
 
-    pub fn get_team_attacks_missed(&self) -> [u8; 5] {
-        return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().3);
-    }
-}
-
-/// input_vector is converted to an array sized to match the max team size.
-/// Excess values are truncated
-fn convert_vec_to_max_team_sized_array<T: Default + Clone>(input_vector: Vec<T>) -> [T; 5] {
-    let mut res_array: [T; 5] = Default::default();
-    for i in 0..res_array.len() {
-        if i >= input_vector.len() {
-            // ran out of input values, initialize rest as 0s
-            res_array[i] = T::default();
-        } else {
-            res_array[i] = input_vector[i].clone();
-        }
+    pub fn get_barrier_broken_by(&self) -> Option<String> {
+        return self.barrier_broken_by.clone();
     }
-    return res_array;
 }
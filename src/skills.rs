@@ -1,5 +1,25 @@
 use serde::{Deserialize, Serialize};
 
+/// Categories of hero skills of which a skillset may only contain a limited number
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SkillCategory {
+    BronzeMastery,
+    GoldMastery,
+}
+
+impl SkillCategory {
+    /// Returns the maximum number of skills of the category allowed in a single skillset
+    pub fn get_cap(&self) -> usize {
+        return match self {
+            SkillCategory::BronzeMastery => 1,
+            SkillCategory::GoldMastery => 1,
+        };
+    }
+}
+
+/// Item types equipped in the off-hand slot rather than the weapon slot
+pub const OFF_HAND_ITEM_TYPES: [&str; 1] = ["Shield"];
+
 /// Information on hero skills
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct HeroSkill {
@@ -39,6 +59,34 @@ impl HeroSkill {
         return self.skill_tier.clone();
     }
 
+    /// Returns how hard the skill is to roll by its rarity, from 1 for common to 3 for epic, or 0 if the rarity is unknown
+    pub fn get_rarity_rank(&self) -> u8 {
+        return match self.rarity.as_str() {
+            "Common" => 1,
+            "Rare" => 2,
+            "Epic" => 3,
+            _ => 0,
+        };
+    }
+
+    /// Returns the capped category of the skill, if any
+    /// Weapon-conditional skills are masteries, bronze if common (e.g. Dagger Master) and gold if epic (e.g. Warlord)
+    /// Off-hand skills (e.g. Shield Master) are not weapon masteries, so they are never capped
+    pub fn get_category(&self) -> Option<SkillCategory> {
+        if !self
+            .item_types
+            .iter()
+            .any(|itype| itype.len() > 0 && !OFF_HAND_ITEM_TYPES.contains(&itype.as_str()))
+        {
+            return None;
+        }
+        return match self.rarity.as_str() {
+            "Common" => Some(SkillCategory::BronzeMastery),
+            "Epic" => Some(SkillCategory::GoldMastery),
+            _ => None,
+        };
+    }
+
     pub fn get_element_qty_req(&self) -> u16 {
         return self.element_qty_req.clone();
     }
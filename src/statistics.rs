@@ -0,0 +1,162 @@
+/// The z-score for a two-sided 95% confidence interval
+pub const Z_95: f64 = 1.959964;
+
+/// Calculates the Wilson score interval for a binomial proportion with the given z-score
+/// Returns (lower, upper) bounds in range [0,1]. With no trials the interval is the whole range
+pub fn wilson_interval(successes: usize, trials: usize, z: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let margin = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+    return ((center - margin).max(0.0), (center + margin).min(1.0));
+}
+
+/// Returns the sample standard deviation of the values, or 0 with fewer than two values
+pub fn std_dev(values: &Vec<f64>) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let sum_of_squares = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>();
+    return (sum_of_squares / (n - 1.0)).sqrt();
+}
+
+/// Returns the value at the given percentile (in range [0,100]) of the values, interpolating linearly between the nearest ranks
+/// Returns 0 for no values
+pub fn percentile(values: &Vec<f64>, pct: f64) -> f64 {
+    if values.len() == 0 {
+        return 0.0;
+    }
+    let mut sorted = values.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let rank = (pct / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    return sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64);
+}
+
+/// Approximates the standard normal cumulative distribution function (Abramowitz and Stegun 26.2.17, error below 7.5e-8)
+pub fn normal_cdf(z: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.2316419 * z.abs());
+    let density = (-z * z / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt();
+    let tail = density
+        * t
+        * (0.319381530
+            + t * (-0.356563782 + t * (1.781477937 + t * (-1.821255978 + t * 1.330274429))));
+    if z >= 0.0 {
+        return 1.0 - tail;
+    }
+    return tail;
+}
+
+/// Tests whether two binomial proportions differ using a pooled two-proportion z-test
+/// Returns (z-score, two-sided p-value). The z-score is positive when a's proportion is higher
+/// When the proportions cannot differ (no trials, or all or none succeeded in both) the p-value is 1
+pub fn two_proportion_z_test(
+    successes_a: usize,
+    trials_a: usize,
+    successes_b: usize,
+    trials_b: usize,
+) -> (f64, f64) {
+    if trials_a == 0 || trials_b == 0 {
+        return (0.0, 1.0);
+    }
+    let n_a = trials_a as f64;
+    let n_b = trials_b as f64;
+    let p_a = successes_a as f64 / n_a;
+    let p_b = successes_b as f64 / n_b;
+    let p_pooled = (successes_a + successes_b) as f64 / (n_a + n_b);
+    let standard_error = (p_pooled * (1.0 - p_pooled) * (1.0 / n_a + 1.0 / n_b)).sqrt();
+    if standard_error == 0.0 {
+        return (0.0, 1.0);
+    }
+    let z = (p_a - p_b) / standard_error;
+    return (z, 2.0 * (1.0 - normal_cdf(z.abs())));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn wilson_interval_matches_reference_values() {
+        let (lower, upper) = wilson_interval(50, 100, Z_95);
+        assert_close(lower, 0.403832);
+        assert_close(upper, 0.596168);
+
+        let (lower, upper) = wilson_interval(95, 100, Z_95);
+        assert_close(lower, 0.888250);
+        assert_close(upper, 0.978456);
+
+        // No successes still gives a non-zero upper bound
+        let (lower, upper) = wilson_interval(0, 10, Z_95);
+        assert_close(lower, 0.0);
+        assert_close(upper, 0.277533);
+
+        assert_eq!(wilson_interval(0, 0, Z_95), (0.0, 1.0));
+    }
+
+    #[test]
+    fn std_dev_is_the_sample_standard_deviation() {
+        assert_close(
+            std_dev(&vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+            2.138090,
+        );
+        assert_eq!(std_dev(&vec![3.0]), 0.0);
+    }
+
+    #[test]
+    fn percentile_interpolates_between_ranks() {
+        let values = vec![4.0, 1.0, 3.0, 2.0];
+        assert_close(percentile(&values, 0.0), 1.0);
+        assert_close(percentile(&values, 50.0), 2.5);
+        assert_close(percentile(&values, 100.0), 4.0);
+
+        let values: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+        assert_close(percentile(&values, 10.0), 1.9);
+        assert_close(percentile(&values, 90.0), 9.1);
+
+        assert_eq!(percentile(&vec![], 50.0), 0.0);
+    }
+
+    #[test]
+    fn normal_cdf_matches_reference_values() {
+        assert_close(normal_cdf(0.0), 0.5);
+        assert_close(normal_cdf(Z_95), 0.975);
+        assert_close(normal_cdf(-1.0), 0.158655);
+        assert_close(normal_cdf(2.5), 0.993790);
+    }
+
+    #[test]
+    fn two_proportion_z_test_matches_reference_values() {
+        let (z, p_value) = two_proportion_z_test(60, 100, 40, 100);
+        assert_close(z, 2.828427);
+        assert_close(p_value, 0.004678);
+
+        let (z, p_value) = two_proportion_z_test(40, 100, 60, 100);
+        assert_close(z, -2.828427);
+        assert_close(p_value, 0.004678);
+
+        let (z, p_value) = two_proportion_z_test(50, 100, 50, 100);
+        assert_close(z, 0.0);
+        assert_close(p_value, 1.0);
+        assert_eq!(two_proportion_z_test(100, 100, 200, 200), (0.0, 1.0));
+        assert_eq!(two_proportion_z_test(5, 10, 0, 0), (0.0, 1.0));
+    }
+}
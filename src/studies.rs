@@ -1,15 +1,31 @@
-// pub mod single_hero_skill_study;
+pub mod gear_optimization_study;
+pub mod single_hero_skill_study;
 pub mod static_duo_skill_study;
+pub mod team_composition_study;
+pub mod team_skill_study;
 
-use std::{collections::HashMap, sync::mpsc::Sender, time::Instant};
+use std::{cmp::Ordering, collections::HashMap, sync::mpsc::Sender, time::Instant};
 
-use indicatif::{MultiProgress, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    decimals::{round_to_2, round_to_4},
+    dungeons::TrialDungeon,
     equipment::Blueprint,
     hero_builder::HeroClass,
-    skills::{HeroSkill, InnateSkill},
+    heroes::Team,
+    inputs::convert_loaded_heroes_to_sim_heroes,
+    scoring::{
+        create_score_normalization, get_team_avg_dmg, get_team_avg_hp_remaining,
+        get_team_avg_survival_rate, ReportSettings, ScoreNormalization,
+    },
+    skills::{HeroSkill, InnateSkill, SkillCategory},
+    trials::{
+        create_trial, get_difficulty_name, load_trial_results_from_csv, AdaptiveSampling, Trial,
+        TrialResult,
+    },
 };
 
 extern crate csv;
@@ -20,10 +36,21 @@ extern crate csv;
 pub struct Study {
     identifier: String,
     description: String,
-    simulation_qty: i32,
+    simulation_qty: i32, // The number of simulations per trial, or the maximum number when sampling adaptively
     runoff_scoring_threshold: f64, // The top X% of the results will be re-tested on the n+1 dungeon in the dungeons vec until either there are no successes or the vec is exhausted. Pass 100.0 to disable runoff scoring
     status: StudyStatus,
     hero_builder_information: HeroBuilderInformation,
+    seed: u64, // The seed passed to every trial in the study, so that re-running a study reproduces its results exactly
+    docket_row_hash: u64, // A hash of the docket row the study was created from, used to detect a checkpoint from a differently configured study
+    output_path: String,  // The directory all output for the study is written under
+    adaptive_sampling: Option<AdaptiveSampling>, // When set, trials sample in batches until their success rate interval is narrow enough
+    combat_log_cap: usize, // The maximum number of failed simulations per trial to save combat logs for
+    dungeons: Vec<TrialDungeon>, // The dungeons to be tested in the study. Only the first will be used unless runoff scoring is enabled
+    automatic_rank_difficulty_optimization: bool, // Whether to re-trial variations above the rank difficulty ceiling on the next difficulty of the same dungeon
+    rank_difficulty_ceiling: f64, // The success rate (as a percent) above which a variation is escalated to the next difficulty
+    ranked_variations: Vec<RankedVariation>, // Each variation trialed so far with its result on every stage it reached
+    stage_best_lower_bounds: HashMap<usize, f64>, // The highest success rate interval lower bound on each stage, used to eliminate clearly losing variations when sampling adaptively
+    report_settings: ReportSettings, // How variations are scored and summarized in the report written when the study finishes
 }
 
 pub fn create_study(
@@ -32,6 +59,15 @@ pub fn create_study(
     simulation_qty: i32,
     runoff_scoring_threshold: f64,
     hero_builder_information: HeroBuilderInformation,
+    seed: u64,
+    docket_row_hash: u64,
+    output_path: String,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    combat_log_cap: usize,
+    dungeons: Vec<TrialDungeon>,
+    automatic_rank_difficulty_optimization: bool,
+    rank_difficulty_ceiling: f64,
+    report_settings: ReportSettings,
 ) -> Study {
     return Study {
         identifier,
@@ -40,19 +76,1016 @@ pub fn create_study(
         runoff_scoring_threshold,
         status: StudyStatus::Created,
         hero_builder_information,
+        seed,
+        docket_row_hash,
+        output_path,
+        adaptive_sampling,
+        combat_log_cap,
+        dungeons,
+        automatic_rank_difficulty_optimization,
+        rank_difficulty_ceiling,
+        ranked_variations: vec![],
+        stage_best_lower_bounds: HashMap::new(),
+        report_settings,
     };
 }
 
+impl Study {
+    /// Returns the adaptive sampling settings for a trial, if enabled
+    /// A trial stops early once its success rate is clearly below the elimination threshold
+    pub fn get_adaptive_sampling_for_trial(
+        &self,
+        elimination_threshold: Option<f64>,
+    ) -> Option<AdaptiveSampling> {
+        return self.adaptive_sampling.map(|adaptive| AdaptiveSampling {
+            elimination_threshold,
+            ..adaptive
+        });
+    }
+
+    pub fn get_identifier(&self) -> String {
+        return self.identifier.to_string();
+    }
+
+    pub fn get_output_path(&self) -> String {
+        return self.output_path.to_string();
+    }
+
+    pub fn get_ranked_variations(&self) -> &Vec<RankedVariation> {
+        return &self.ranked_variations;
+    }
+
+    pub fn get_skill_abbreviation_map(&self) -> &HashMap<String, String> {
+        return &self.hero_builder_information.hero_skill_abbreviation_map;
+    }
+
+    pub fn get_hero_skill_map(&self) -> &HashMap<String, HeroSkill> {
+        return &self.hero_builder_information.hero_skill_map;
+    }
+
+    /// Create a team from the base team with the subject hero rebuilt using the given skillset
+    /// Alternate equipment is substituted where the skillset calls for it, returning a description of each substitution along with the team
+    pub fn create_team_with_subject_hero_skillset(
+        &self,
+        base_team: &Team,
+        subject_hero_identifier: &String,
+        subject_hero_builder: &crate::hero_builder::Hero,
+        skill_variation: &Vec<String>,
+    ) -> (Team, Vec<String>) {
+        let mut new_hero = subject_hero_builder.clone();
+        new_hero.set_hero_skills(skill_variation.clone());
+        let equipment_substitutions = new_hero.substitute_alternate_equipment_for_skills(
+            &self.hero_builder_information.bp_map,
+            &self.hero_builder_information.hero_classes,
+            &self.hero_builder_information.hero_skill_map,
+        );
+        let new_team =
+            self.create_team_with_subject_hero(base_team, subject_hero_identifier, new_hero);
+        return (new_team, equipment_substitutions);
+    }
+
+    /// Create a team from the base team with the subject hero replaced by the given hero builder representation of it
+    pub fn create_team_with_subject_hero(
+        &self,
+        base_team: &Team,
+        subject_hero_identifier: &String,
+        subject_hero_builder: crate::hero_builder::Hero,
+    ) -> Team {
+        let mut new_team = base_team.clone();
+        let target_hero_index = new_team
+            .get_index_of_hero_with_identifier(subject_hero_identifier)
+            .unwrap();
+        let heroes_hashmap: HashMap<String, crate::hero_builder::Hero> =
+            HashMap::from([(subject_hero_identifier.to_string(), subject_hero_builder)]);
+        let new_sim_heroes = convert_loaded_heroes_to_sim_heroes(
+            heroes_hashmap,
+            self.hero_builder_information.bp_map.clone(),
+            self.hero_builder_information
+                .hero_skill_tier_1_name_map
+                .clone(),
+            self.hero_builder_information.hero_skill_map.clone(),
+            self.hero_builder_information
+                .class_innate_skill_names_map
+                .clone(),
+            self.hero_builder_information.innate_skill_map.clone(),
+        );
+        new_team.set_hero_at_index(
+            target_hero_index,
+            new_sim_heroes[subject_hero_identifier].clone(),
+        );
+        return new_team;
+    }
+}
+
+/// Abbreviate each skill where possible, returning the skills in their original order
+pub fn abbreviate_skills(
+    skills: &Vec<String>,
+    skill_abbreviation_map: &HashMap<String, String>,
+) -> Vec<String> {
+    return skills
+        .iter()
+        .map(|skill| {
+            skill_abbreviation_map
+                .get(skill)
+                .unwrap_or(skill)
+                .to_string()
+        })
+        .collect();
+}
+
+/// The minimum number of seconds between checkpoints while a study is running
+pub const CHECKPOINT_INTERVAL_SECONDS: u64 = 60;
+
 /// Runnable studies must have a run function
 pub trait Runnable {
     fn run(
         &mut self,
         m: &MultiProgress,
         m_sty: &ProgressStyle,
-        tx: Sender<(String, u32, u32, Instant)>,
+        tx: Sender<(String, u64, u64, Instant)>,
     );
 }
 
+/// A study that trials each of a fixed list of variations (e.g. skillsets) and ranks them
+/// Implementors describe the variations and how they are saved, the run loop, runoff stages and checkpointing are shared
+pub trait VariationStudy {
+    fn get_study(&self) -> &Study;
+    fn get_study_mut(&mut self) -> &mut Study;
+    fn get_combination_index(&self) -> i64;
+    fn set_combination_index(&mut self, combination_index: i64);
+    fn count_variations_total(&self) -> i64;
+    fn get_variation_at_combination_index(&self, combination_index: i64) -> Vec<String>;
+    /// Returns the team along with a description of any alternate equipment substituted for the variation
+    fn create_team_for_variation(&self, variation: &Vec<String>) -> (Team, Vec<String>);
+    /// The message shown on the study's progress bar
+    fn get_progress_message(&self) -> String;
+    /// The csvs appended to by save_trial_results, truncated when resuming from a checkpoint
+    fn get_trial_csv_paths(&self) -> Vec<String>;
+    /// Save the results of a completed trial
+    fn save_trial_results(&self, trial: &Trial) -> Result<(), std::io::Error>;
+    /// Save any output for the study as a whole, called once the variations have been ranked
+    fn save_study_results(&self) -> Result<(), std::io::Error>;
+
+    /// The factor the variation's success rate is scaled by in its composite score
+    fn get_success_rate_factor(&self, _variation: &Vec<String>) -> f64 {
+        return 1.0;
+    }
+
+    fn count_variations_remaining(&self) -> i64 {
+        return self.count_variations_total() - self.get_combination_index();
+    }
+
+    /// Trial every variation on the first dungeon, run any additional stages, then rank and save the variations
+    fn run_variation_study(
+        &mut self,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: Sender<(String, u64, u64, Instant)>,
+    ) {
+        let study_identifier = self.get_study().get_identifier();
+        let study_start_instant = Instant::now();
+        tx.send((
+            study_identifier.to_string(),
+            0u64,
+            self.count_variations_total() as u64,
+            study_start_instant,
+        ))
+        .unwrap();
+        info!("Start Study: {}", study_identifier);
+
+        self.get_study_mut().status = StudyStatus::Running;
+
+        let pb = m.add(ProgressBar::new(
+            self.count_variations_total().try_into().unwrap(),
+        ));
+        pb.set_style(m_sty.clone());
+        pb.set_message(self.get_progress_message());
+
+        // A study starting over moves aside the csvs of any earlier run, so the rows resumed from are always this run's
+        if !self.resume_from_checkpoint() {
+            rotate_csvs(&self.get_trial_csv_paths()).unwrap();
+        }
+        self.save_checkpoint();
+        let mut last_checkpoint_instant = Instant::now();
+
+        let initial_trial_dungeon = self.get_study().dungeons[0].clone();
+        while self.count_variations_remaining() > 0 {
+            tx.send((
+                study_identifier.to_string(),
+                self.get_combination_index() as u64,
+                self.count_variations_total() as u64,
+                study_start_instant,
+            ))
+            .unwrap();
+            pb.set_position(self.get_combination_index().try_into().unwrap());
+
+            let variation = self.get_variation_at_combination_index(self.get_combination_index());
+            let trial_result = self.run_trial_for_variation(&variation, &initial_trial_dungeon, 0);
+            self.get_study_mut()
+                .ranked_variations
+                .push(create_ranked_variation(variation, 0, trial_result));
+            self.set_combination_index(self.get_combination_index() + 1);
+
+            if last_checkpoint_instant.elapsed().as_secs() >= CHECKPOINT_INTERVAL_SECONDS {
+                self.save_checkpoint();
+                last_checkpoint_instant = Instant::now();
+            }
+        }
+
+        // Outside While, this is assumed but check anyways because why not...
+        if self.count_variations_remaining() == 0 {
+            tx.send((
+                study_identifier.to_string(),
+                self.count_variations_total() as u64,
+                self.count_variations_total() as u64,
+                study_start_instant,
+            ))
+            .unwrap();
+            pb.set_position(self.count_variations_total().try_into().unwrap());
+
+            // Additional stages are re-run in full if the study is interrupted during them
+            self.save_checkpoint();
+            self.run_additional_stages(m, m_sty, &tx);
+
+            rank_variations(&mut self.get_study_mut().ranked_variations);
+            save_ranked_variations_to_csv(
+                f!("{}/csvs/runoff_ranking.csv", self.get_study().output_path),
+                &self.get_study().ranked_variations,
+                self.get_study().get_skill_abbreviation_map(),
+            )
+            .unwrap();
+            self.save_study_results().unwrap();
+            save_ranked_results_to_csv_and_summary(
+                f!("{}/csvs/ranked_results.csv", self.get_study().output_path),
+                f!("{}/summary.md", self.get_study().output_path),
+                self.get_study(),
+                &|variation| self.get_success_rate_factor(variation),
+            )
+            .unwrap();
+            self.remove_checkpoint();
+
+            self.get_study_mut().status = StudyStatus::Finished;
+            pb.finish_with_message("Study Complete");
+        } else {
+            error!("This should not occur, while running study managed to escape while loop with variations remaining...");
+            log::logger().flush();
+            panic!("This should not occur, while running study managed to escape while loop with variations remaining...")
+        }
+    }
+
+    fn get_checkpoint_path(&self) -> String {
+        return f!("{}/checkpoint.yaml", self.get_study().output_path);
+    }
+
+    /// Returns the directory for the combat logs of a variation's trial on the given stage, named using the abbreviated variation
+    fn get_combat_log_directory(&self, variation: &Vec<String>, stage: usize) -> String {
+        let abbr_variation: Vec<String> =
+            abbreviate_skills(variation, self.get_study().get_skill_abbreviation_map())
+                .iter()
+                .map(|abbr| abbr.replace(|c: char| !c.is_alphanumeric(), ""))
+                .collect();
+        return f!(
+            "{}/logs/stage_{}/{}",
+            self.get_study().output_path,
+            stage,
+            abbr_variation.join("_")
+        );
+    }
+
+    /// Save the study's progress, along with the current length of each csv the study appends to
+    /// The ranked variations are not saved, they are rebuilt from the trial results csv when resuming
+    fn save_checkpoint(&self) {
+        let study = self.get_study();
+        let checkpoint = StudyCheckpoint {
+            docket_row_hash: study.docket_row_hash,
+            seed: study.seed,
+            combination_index: self.get_combination_index(),
+            combination_total: self.count_variations_total(),
+            stage_best_lower_bounds: study.stage_best_lower_bounds.clone(),
+            csv_lengths: get_csv_lengths(&self.get_trial_csv_paths()),
+        };
+        save_study_checkpoint(&self.get_checkpoint_path(), &checkpoint).unwrap();
+    }
+
+    /// Restore the study's progress from its checkpoint, if there is one
+    /// Rows written to the csvs after the checkpoint was saved are discarded, since those combinations will be trialed again
+    /// The checkpoint is ignored if the study's docket row, seed or number of combinations has changed since it was saved
+    /// Returns whether the study resumed from its checkpoint
+    fn resume_from_checkpoint(&mut self) -> bool {
+        let checkpoint_path = self.get_checkpoint_path();
+        let checkpoint = match load_study_checkpoint(&checkpoint_path) {
+            Some(checkpoint) => checkpoint,
+            None => return false,
+        };
+
+        let study = self.get_study();
+        if checkpoint.docket_row_hash != study.docket_row_hash || checkpoint.seed != study.seed {
+            warn!(
+                "Ignoring checkpoint for study {}: it was saved for a different docket row or seed",
+                study.identifier
+            );
+            return false;
+        }
+        if checkpoint.combination_total != self.count_variations_total() {
+            warn!(
+                "Ignoring checkpoint for study {}: it was saved for {} combinations but the study has {}",
+                study.identifier,
+                checkpoint.combination_total,
+                self.count_variations_total()
+            );
+            return false;
+        }
+
+        truncate_csvs_to_lengths(&checkpoint.csv_lengths).unwrap();
+
+        // Each combination trialed before the checkpoint saved one row on the first stage, so they are the last rows of the csv
+        let trial_results_csv_path = f!("{}/csvs/trial_results.csv", study.output_path);
+        let mut trial_results = match load_trial_results_from_csv(&trial_results_csv_path) {
+            Ok(trial_results) => trial_results,
+            Err(_) if checkpoint.combination_index == 0 => vec![],
+            Err(e) => {
+                warn!(
+                    "Ignoring checkpoint for study {}: failed to read {}: {}",
+                    study.identifier, trial_results_csv_path, e
+                );
+                return false;
+            }
+        };
+        let resumed_qty = checkpoint.combination_index as usize;
+        if trial_results.len() < resumed_qty {
+            warn!(
+                "Ignoring checkpoint for study {}: it was saved after {} combinations but {} has {} rows",
+                study.identifier,
+                resumed_qty,
+                trial_results_csv_path,
+                trial_results.len()
+            );
+            return false;
+        }
+        let trial_results = trial_results.split_off(trial_results.len() - resumed_qty);
+
+        let mut ranked_variations: Vec<RankedVariation> = vec![];
+        for (combination_index, trial_result) in trial_results.into_iter().enumerate() {
+            let variation = self.get_variation_at_combination_index(combination_index as i64);
+            if trial_result.get_trial_description() != format!("{:?}", variation) {
+                warn!(
+                    "Ignoring checkpoint for study {}: row {} of {} does not match combination {}",
+                    study.identifier, combination_index, trial_results_csv_path, combination_index
+                );
+                return false;
+            }
+            ranked_variations.push(create_ranked_variation(variation, 0, trial_result));
+        }
+
+        self.set_combination_index(checkpoint.combination_index);
+        let study = self.get_study_mut();
+        study.ranked_variations = ranked_variations;
+        study.stage_best_lower_bounds = checkpoint.stage_best_lower_bounds;
+        info!(
+            "Resuming study {} from combination {} of {}",
+            study.identifier, checkpoint.combination_index, checkpoint.combination_total
+        );
+        return true;
+    }
+
+    fn remove_checkpoint(&self) {
+        let checkpoint_path = self.get_checkpoint_path();
+        if std::path::Path::new(&checkpoint_path).exists() {
+            std::fs::remove_file(checkpoint_path).unwrap();
+        }
+    }
+
+    /// Run the stages following the initial stage
+    /// When enabled, variations above the rank difficulty ceiling are escalated through harder difficulties of each dungeon
+    /// and the top runoff_scoring_threshold% of variations are re-trialed on each subsequent dungeon until the dungeons are exhausted or no variation succeeds
+    fn run_additional_stages(
+        &mut self,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: &Sender<(String, u64, u64, Instant)>,
+    ) {
+        let mut next_stage = 1;
+        let mut dungeon_base_stage = 0;
+        let mut dungeon_index = 0;
+        loop {
+            if self.get_study().automatic_rank_difficulty_optimization {
+                let base_trial_dungeon = self.get_study().dungeons[dungeon_index].clone();
+                next_stage = self.run_difficulty_escalation(
+                    &base_trial_dungeon,
+                    dungeon_base_stage,
+                    next_stage,
+                    m,
+                    m_sty,
+                    tx,
+                );
+            }
+
+            dungeon_index += 1;
+            if self.get_study().runoff_scoring_threshold >= 100.0
+                || dungeon_index >= self.get_study().dungeons.len()
+            {
+                break;
+            }
+
+            rank_variations(&mut self.get_study_mut().ranked_variations);
+            let candidates = select_runoff_candidates(
+                &self.get_study().ranked_variations,
+                dungeon_base_stage,
+                self.get_study().runoff_scoring_threshold,
+            );
+            if candidates.len() == 0 {
+                info!(
+                    "No variations succeeded on stage {}, ending runoff for study {}",
+                    dungeon_base_stage,
+                    self.get_study().identifier
+                );
+                break;
+            }
+
+            let trial_dungeon = self.get_study().dungeons[dungeon_index].clone();
+            self.run_stage(next_stage, &trial_dungeon, &candidates, m, m_sty, tx);
+            dungeon_base_stage = next_stage;
+            next_stage += 1;
+        }
+    }
+
+    /// Re-trial variations whose success rate exceeds the rank difficulty ceiling on the next difficulty of the same dungeon
+    /// Repeats until no variation exceeds the ceiling or Boss Extreme has been trialed, returning the next unused stage index
+    fn run_difficulty_escalation(
+        &mut self,
+        base_trial_dungeon: &TrialDungeon,
+        from_stage: usize,
+        mut next_stage: usize,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: &Sender<(String, u64, u64, Instant)>,
+    ) -> usize {
+        let mut current_stage = from_stage;
+        let mut trial_dungeon = base_trial_dungeon.clone();
+
+        while trial_dungeon.difficulty < 8 {
+            let saturated = select_saturated_variations(
+                &self.get_study().ranked_variations,
+                current_stage,
+                self.get_study().rank_difficulty_ceiling,
+            );
+            if saturated.len() == 0 {
+                break;
+            }
+
+            trial_dungeon.difficulty += 1;
+            info!(
+                "{} variations exceeded the rank difficulty ceiling on stage {}, escalating to {}",
+                saturated.len(),
+                current_stage,
+                get_difficulty_name(trial_dungeon.difficulty)
+            );
+            self.run_stage(next_stage, &trial_dungeon, &saturated, m, m_sty, tx);
+            current_stage = next_stage;
+            next_stage += 1;
+        }
+        return next_stage;
+    }
+
+    /// Trial each of the candidate variations (indices into ranked_variations) on the trial dungeon, recording the results under stage
+    fn run_stage(
+        &mut self,
+        stage: usize,
+        trial_dungeon: &TrialDungeon,
+        candidates: &Vec<usize>,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: &Sender<(String, u64, u64, Instant)>,
+    ) {
+        let stage_identifier = f!(
+            "{} (Stage {}: {} {})",
+            self.get_study().identifier,
+            stage,
+            trial_dungeon.dungeon._get_zone(),
+            get_difficulty_name(trial_dungeon.difficulty)
+        );
+        let stage_start_instant = Instant::now();
+        let pb = m.add(ProgressBar::new(candidates.len() as u64));
+        pb.set_style(m_sty.clone());
+        pb.set_message(stage_identifier.to_string());
+
+        for (i, candidate) in candidates.iter().enumerate() {
+            tx.send((
+                stage_identifier.to_string(),
+                i as u64,
+                candidates.len() as u64,
+                stage_start_instant,
+            ))
+            .unwrap();
+            pb.set_position(i as u64);
+
+            let variation = self.get_study().ranked_variations[*candidate].get_variation();
+            let trial_result = self.run_trial_for_variation(&variation, trial_dungeon, stage);
+            self.get_study_mut().ranked_variations[*candidate]
+                .add_stage_result(stage, trial_result);
+        }
+
+        tx.send((
+            stage_identifier.to_string(),
+            candidates.len() as u64,
+            candidates.len() as u64,
+            stage_start_instant,
+        ))
+        .unwrap();
+        pb.finish_with_message(f!("{} Complete", stage_identifier));
+    }
+
+    /// Run a trial of the variation against the trial dungeon for the given stage, saving and returning its result
+    fn run_trial_for_variation(
+        &mut self,
+        variation: &Vec<String>,
+        trial_dungeon: &TrialDungeon,
+        stage: usize,
+    ) -> TrialResult {
+        let (new_team, equipment_substitutions) = self.create_team_for_variation(variation);
+        let study = self.get_study();
+
+        // Create new trial with new team
+        let mut trial = create_trial(
+            format!("{}", study.identifier),
+            format!("{:?}", variation),
+            study.simulation_qty as usize,
+            new_team,
+            trial_dungeon.dungeon.clone(),
+            [trial_dungeon.difficulty].to_vec(),
+            trial_dungeon.force_minibosses,
+            false,
+            study.seed,
+        )
+        .unwrap();
+        trial.set_equipment_substitutions(equipment_substitutions);
+        trial.set_barrier_setting(trial_dungeon.barrier_setting);
+        trial.set_combat_logging(
+            self.get_combat_log_directory(variation, stage),
+            study.combat_log_cap,
+        );
+        trial.set_adaptive_sampling(
+            study.get_adaptive_sampling_for_trial(
+                study.stage_best_lower_bounds.get(&stage).copied(),
+            ),
+        );
+
+        // Run simulations
+        let timer = Instant::now();
+        trial.run_simulations_parallel();
+        let timer_duration = timer.elapsed().as_nanos() as f32 / 1000000.0f32;
+        info!("Completed trial in {:#?}ms.", timer_duration,);
+        trial.save_combat_logs().unwrap();
+
+        for csv_path in self.get_trial_csv_paths() {
+            if let Some(p) = std::path::Path::new(&csv_path).parent() {
+                std::fs::create_dir_all(p).unwrap();
+            }
+        }
+        self.save_trial_results(&trial).unwrap();
+
+        let trial_result = trial.create_trial_result();
+        let (lower_bound, _) = trial_result.get_success_rate_interval();
+        let best_lower_bound = self
+            .get_study_mut()
+            .stage_best_lower_bounds
+            .entry(stage)
+            .or_insert(0.0);
+        if lower_bound > *best_lower_bound {
+            *best_lower_bound = lower_bound;
+        }
+        return trial_result;
+    }
+}
+
+/// A variation (e.g. a skillset) trialed by a study, with its result on each stage of the study it reached
+/// Stages are numbered in the order they are run, so a variation that reached a higher stage outlasted those that did not
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RankedVariation {
+    variation: Vec<String>,
+    stage_results: Vec<(usize, TrialResult)>, // (stage index, result) in the order the stages were run
+}
+
+impl RankedVariation {
+    pub fn get_variation(&self) -> Vec<String> {
+        return self.variation.clone();
+    }
+
+    pub fn add_stage_result(&mut self, stage: usize, result: TrialResult) {
+        self.stage_results.push((stage, result));
+    }
+
+    pub fn get_stage_result(&self, stage: usize) -> Option<&TrialResult> {
+        return self
+            .stage_results
+            .iter()
+            .find(|(s, _)| *s == stage)
+            .map(|(_, result)| result);
+    }
+
+    pub fn get_latest_stage_result(&self) -> Option<&(usize, TrialResult)> {
+        return self.stage_results.last();
+    }
+}
+
+pub fn create_ranked_variation(
+    variation: Vec<String>,
+    stage: usize,
+    result: TrialResult,
+) -> RankedVariation {
+    return RankedVariation {
+        variation,
+        stage_results: vec![(stage, result)],
+    };
+}
+
+/// Variations that reached a later stage rank higher
+/// Ties are broken by success rate on the latest stage, then on each earlier stage in turn
+pub fn compare_ranked_variations(a: &RankedVariation, b: &RankedVariation) -> Ordering {
+    let mut a_iter = a.stage_results.iter().rev();
+    let mut b_iter = b.stage_results.iter().rev();
+    loop {
+        match (a_iter.next(), b_iter.next()) {
+            (Some((a_stage, a_result)), Some((b_stage, b_result))) => {
+                let stage_ordering = b_stage.cmp(a_stage);
+                if stage_ordering != Ordering::Equal {
+                    return stage_ordering;
+                }
+                let success_ordering = b_result
+                    .get_success_rate()
+                    .partial_cmp(&a_result.get_success_rate())
+                    .unwrap_or(Ordering::Equal);
+                if success_ordering != Ordering::Equal {
+                    return success_ordering;
+                }
+            }
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => return Ordering::Equal,
+        }
+    }
+}
+
+/// Sorts the variations from best to worst
+pub fn rank_variations(ranked_variations: &mut Vec<RankedVariation>) {
+    ranked_variations.sort_by(compare_ranked_variations);
+}
+
+/// Returns the indices of the variations to advance to the next runoff stage
+/// These are the top runoff_scoring_threshold% of the variations that took part in from_stage, excluding any that never succeeded on it
+/// Expects ranked_variations to already be ranked
+pub fn select_runoff_candidates(
+    ranked_variations: &Vec<RankedVariation>,
+    from_stage: usize,
+    runoff_scoring_threshold: f64,
+) -> Vec<usize> {
+    let participants: Vec<usize> = ranked_variations
+        .iter()
+        .enumerate()
+        .filter(|(_, rv)| rv.get_stage_result(from_stage).is_some())
+        .map(|(i, _)| i)
+        .collect();
+    let candidate_qty =
+        ((participants.len() as f64 * runoff_scoring_threshold / 100.0).ceil() as usize).max(1);
+    return participants
+        .into_iter()
+        .take(candidate_qty)
+        .filter(|i| {
+            ranked_variations[*i]
+                .get_stage_result(from_stage)
+                .unwrap()
+                .get_success_rate()
+                > 0.0
+        })
+        .collect();
+}
+
+/// Returns the indices of the variations whose success rate on stage exceeds ceiling (as a percent)
+pub fn select_saturated_variations(
+    ranked_variations: &Vec<RankedVariation>,
+    stage: usize,
+    ceiling: f64,
+) -> Vec<usize> {
+    return ranked_variations
+        .iter()
+        .enumerate()
+        .filter(|(_, rv)| match rv.get_stage_result(stage) {
+            Some(result) => result.get_success_rate() * 100.0 > ceiling,
+            None => false,
+        })
+        .map(|(i, _)| i)
+        .collect();
+}
+
+/// Defines the format for saving ranked variations to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct RankedVariationCSVRecord {
+    rank: usize,
+    variation: String,
+    final_stage: usize,
+    final_dungeon_identifier: String,
+    final_difficulty_settings: String,
+    final_success_rate: String,
+    stage_success_rates: String, // Semi-colon separated list of stage:success_rate for each stage reached
+}
+
+/// Save the (ranked) variations to a CSV, replacing any previous ranking at the path
+/// Each skill in the variation is abbreviated where possible
+pub fn save_ranked_variations_to_csv(
+    string_path: String,
+    ranked_variations: &Vec<RankedVariation>,
+    skill_abbreviation_map: &HashMap<String, String>,
+) -> Result<(), std::io::Error> {
+    if let Some(p) = std::path::Path::new(&string_path).parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    let mut wtr = csv::Writer::from_path(string_path)?;
+
+    for (i, rv) in ranked_variations.iter().enumerate() {
+        let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+        let record = RankedVariationCSVRecord {
+            rank: i + 1,
+            variation: abbreviate_skills(&rv.variation, skill_abbreviation_map).join(";"),
+            final_stage: *final_stage,
+            final_dungeon_identifier: final_result.get_dungeon_identifier(),
+            final_difficulty_settings: final_result
+                .get_difficulty_settings()
+                .iter()
+                .map(|diff| get_difficulty_name(*diff))
+                .collect::<Vec<&str>>()
+                .join(";"),
+            final_success_rate: f!("{:.4}", round_to_4(final_result.get_success_rate())),
+            stage_success_rates: rv
+                .stage_results
+                .iter()
+                .map(|(stage, result)| f!("{}:{:.4}", stage, round_to_4(result.get_success_rate())))
+                .collect::<Vec<String>>()
+                .join(";"),
+        };
+        wtr.serialize(record)?;
+    }
+
+    wtr.flush()?;
+    return Ok(());
+}
+
+/// Defines the format for saving the variations ranked by composite score to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct RankedResultCSVRecord {
+    rank: usize,
+    variation: String,
+    final_stage: usize,
+    final_dungeon_identifier: String,
+    final_difficulty_settings: String,
+    composite_score: String,
+    success_rate: String,
+    success_rate_ci_lower: String,
+    success_rate_ci_upper: String,
+    success_rate_p_value_vs_next: String, // The p-value of the difference in success rate from the next ranked variation, empty if that is on another final stage or there is none
+    team_avg_survival_rate: String,
+    average_rounds: String,
+    team_avg_dmg: String,
+    team_avg_hp_remaining: String,
+    equipment_substitutions: String, // Semi-colon separated list of the alternate equipment substituted for the variation
+}
+
+/// Returns the variations ranked by final stage and then composite score, along with each one's score
+/// Results are normalized against the other results on the same final stage, since their dungeon and difficulty match
+/// Each variation's success rate is scaled by success_rate_factor before scoring
+pub fn rank_variations_by_composite_score<'a>(
+    ranked_variations: &'a Vec<RankedVariation>,
+    report_settings: &ReportSettings,
+    success_rate_factor: &dyn Fn(&Vec<String>) -> f64,
+) -> Vec<(&'a RankedVariation, f64)> {
+    let mut normalizations: HashMap<usize, ScoreNormalization> = Default::default();
+    for (final_stage, _) in ranked_variations
+        .iter()
+        .map(|rv| rv.get_latest_stage_result().unwrap())
+    {
+        if normalizations.contains_key(final_stage) {
+            continue;
+        }
+        let stage_results: Vec<&TrialResult> = ranked_variations
+            .iter()
+            .map(|rv| rv.get_latest_stage_result().unwrap())
+            .filter(|(stage, _)| stage == final_stage)
+            .map(|(_, result)| result)
+            .collect();
+        normalizations.insert(*final_stage, create_score_normalization(&stage_results));
+    }
+
+    let mut res: Vec<(&RankedVariation, f64)> = ranked_variations
+        .iter()
+        .map(|rv| {
+            let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+            return (
+                rv,
+                report_settings.scoring_weights.calculate_composite_score(
+                    final_result,
+                    &normalizations[final_stage],
+                    success_rate_factor(&rv.variation),
+                ),
+            );
+        })
+        .collect();
+    res.sort_by(|(a, a_score), (b, b_score)| {
+        let a_stage = a.get_latest_stage_result().unwrap().0;
+        let b_stage = b.get_latest_stage_result().unwrap().0;
+        return b_stage
+            .cmp(&a_stage)
+            .then(b_score.partial_cmp(a_score).unwrap_or(Ordering::Equal))
+            .then(compare_ranked_variations(a, b));
+    });
+    return res;
+}
+
+/// Save the study's variations ranked by composite score to a CSV, along with a markdown summary of the top builds
+/// Both replace any previous report at their paths
+pub fn save_ranked_results_to_csv_and_summary(
+    csv_path: String,
+    summary_path: String,
+    study: &Study,
+    success_rate_factor: &dyn Fn(&Vec<String>) -> f64,
+) -> Result<(), std::io::Error> {
+    if let Some(p) = std::path::Path::new(&csv_path).parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    let mut wtr = csv::Writer::from_path(csv_path)?;
+    let scored_variations = rank_variations_by_composite_score(
+        &study.ranked_variations,
+        &study.report_settings,
+        success_rate_factor,
+    );
+
+    let mut records: Vec<RankedResultCSVRecord> = vec![];
+    for (i, (rv, composite_score)) in scored_variations.iter().enumerate() {
+        let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+        let (ci_lower, ci_upper) = final_result.get_success_rate_interval();
+        // Results on different final stages were trialed on different dungeons or difficulties, so are not compared
+        let p_value_vs_next = match scored_variations.get(i + 1) {
+            Some((next_rv, _)) => {
+                let (next_stage, next_result) = next_rv.get_latest_stage_result().unwrap();
+                if next_stage == final_stage {
+                    let (_, p_value) = final_result.compare_success_rate(next_result);
+                    f!("{:.4}", round_to_4(p_value))
+                } else {
+                    String::from("")
+                }
+            }
+            None => String::from(""),
+        };
+        let record = RankedResultCSVRecord {
+            rank: i + 1,
+            variation: abbreviate_skills(&rv.variation, study.get_skill_abbreviation_map())
+                .join(";"),
+            final_stage: *final_stage,
+            final_dungeon_identifier: final_result.get_dungeon_identifier(),
+            final_difficulty_settings: final_result
+                .get_difficulty_settings()
+                .iter()
+                .map(|diff| get_difficulty_name(*diff))
+                .collect::<Vec<&str>>()
+                .join(";"),
+            composite_score: f!("{:.4}", round_to_4(*composite_score)),
+            success_rate: f!("{:.4}", round_to_4(final_result.get_success_rate())),
+            success_rate_ci_lower: f!("{:.4}", round_to_4(ci_lower)),
+            success_rate_ci_upper: f!("{:.4}", round_to_4(ci_upper)),
+            success_rate_p_value_vs_next: p_value_vs_next,
+            team_avg_survival_rate: f!(
+                "{:.4}",
+                round_to_4(get_team_avg_survival_rate(final_result))
+            ),
+            average_rounds: f!("{:.2}", round_to_2(final_result.get_average_rounds())),
+            team_avg_dmg: f!("{:.2}", round_to_2(get_team_avg_dmg(final_result))),
+            team_avg_hp_remaining: f!("{:.2}", round_to_2(get_team_avg_hp_remaining(final_result))),
+            equipment_substitutions: final_result.get_equipment_substitutions().join(";"),
+        };
+        wtr.serialize(record.clone())?;
+        records.push(record);
+    }
+    wtr.flush()?;
+
+    let top_n = study.report_settings.summary_top_n.min(records.len());
+    let mut summary = f!("# {}\n\n", study.identifier);
+    if study.description.len() > 0 {
+        summary += &f!("{}\n\n", study.description);
+    }
+    summary += &f!(
+        "Top {} of {} variations, ranked by final stage and then composite score ({}).\n\n",
+        top_n,
+        records.len(),
+        study.report_settings.scoring_weights.describe().join(", ")
+    );
+    summary += "| Rank | Variation | Dungeon | Difficulty | Score | Success Rate | Survival Rate | Avg Rounds |\n";
+    summary += "| ---: | --- | --- | --- | ---: | ---: | ---: | ---: |\n";
+    for record in records.iter().take(top_n) {
+        summary += &f!(
+            "| {} | {} | {} | {} | {} | {} ({} - {}) | {} | {} |\n",
+            record.rank,
+            record.variation.replace(";", ", "),
+            record.final_dungeon_identifier,
+            record.final_difficulty_settings.replace(";", ", "),
+            record.composite_score,
+            record.success_rate,
+            record.success_rate_ci_lower,
+            record.success_rate_ci_upper,
+            record.team_avg_survival_rate,
+            record.average_rounds
+        );
+    }
+    std::fs::write(summary_path, summary)?;
+    return Ok(());
+}
+
+/// Progress saved periodically to a study's output folder so that an interrupted study can resume where it left off
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StudyCheckpoint {
+    pub docket_row_hash: u64, // The hash of the study's docket row, used along with the seed to detect a checkpoint from a differently configured study
+    pub seed: u64,
+    pub combination_index: i64, // The index of the next combination to be trialed
+    pub combination_total: i64, // The total number of combinations, which can also change with the hero or skill data
+    pub stage_best_lower_bounds: HashMap<usize, f64>,
+    pub csv_lengths: HashMap<String, u64>, // The length in bytes of each output csv when the checkpoint was saved
+}
+
+/// Save the checkpoint as yaml, writing to a temporary file first so an interruption never leaves a partial checkpoint
+pub fn save_study_checkpoint(
+    string_path: &String,
+    checkpoint: &StudyCheckpoint,
+) -> Result<(), std::io::Error> {
+    if let Some(p) = std::path::Path::new(string_path).parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    let temp_path = f!("{}.tmp", string_path);
+    let writer = std::fs::File::create(&temp_path)?;
+    serde_yaml::to_writer(writer, checkpoint)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    std::fs::rename(temp_path, string_path)?;
+    return Ok(());
+}
+
+/// Load the checkpoint at the path, returning None if there is no checkpoint or it could not be read
+pub fn load_study_checkpoint(string_path: &String) -> Option<StudyCheckpoint> {
+    let reader = std::fs::File::open(string_path).ok()?;
+    match serde_yaml::from_reader::<std::fs::File, StudyCheckpoint>(reader) {
+        Ok(checkpoint) => return Some(checkpoint),
+        Err(e) => {
+            warn!("Failed to read checkpoint {}: {}", string_path, e);
+            return None;
+        }
+    }
+}
+
+/// Returns the current length in bytes of each csv, with 0 for any csv that does not exist yet
+pub fn get_csv_lengths(csv_paths: &Vec<String>) -> HashMap<String, u64> {
+    return csv_paths
+        .iter()
+        .map(|path| {
+            let length = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            return (path.to_string(), length);
+        })
+        .collect();
+}
+
+/// Move each existing csv aside by adding the current unix time to its name, e.g. trial_results.1700000000.csv
+pub fn rotate_csvs(csv_paths: &Vec<String>) -> Result<(), std::io::Error> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    for path in csv_paths {
+        if !std::path::Path::new(path).exists() {
+            continue;
+        }
+        let rotated_path = match path.strip_suffix(".csv") {
+            Some(stem) => f!("{}.{}.csv", stem, timestamp),
+            None => f!("{}.{}", path, timestamp),
+        };
+        std::fs::rename(path, &rotated_path)?;
+        info!("Moved {} from an earlier run to {}", path, rotated_path);
+    }
+    return Ok(());
+}
+
+/// Truncate each csv back to its recorded length, discarding rows written after the checkpoint was saved
+/// A csv that did not exist when the checkpoint was saved is removed, so its header is written again
+pub fn truncate_csvs_to_lengths(csv_lengths: &HashMap<String, u64>) -> Result<(), std::io::Error> {
+    for (path, length) in csv_lengths {
+        if !std::path::Path::new(path).exists() {
+            continue;
+        }
+        if *length == 0 {
+            std::fs::remove_file(path)?;
+            continue;
+        }
+        let file = std::fs::OpenOptions::new().write(true).open(path)?;
+        if file.metadata()?.len() > *length {
+            file.set_len(*length)?;
+        }
+    }
+    return Ok(());
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 enum StudyStatus {
     Created,
@@ -90,16 +1123,112 @@ impl HeroBuilderInformation {
         return res;
     }
 
+    /// Returns whether the skillset has no skill incompatible with another skill in it
+    /// and no more skills of any category (e.g. gold masteries) than the category's cap
+    pub fn is_valid_skillset(&self, skillset: &Vec<String>) -> bool {
+        let tier_1_names: Vec<String> = skillset
+            .iter()
+            .map(|skill| {
+                self.hero_skill_any_tier_to_tier_1_name_map
+                    .get(skill)
+                    .unwrap_or(skill)
+                    .to_string()
+            })
+            .collect();
+        let mut category_counts: HashMap<SkillCategory, usize> = Default::default();
+
+        for skill in skillset {
+            let hs_opt = self.hero_skill_map.get(skill);
+            match hs_opt {
+                Some(hskill) => {
+                    let incompatible_with = hskill.get_incompatible_with();
+                    if incompatible_with.len() > 0 && tier_1_names.contains(&incompatible_with) {
+                        return false;
+                    }
+                    if let Some(category) = hskill.get_category() {
+                        let count = category_counts.entry(category).or_insert(0);
+                        *count += 1;
+                        if *count > category.get_cap() {
+                            return false;
+                        }
+                    }
+                }
+                None => (),
+            }
+        }
+
+        return true;
+    }
+
+    /// Returns the index of each combination of varying_skill_slot_count skills from valid_skills that forms a valid skillset
+    /// alongside the preset skills, in the order of the combination indices, so invalid skillsets are never trialed
+    /// Every one of the C(n, k) combinations is checked when the study is created, and the valid indices are kept in memory
+    /// (8 bytes each), e.g. 60 valid skills with 4 varying slots checks 487,635 skillsets and keeps up to ~3.9 MB
+    pub fn get_valid_skill_combination_indices(
+        &self,
+        valid_skills: &Vec<String>,
+        preset_skills: &Vec<String>,
+        varying_skill_slot_count: i64,
+    ) -> Vec<i64> {
+        let valid_skills_count = valid_skills.len() as i64;
+        let mut res: Vec<i64> = Default::default();
+
+        for combination_index in
+            0..crate::combinations::count_combinations(valid_skills_count, varying_skill_slot_count)
+        {
+            let mut skillset = preset_skills.clone();
+            for idx in crate::combinations::iter_combination(
+                combination_index,
+                valid_skills_count,
+                varying_skill_slot_count,
+            ) {
+                skillset.push(valid_skills[idx as usize].clone());
+            }
+            if self.is_valid_skillset(&skillset) {
+                res.push(combination_index);
+            }
+        }
+
+        return res;
+    }
+
     pub fn get_skills_incompatible_with_hero(&self, hero_class: String) -> Vec<String> {
         let mut res: Vec<String> = Default::default();
 
         for (identifier, hs) in &self.hero_skill_map {
             let allowed = hs.get_classes_allowed();
-            if allowed.contains(&hero_class) {
+            if !allowed.contains(&hero_class) {
                 res.push(identifier.to_string());
             }
         }
 
         return res;
     }
+
+    /// Returns each item-conditional skill (e.g. Dagger Master) along with its item types when none of the hero's equipment
+    /// or alternate equipment is of those types, so its bonus could never apply
+    pub fn get_skills_without_matching_equipment(
+        &self,
+        hero: &crate::hero_builder::Hero,
+    ) -> Vec<(String, Vec<String>)> {
+        let available_item_types = hero.get_available_item_types(&self.bp_map);
+        let mut res: Vec<(String, Vec<String>)> = Default::default();
+
+        for (identifier, hs) in &self.hero_skill_map {
+            let item_types: Vec<String> = hs
+                .get_item_types()
+                .into_iter()
+                .filter(|itype| itype.len() > 0)
+                .collect();
+            if item_types.len() > 0
+                && !item_types
+                    .iter()
+                    .any(|itype| available_item_types.contains(itype))
+            {
+                res.push((identifier.to_string(), item_types));
+            }
+        }
+
+        return res;
+    }
 }
@@ -0,0 +1,604 @@
+use std::time::Instant;
+
+use indicatif::{MultiProgress, ProgressStyle};
+
+use crate::{
+    decimals::{round_to_2, round_to_4},
+    dungeons::TrialDungeon,
+    hero_builder::Hero,
+    heroes::Team,
+    studies::*,
+    trials::{get_difficulty_name, AdaptiveSampling, Trial},
+};
+
+/// The qualities gear may be crafted at, from lowest to highest
+pub const GEAR_QUALITIES: [&str; 5] = ["Normal", "Superior", "Flawless", "Epic", "Legendary"];
+
+/// The tiers of spirits that may be socketed, from lowest to highest
+pub const SPIRIT_TIERS: [&str; 8] = ["T0", "T4", "T5", "T7", "T9", "TM", "T11", "T12"];
+
+/// The most gear sets a GearOptimizationStudy may trial, each is a full trial of simulation_qty simulations
+pub const MAX_GEAR_SETS: i64 = 10_000_000;
+
+/// Defines the constraints on the gear a GearOptimizationStudy may search
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GearConstraints {
+    pub max_tier: u8,               // The highest blueprint tier allowed, 0 for no limit
+    pub qualities: Vec<String>, // The qualities each item may be crafted at, empty to keep the quality of the subject hero's item in each slot
+    pub elements: Vec<String>,  // The 6 elements available to socket into the gear, e.g. "Water 4"
+    pub spirits: Vec<String>, // The 6 spirits available to socket into the gear, e.g. "Mammoth T9"
+    pub candidates_per_slot: usize, // The number of top candidates by prefilter score kept for each slot
+}
+
+/// An item and quality that may be equipped in a slot, along with its prefilter score
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct GearCandidate {
+    item: String,
+    quality: String,
+    score: f64, // The sum of the hero's hp, atk and def relative to those with its own gear, when equipped in place of the hero's own item
+}
+
+impl GearCandidate {
+    fn get_description(&self) -> String {
+        return f!("{} ({})", self.item, self.quality);
+    }
+}
+
+/// Split a gear description of the form "[item] ([quality])" into the item and quality
+fn parse_gear_description(description: &String) -> (String, String) {
+    let (item, quality) = description.rsplit_once(" (").unwrap();
+    return (item.to_string(), quality.trim_end_matches(")").to_string());
+}
+
+/// An extension of Study for generating and ranking Trials for each set of gear for a hero with fixed skills
+/// A variation is the description of the item equipped in each of the 6 slots
+pub struct GearOptimizationStudy {
+    study: Study,
+    base_team: Team,
+    subject_hero_identifier: String, // The identifier of the hero whose gear is varied
+    subject_hero_builder: Hero, // The hero builder representation of the subject hero with the study's skills, to be converted to a simhero for variation
+    elements: Vec<String>,      // The elements socketed into each set of gear
+    spirits: Vec<String>,       // The spirits socketed into each set of gear
+    scored_candidates: Vec<Vec<GearCandidate>>, // Every candidate for each slot that satisfies the constraints, in order of descending prefilter score
+    candidates_per_slot: usize, // The number of top candidates of each slot that are varied upon
+    combination_index: i64, // The current index of the combinations of each slot's candidates being trialed
+}
+
+pub fn create_gear_optimization_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    runoff_scoring_threshold: f64,
+    base_team: Team,
+    subject_hero_identifier: String,
+    subject_hero_builder: Hero,
+    skills: Vec<String>,
+    gear_constraints: GearConstraints,
+    dungeons: Vec<TrialDungeon>,
+    automatic_rank_difficulty_optimization: bool,
+    rank_difficulty_ceiling: f64,
+    hero_builder_information: HeroBuilderInformation,
+    seed: u64,
+    docket_row_hash: u64,
+    output_path: String,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    combat_log_cap: usize,
+    report_settings: ReportSettings,
+) -> GearOptimizationStudy {
+    let mut hero = subject_hero_builder.clone();
+    if skills.len() > 0 {
+        hero.set_hero_skills(skills);
+    }
+    let elements = if gear_constraints.elements.len() > 0 {
+        gear_constraints.elements.clone()
+    } else {
+        hero.get_elements_socketed().to_vec()
+    };
+    let spirits = if gear_constraints.spirits.len() > 0 {
+        gear_constraints.spirits.clone()
+    } else {
+        hero.get_spirits_socketed().to_vec()
+    };
+    let scored_candidates = score_gear_candidates(
+        &hero,
+        &gear_constraints,
+        &elements,
+        &spirits,
+        &hero_builder_information,
+    );
+    for (slot, candidates) in scored_candidates.iter().enumerate() {
+        info!(
+            "Gear candidates for {} slot {}: {}",
+            subject_hero_identifier,
+            slot + 1,
+            candidates
+                .iter()
+                .take(gear_constraints.candidates_per_slot)
+                .map(|candidate| f!("{} [{:.2}]", candidate.get_description(), candidate.score))
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+    }
+
+    return GearOptimizationStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            runoff_scoring_threshold,
+            hero_builder_information,
+            seed,
+            docket_row_hash,
+            output_path,
+            adaptive_sampling,
+            combat_log_cap,
+            dungeons,
+            automatic_rank_difficulty_optimization,
+            rank_difficulty_ceiling,
+            report_settings,
+        ),
+        base_team,
+        subject_hero_identifier,
+        subject_hero_builder: hero,
+        elements,
+        spirits,
+        scored_candidates,
+        candidates_per_slot: gear_constraints.candidates_per_slot,
+        combination_index: 0,
+    };
+}
+
+/// Assign each element and spirit to the gear, giving the best of each to items with a matching affinity first
+/// Returns the element and spirit socketed into each slot
+fn assign_sockets(
+    equipment: &[String; 6],
+    elements: &Vec<String>,
+    spirits: &Vec<String>,
+    hero_builder_information: &HeroBuilderInformation,
+) -> ([String; 6], [String; 6]) {
+    let mut sorted_elements = elements.clone();
+    sorted_elements.sort_by_key(|element| {
+        std::cmp::Reverse(
+            element
+                .split_whitespace()
+                .last()
+                .unwrap_or_default()
+                .parse::<u8>()
+                .unwrap_or_default(),
+        )
+    });
+    let mut sorted_spirits = spirits.clone();
+    sorted_spirits.sort_by_key(|spirit| {
+        std::cmp::Reverse(
+            SPIRIT_TIERS
+                .iter()
+                .position(|tier| Some(*tier) == spirit.split_whitespace().last()),
+        )
+    });
+
+    let bp_map = &hero_builder_information.bp_map;
+    return (
+        assign_sockets_by_affinity(equipment, sorted_elements, |item| {
+            bp_map
+                .get(item)
+                .map_or(String::new(), |bp| bp.get_elemental_affinity())
+        }),
+        assign_sockets_by_affinity(equipment, sorted_spirits, |item| {
+            bp_map
+                .get(item)
+                .map_or(String::new(), |bp| bp.get_spirit_affinity())
+        }),
+    );
+}
+
+/// Assign each socket (in order) to the first unassigned item whose affinity matches it, then the rest to the remaining items in slot order
+fn assign_sockets_by_affinity(
+    equipment: &[String; 6],
+    sockets: Vec<String>,
+    get_affinity: impl Fn(&String) -> String,
+) -> [String; 6] {
+    let mut res: [String; 6] = Default::default();
+    let mut assigned = [false; 6];
+    let mut unmatched_sockets: Vec<String> = vec![];
+    for socket in sockets {
+        let socket_name = socket.split_whitespace().next().unwrap_or_default();
+        let matching_slot = (0..6).find(|i| {
+            !assigned[*i]
+                && get_affinity(&equipment[*i]).split_whitespace().next() == Some(socket_name)
+        });
+        match matching_slot {
+            Some(i) => {
+                res[i] = socket;
+                assigned[i] = true;
+            }
+            None => unmatched_sockets.push(socket),
+        }
+    }
+    for socket in unmatched_sockets {
+        let i = (0..6).find(|i| !assigned[*i]).unwrap();
+        res[i] = socket;
+        assigned[i] = true;
+    }
+    return res;
+}
+
+/// Returns the hero equipped with the gear and the elements and spirits assigned to it
+fn equip_gear(
+    hero: &Hero,
+    equipment: [String; 6],
+    quality: [String; 6],
+    elements: &Vec<String>,
+    spirits: &Vec<String>,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Hero {
+    let (elements_socketed, spirits_socketed) =
+        assign_sockets(&equipment, elements, spirits, hero_builder_information);
+    let mut new_hero = hero.clone();
+    new_hero.set_equipment(
+        equipment,
+        quality,
+        elements_socketed,
+        spirits_socketed,
+        &hero_builder_information.bp_map,
+        &hero_builder_information.hero_classes,
+    );
+    return new_hero;
+}
+
+/// Returns the hp, atk and def of the hero once its stats are calculated from its gear and skills
+fn calculate_gear_stats(
+    hero: &Hero,
+    hero_builder_information: &HeroBuilderInformation,
+) -> (f64, f64, f64) {
+    let mut new_hero = hero.clone();
+    new_hero.calculate_innate_tier(
+        &hero_builder_information.class_innate_skill_names_map,
+        &hero_builder_information.innate_skill_map,
+    );
+    new_hero.calculate_stat_improvements_from_gear_and_skills(
+        &hero_builder_information.bp_map,
+        &hero_builder_information.hero_skill_tier_1_name_map,
+        &hero_builder_information.hero_skill_map,
+        &hero_builder_information.class_innate_skill_names_map,
+        &hero_builder_information.innate_skill_map,
+    );
+    return (new_hero.get_hp(), new_hero.get_atk(), new_hero.get_def());
+}
+
+/// Returns the stat relative to the hero's stat with its own gear
+/// A stat the hero has none of with its own gear scores 1 for every candidate, so candidates are compared on the other stats
+fn get_relative_stat(stat: f64, base_stat: f64) -> f64 {
+    if base_stat <= 0.0 {
+        return 1.0;
+    }
+    return stat / base_stat;
+}
+
+/// Prefilter the blueprints for each slot, returning every item and quality allowed in the slot by the hero's class and the constraints
+/// Each candidate is scored by the hero's stats when equipped in place of the hero's own item, relative to the hero's stats with its own gear
+/// Candidates are sorted by descending score, and a slot without any candidates keeps the hero's own item
+fn score_gear_candidates(
+    hero: &Hero,
+    gear_constraints: &GearConstraints,
+    elements: &Vec<String>,
+    spirits: &Vec<String>,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Vec<Vec<GearCandidate>> {
+    let class = &hero_builder_information.hero_classes[&hero.get_class()];
+    let equipment_allowed = class.get_equipment_allowed();
+    let base_equipment = hero.get_equipment_equipped();
+    let base_quality = hero.get_equipment_quality();
+    let (base_hp, base_atk, base_def) = calculate_gear_stats(
+        &equip_gear(
+            hero,
+            base_equipment.clone(),
+            base_quality.clone(),
+            elements,
+            spirits,
+            hero_builder_information,
+        ),
+        hero_builder_information,
+    );
+
+    let mut res: Vec<Vec<GearCandidate>> = vec![];
+    for slot in 0..6 {
+        let qualities: Vec<String> = if gear_constraints.qualities.len() > 0 {
+            gear_constraints.qualities.clone()
+        } else {
+            vec![base_quality[slot].to_string()]
+        };
+
+        let mut candidates: Vec<GearCandidate> = vec![];
+        for (item, bp) in hero_builder_information.bp_map.iter() {
+            if !equipment_allowed[slot].contains(&bp.get_type())
+                || (gear_constraints.max_tier > 0 && bp.get_tier() > gear_constraints.max_tier)
+            {
+                continue;
+            }
+            for quality in qualities.iter() {
+                let mut equipment = base_equipment.clone();
+                equipment[slot] = item.to_string();
+                let mut equipment_quality = base_quality.clone();
+                equipment_quality[slot] = quality.to_string();
+                let (hp, atk, def) = calculate_gear_stats(
+                    &equip_gear(
+                        hero,
+                        equipment,
+                        equipment_quality,
+                        elements,
+                        spirits,
+                        hero_builder_information,
+                    ),
+                    hero_builder_information,
+                );
+                candidates.push(GearCandidate {
+                    item: item.to_string(),
+                    quality: quality.to_string(),
+                    score: get_relative_stat(hp, base_hp)
+                        + get_relative_stat(atk, base_atk)
+                        + get_relative_stat(def, base_def),
+                });
+            }
+        }
+
+        if candidates.len() == 0 {
+            warn!(
+                "No gear satisfies the constraints for slot {}, keeping {}",
+                slot + 1,
+                base_equipment[slot]
+            );
+            candidates.push(GearCandidate {
+                item: base_equipment[slot].to_string(),
+                quality: base_quality[slot].to_string(),
+                score: 3.0,
+            });
+        }
+        candidates.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(a.item.cmp(&b.item))
+                .then(a.quality.cmp(&b.quality))
+        });
+        res.push(candidates);
+    }
+    return res;
+}
+
+impl Runnable for GearOptimizationStudy {
+    /// Handle running trials for the study
+    fn run(
+        &mut self,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: Sender<(String, u64, u64, Instant)>,
+    ) {
+        self.run_variation_study(m, m_sty, tx);
+    }
+}
+
+impl VariationStudy for GearOptimizationStudy {
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn get_combination_index(&self) -> i64 {
+        return self.combination_index;
+    }
+
+    fn set_combination_index(&mut self, combination_index: i64) {
+        self.combination_index = combination_index;
+    }
+
+    fn count_variations_total(&self) -> i64 {
+        return (0..6)
+            .map(|slot| self.count_slot_candidates(slot) as i64)
+            .product();
+    }
+
+    /// The combination index is split into the candidate index of each slot, with the last slot varying fastest
+    fn get_variation_at_combination_index(&self, combination_index: i64) -> Vec<String> {
+        let mut res: Vec<String> = vec![String::new(); 6];
+        let mut remaining_index = combination_index;
+        for slot in (0..6).rev() {
+            let candidate_qty = self.count_slot_candidates(slot) as i64;
+            res[slot] = self.scored_candidates[slot][(remaining_index % candidate_qty) as usize]
+                .get_description();
+            remaining_index /= candidate_qty;
+        }
+        return res;
+    }
+
+    /// Create the team with the subject hero equipped with the gear, whose equipment is never substituted
+    fn create_team_for_variation(&self, variation: &Vec<String>) -> (Team, Vec<String>) {
+        let new_hero = self.equip_gear_for_variation(variation);
+        return (
+            self.study.create_team_with_subject_hero(
+                &self.base_team,
+                &self.subject_hero_identifier,
+                new_hero,
+            ),
+            vec![],
+        );
+    }
+
+    fn get_progress_message(&self) -> String {
+        return format!(
+            "{} ({})",
+            self.study.get_identifier(),
+            self.subject_hero_identifier
+        );
+    }
+
+    fn get_trial_csv_paths(&self) -> Vec<String> {
+        return vec![self.get_trial_results_csv_path()];
+    }
+
+    fn save_trial_results(&self, trial: &Trial) -> Result<(), std::io::Error> {
+        return trial.save_trial_result_to_csv(self.get_trial_results_csv_path());
+    }
+
+    /// Save the ranked gear sets along with the prefilter score of every candidate
+    fn save_study_results(&self) -> Result<(), std::io::Error> {
+        self.save_gear_candidates_to_csv(f!(
+            "{}/csvs/gear_candidates.csv",
+            self.study.get_output_path()
+        ))?;
+        return self.save_gear_optimization_ranking_to_csv(f!(
+            "{}/csvs/gear_optimization_ranking.csv",
+            self.study.get_output_path()
+        ));
+    }
+}
+
+impl GearOptimizationStudy {
+    fn get_trial_results_csv_path(&self) -> String {
+        return f!("{}/csvs/trial_results.csv", self.study.get_output_path());
+    }
+
+    /// Returns the number of candidates varied upon for the slot
+    fn count_slot_candidates(&self, slot: usize) -> usize {
+        return self.scored_candidates[slot]
+            .len()
+            .min(self.candidates_per_slot);
+    }
+
+    fn equip_gear_for_variation(&self, variation: &Vec<String>) -> Hero {
+        let mut equipment: [String; 6] = Default::default();
+        let mut equipment_quality: [String; 6] = Default::default();
+        for (slot, description) in variation.iter().enumerate() {
+            let (item, quality) = parse_gear_description(description);
+            equipment[slot] = item;
+            equipment_quality[slot] = quality;
+        }
+        return equip_gear(
+            &self.subject_hero_builder,
+            equipment,
+            equipment_quality,
+            &self.elements,
+            &self.spirits,
+            &self.study.hero_builder_information,
+        );
+    }
+
+    /// Save the (ranked) gear sets to a CSV, replacing any previous ranking at the path
+    fn save_gear_optimization_ranking_to_csv(
+        &self,
+        string_path: String,
+    ) -> Result<(), std::io::Error> {
+        if let Some(p) = std::path::Path::new(&string_path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let mut wtr = csv::Writer::from_path(string_path)?;
+
+        let hero_index = self
+            .base_team
+            .get_index_of_hero_with_identifier(&self.subject_hero_identifier)
+            .unwrap();
+        for (i, rv) in self.study.get_ranked_variations().iter().enumerate() {
+            let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+            let (ci_lower, ci_upper) = final_result.get_success_rate_interval();
+            let equipped_hero = self.equip_gear_for_variation(&rv.get_variation());
+            let record = GearOptimizationRankingCSVRecord {
+                rank: i + 1,
+                gear: rv.get_variation().join(";"),
+                elements: equipped_hero.get_elements_socketed().join(";"),
+                spirits: equipped_hero.get_spirits_socketed().join(";"),
+                final_stage: *final_stage,
+                final_dungeon_identifier: final_result.get_dungeon_identifier(),
+                final_difficulty_settings: final_result
+                    .get_difficulty_settings()
+                    .iter()
+                    .map(|diff| get_difficulty_name(*diff))
+                    .collect::<Vec<&str>>()
+                    .join(";"),
+                success_rate: f!("{:.4}", round_to_4(final_result.get_success_rate())),
+                success_rate_ci_lower: f!("{:.4}", round_to_4(ci_lower)),
+                success_rate_ci_upper: f!("{:.4}", round_to_4(ci_upper)),
+                average_rounds: f!("{:.2}", round_to_2(final_result.get_average_rounds())),
+                hero_survival_rate: f!(
+                    "{:.4}",
+                    round_to_4(final_result.get_hero_survival_rate(hero_index))
+                ),
+                hero_avg_hp_remaining: f!(
+                    "{:.2}",
+                    round_to_2(final_result.get_hero_avg_hp_remaining(hero_index))
+                ),
+                hero_avg_dmg: f!(
+                    "{:.2}",
+                    round_to_2(final_result.get_hero_avg_dmg(hero_index))
+                ),
+            };
+            wtr.serialize(record)?;
+        }
+
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    /// Save every candidate of each slot with its prefilter score to a CSV, replacing any previous candidates at the path
+    fn save_gear_candidates_to_csv(&self, string_path: String) -> Result<(), std::io::Error> {
+        if let Some(p) = std::path::Path::new(&string_path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let mut wtr = csv::Writer::from_path(string_path)?;
+
+        for (slot, candidates) in self.scored_candidates.iter().enumerate() {
+            for (i, candidate) in candidates.iter().enumerate() {
+                let bp = self
+                    .study
+                    .hero_builder_information
+                    .bp_map
+                    .get(&candidate.item);
+                let record = GearCandidateCSVRecord {
+                    slot: slot + 1,
+                    item: candidate.item.to_string(),
+                    quality: candidate.quality.to_string(),
+                    item_type: bp.map_or(String::new(), |bp| bp.get_type()),
+                    tier: bp.map_or(0, |bp| bp.get_tier()),
+                    prefilter_score: f!("{:.4}", round_to_4(candidate.score)),
+                    trialed: i < self.candidates_per_slot,
+                };
+                wtr.serialize(record)?;
+            }
+        }
+
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// Defines the format for saving the ranking of a gear optimization study to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct GearOptimizationRankingCSVRecord {
+    rank: usize,
+    gear: String, // Semi-colon separated list of the item (quality) equipped in each slot
+    elements: String, // Semi-colon separated list of the element socketed in each slot
+    spirits: String, // Semi-colon separated list of the spirit socketed in each slot
+    final_stage: usize,
+    final_dungeon_identifier: String,
+    final_difficulty_settings: String,
+    success_rate: String,
+    success_rate_ci_lower: String,
+    success_rate_ci_upper: String,
+    average_rounds: String,
+    hero_survival_rate: String,
+    hero_avg_hp_remaining: String,
+    hero_avg_dmg: String,
+}
+
+/// Defines the format for saving the prefiltered gear candidates to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct GearCandidateCSVRecord {
+    slot: usize,
+    item: String,
+    quality: String,
+    item_type: String,
+    tier: u8,
+    prefilter_score: String,
+    trialed: bool, // Whether the candidate scored high enough to be varied upon
+}
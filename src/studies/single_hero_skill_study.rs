@@ -1,16 +1,27 @@
-use crate::{dungeons::Dungeon, heroes::Team, studies::*};
+use std::time::Instant;
 
-/// An extension of Study for generating and ranking Trials for each combination of skills for a single hero on a team
+use indicatif::{MultiProgress, ProgressStyle};
+
+use crate::{
+    decimals::{round_to_2, round_to_4},
+    dungeons::TrialDungeon,
+    heroes::Team,
+    studies::*,
+    trials::{get_difficulty_name, AdaptiveSampling, Trial},
+};
+
+/// An extension of Study for generating and ranking Trials for each combination of skills for a single hero fighting alone
 pub struct SingleHeroSkillStudy {
     study: Study,
     base_team: Team,
     subject_hero_identifier: String, // The identifier of the hero to vary upon, and whose performance will be analyzed for the purposes of this study
-    valid_skills: Vec<String>,       // The vector of all skills to be varied upon
-    valid_skills_count: i64,         // The number of valid skills to vary upon
+    subject_hero_builder: crate::hero_builder::Hero, // The hero builder representation of the subject hero, to be converted to a simhero for variation
+    valid_skills: Vec<String>,                       // The vector of all skills to be varied upon
+    valid_skills_count: i64,                         // The number of valid skills to vary upon
     preset_skills: Vec<String>, // A vector containing 0-3 innate skills that are preset and unchanging
-    varying_skill_slot_count: i64,   // The number of skill slots to vary
-    skill_combination_index: i64, // The current index of the combinations of the valid_skills list being trialed
-    dungeons: Vec<Dungeon>,
+    varying_skill_slot_count: i64, // The number of skill slots to vary
+    valid_combination_indices: Vec<i64>, // The indices of the combinations of the valid_skills list that form valid skillsets
+    skill_combination_index: i64, // The current index of the valid combinations being trialed
 }
 
 pub fn create_single_hero_skill_study(
@@ -22,26 +33,53 @@ pub fn create_single_hero_skill_study(
     valid_skills: Vec<String>,
     preset_skills: Vec<String>,
     subject_hero_identifier: String,
-    dungeons: Vec<Dungeon>,
+    subject_hero_builder: crate::hero_builder::Hero,
+    dungeons: Vec<TrialDungeon>,
+    automatic_rank_difficulty_optimization: bool,
+    rank_difficulty_ceiling: f64,
+    hero_builder_information: HeroBuilderInformation,
+    seed: u64,
+    docket_row_hash: u64,
+    output_path: String,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    combat_log_cap: usize,
+    report_settings: ReportSettings,
 ) -> SingleHeroSkillStudy {
     let mut vs = valid_skills.clone();
     vs.retain(|x| !preset_skills.contains(x));
     vs.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+    let varying_skill_slot_count = 4 - preset_skills.len() as i64;
+    let valid_combination_indices = hero_builder_information.get_valid_skill_combination_indices(
+        &vs,
+        &preset_skills,
+        varying_skill_slot_count,
+    );
     return SingleHeroSkillStudy {
         study: create_study(
             identifier,
             description,
             simulation_qty,
             runoff_scoring_threshold,
+            hero_builder_information,
+            seed,
+            docket_row_hash,
+            output_path,
+            adaptive_sampling,
+            combat_log_cap,
+            dungeons,
+            automatic_rank_difficulty_optimization,
+            rank_difficulty_ceiling,
+            report_settings,
         ),
         base_team,
         subject_hero_identifier,
+        subject_hero_builder,
         valid_skills_count: vs.len() as i64,
         valid_skills: vs,
-        varying_skill_slot_count: 4 - preset_skills.len() as i64,
+        varying_skill_slot_count,
         preset_skills,
+        valid_combination_indices,
         skill_combination_index: 0,
-        dungeons,
     };
 }
 
@@ -53,44 +91,106 @@ pub fn create_single_hero_skill_study(
 Limitations
 1. Must be able to resume from mid-generation by saving combination index, the current step, and the current list
 2. Combination generator is naive to the skills themselves, only works on indices of the current list
-2. a. Because of this, must skip incompatible_with skills while executing trials rather than before generation
+2. a. Because of this, incompatible_with skills and excess bronze/gold masteries are skipped by prefiltering the combination indices
 2. b. OR Decide a subset of these beforehand, prefiltering the list and yielding far fewer combinations! - In what universe is the bronze ever better than the gold?
 2. b. i. Note however that this does somewhat defeat the purpose of an objective ranking system for all skills...
 */
 
-impl SingleHeroSkillStudy {
-    pub fn count_skill_variations_completed(&self) -> i64 {
+impl Runnable for SingleHeroSkillStudy {
+    /// Handle running trials for the study
+    fn run(
+        &mut self,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: Sender<(String, u64, u64, Instant)>,
+    ) {
+        self.run_variation_study(m, m_sty, tx);
+    }
+}
+
+impl VariationStudy for SingleHeroSkillStudy {
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn get_combination_index(&self) -> i64 {
         return self.skill_combination_index;
     }
-    pub fn count_skill_variations_total(&self) -> i64 {
-        return crate::combinations::count_combinations(
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
+
+    fn set_combination_index(&mut self, combination_index: i64) {
+        self.skill_combination_index = combination_index;
+    }
+
+    fn count_variations_total(&self) -> i64 {
+        return self.count_skill_variations_total();
+    }
+
+    fn get_variation_at_combination_index(&self, combination_index: i64) -> Vec<String> {
+        return self.get_full_translated_skillset_at_combination_index(combination_index);
+    }
+
+    /// Create the solo team with the subject hero built using the given skillset
+    fn create_team_for_variation(&self, skill_variation: &Vec<String>) -> (Team, Vec<String>) {
+        return self.study.create_team_with_subject_hero_skillset(
+            &self.base_team,
+            &self.subject_hero_identifier,
+            &self.subject_hero_builder,
+            skill_variation,
         );
     }
-    pub fn count_skill_variations_remaining(&self) -> i64 {
-        return crate::combinations::count_combinations(
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
-        ) - self.skill_combination_index;
+
+    fn get_progress_message(&self) -> String {
+        return format!(
+            "{} ({})",
+            self.study.get_identifier(),
+            self.subject_hero_identifier
+        );
     }
-    pub fn get_skillset_at_specific_combination_index(&self, combination_index: i64) -> Vec<i64> {
-        return crate::combinations::iter_combination(
-            combination_index,
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
+
+    fn get_trial_csv_paths(&self) -> Vec<String> {
+        return vec![self.get_trial_results_csv_path()];
+    }
+
+    fn save_trial_results(&self, trial: &Trial) -> Result<(), std::io::Error> {
+        return trial.save_trial_result_to_csv(self.get_trial_results_csv_path());
+    }
+
+    /// Save the ranked skillsets along with the subject hero's performance on the final stage each reached
+    fn save_study_results(&self) -> Result<(), std::io::Error> {
+        return save_single_hero_skill_ranking_to_csv(
+            f!(
+                "{}/csvs/single_hero_skill_ranking.csv",
+                self.study.get_output_path()
+            ),
+            self.study.get_ranked_variations(),
+            self.study.get_skill_abbreviation_map(),
         );
     }
-    pub fn get_skillset_at_current_combination_index(&self) -> Vec<i64> {
+}
+
+impl SingleHeroSkillStudy {
+    fn get_trial_results_csv_path(&self) -> String {
+        return f!("{}/csvs/trial_results.csv", self.study.get_output_path());
+    }
+
+    pub fn _count_skill_variations_completed(&self) -> i64 {
+        return self.skill_combination_index;
+    }
+    /// Returns the number of valid skillsets, excluding those with incompatible skills or too many of a skill category
+    pub fn count_skill_variations_total(&self) -> i64 {
+        return self.valid_combination_indices.len() as i64;
+    }
+    pub fn get_skillset_at_specific_combination_index(&self, combination_index: i64) -> Vec<i64> {
         return crate::combinations::iter_combination(
-            self.skill_combination_index,
+            self.valid_combination_indices[combination_index as usize],
             self.valid_skills_count,
             self.varying_skill_slot_count,
         );
     }
-    pub fn increment_combination_index(&mut self) {
-        self.skill_combination_index += 1;
-    }
     pub fn translate_skillset_from_indices(&self, indices_array: Vec<i64>) -> Vec<String> {
         let mut res = vec![];
         for idx in indices_array {
@@ -98,11 +198,78 @@ impl SingleHeroSkillStudy {
         }
         return res;
     }
-    pub fn get_full_translated_skillset_at_current_combination_index(&self) -> Vec<String> {
+    pub fn get_full_translated_skillset_at_combination_index(
+        &self,
+        combination_index: i64,
+    ) -> Vec<String> {
         let mut res = self.preset_skills.clone();
-        let mut translated_skillset =
-            self.translate_skillset_from_indices(self.get_skillset_at_current_combination_index());
+        let mut translated_skillset = self.translate_skillset_from_indices(
+            self.get_skillset_at_specific_combination_index(combination_index),
+        );
         res.append(&mut translated_skillset);
         return res;
     }
 }
+
+/// Defines the format for saving the ranking of a single hero skill study to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SingleHeroSkillRankingCSVRecord {
+    rank: usize,
+    skills: String,
+    final_stage: usize,
+    final_dungeon_identifier: String,
+    final_difficulty_settings: String,
+    success_rate: String,
+    success_rate_ci_lower: String,
+    success_rate_ci_upper: String,
+    average_rounds: String,
+    hero_survival_rate: String,
+    hero_avg_hp_remaining: String,
+    hero_avg_dmg: String,
+    equipment_substitutions: String, // Semi-colon separated list of the alternate equipment substituted for the skillset
+}
+
+/// Save the (ranked) skillsets to a CSV, replacing any previous ranking at the path
+/// The subject hero is the only hero on the team, so its stats are those of the first hero
+fn save_single_hero_skill_ranking_to_csv(
+    string_path: String,
+    ranked_variations: &Vec<RankedVariation>,
+    skill_abbreviation_map: &HashMap<String, String>,
+) -> Result<(), std::io::Error> {
+    if let Some(p) = std::path::Path::new(&string_path).parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    let mut wtr = csv::Writer::from_path(string_path)?;
+
+    for (i, rv) in ranked_variations.iter().enumerate() {
+        let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+        let (ci_lower, ci_upper) = final_result.get_success_rate_interval();
+        let record = SingleHeroSkillRankingCSVRecord {
+            rank: i + 1,
+            skills: abbreviate_skills(&rv.get_variation(), skill_abbreviation_map).join(";"),
+            final_stage: *final_stage,
+            final_dungeon_identifier: final_result.get_dungeon_identifier(),
+            final_difficulty_settings: final_result
+                .get_difficulty_settings()
+                .iter()
+                .map(|diff| get_difficulty_name(*diff))
+                .collect::<Vec<&str>>()
+                .join(";"),
+            success_rate: f!("{:.4}", round_to_4(final_result.get_success_rate())),
+            success_rate_ci_lower: f!("{:.4}", round_to_4(ci_lower)),
+            success_rate_ci_upper: f!("{:.4}", round_to_4(ci_upper)),
+            average_rounds: f!("{:.2}", round_to_2(final_result.get_average_rounds())),
+            hero_survival_rate: f!("{:.4}", round_to_4(final_result.get_hero_survival_rate(0))),
+            hero_avg_hp_remaining: f!(
+                "{:.2}",
+                round_to_2(final_result.get_hero_avg_hp_remaining(0))
+            ),
+            hero_avg_dmg: f!("{:.2}", round_to_2(final_result.get_hero_avg_dmg(0))),
+            equipment_substitutions: final_result.get_equipment_substitutions().join(";"),
+        };
+        wtr.serialize(record)?;
+    }
+
+    wtr.flush()?;
+    return Ok(());
+}
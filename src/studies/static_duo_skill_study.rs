@@ -1,11 +1,13 @@
 use std::time::Instant;
 
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use log::{error, info};
+use indicatif::{MultiProgress, ProgressStyle};
 
 use crate::{
-    dungeons::TrialDungeon, heroes::Team, inputs::convert_loaded_heroes_to_sim_heroes, studies::*,
-    trials::create_trial,
+    decimals::{round_to_2, round_to_4},
+    dungeons::TrialDungeon,
+    heroes::Team,
+    studies::*,
+    trials::{get_difficulty_name, AdaptiveSampling, Trial},
 };
 
 /// An extension of Study for generating and ranking Trials for each combination of skills for a single hero with a static Duo partner
@@ -18,10 +20,11 @@ pub struct StaticDuoSkillStudy {
     valid_skills_count: i64,                         // The number of valid skills to vary upon
     preset_skills: Vec<String>, // A vector containing 0-3 innate skills that are preset and unchanging
     varying_skill_slot_count: i64, // The number of skill slots to vary
-    skill_combination_index: i64, // The current index of the combinations of the valid_skills list being trialed
-    dungeons: Vec<TrialDungeon>, // The dungeons to be tested in the study. Only the first will be used unless automatic_rank_difficulty_optimization is enabled
-    _automatic_rank_difficulty_optimization: bool, // Whether to optimize ranking by testing skills above a certain rank on additional dungeons
+    valid_combination_indices: Vec<i64>, // The indices of the combinations of the valid_skills list that form valid skillsets
+    skill_combination_index: i64, // The current index of the valid combinations being trialed
     skill_abbreviation_map: HashMap<String, String>, // The map used to translate Skill Tier 1 names to Peetee's DuoSkillz Abbreviations
+    ordered_skill_slots: bool, // Whether each skillset is placed into ordered skill slots, with the varied skills filling the open slots rarest first
+    slot_weights: [f64; 4], // The relative ease of rolling a skill into each skill slot, used to weight the ranking when slots are ordered
 }
 
 pub fn create_static_duo_skill_study(
@@ -36,12 +39,27 @@ pub fn create_static_duo_skill_study(
     subject_hero_builder: crate::hero_builder::Hero,
     dungeons: Vec<TrialDungeon>,
     automatic_rank_difficulty_optimization: bool,
+    rank_difficulty_ceiling: f64,
     hero_builder_information: HeroBuilderInformation,
     skill_abbreviation_map: HashMap<String, String>,
+    ordered_skill_slots: bool,
+    slot_weights: [f64; 4],
+    seed: u64,
+    docket_row_hash: u64,
+    output_path: String,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    combat_log_cap: usize,
+    report_settings: ReportSettings,
 ) -> StaticDuoSkillStudy {
     let mut vs = valid_skills.clone();
     vs.retain(|x| !preset_skills.contains(x));
     vs.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+    let varying_skill_slot_count = 4 - preset_skills.len() as i64;
+    let valid_combination_indices = hero_builder_information.get_valid_skill_combination_indices(
+        &vs,
+        &preset_skills,
+        varying_skill_slot_count,
+    );
     return StaticDuoSkillStudy {
         study: create_study(
             identifier,
@@ -49,18 +67,28 @@ pub fn create_static_duo_skill_study(
             simulation_qty,
             runoff_scoring_threshold,
             hero_builder_information,
+            seed,
+            docket_row_hash,
+            output_path,
+            adaptive_sampling,
+            combat_log_cap,
+            dungeons,
+            automatic_rank_difficulty_optimization,
+            rank_difficulty_ceiling,
+            report_settings,
         ),
         base_team,
         subject_hero_identifier,
         subject_hero_builder,
         valid_skills_count: vs.len() as i64,
         valid_skills: vs,
-        varying_skill_slot_count: 4 - preset_skills.len() as i64,
+        varying_skill_slot_count,
         preset_skills,
+        valid_combination_indices,
         skill_combination_index: 0,
-        dungeons,
-        _automatic_rank_difficulty_optimization: automatic_rank_difficulty_optimization,
         skill_abbreviation_map,
+        ordered_skill_slots,
+        slot_weights,
     };
 }
 
@@ -70,203 +98,111 @@ impl Runnable for StaticDuoSkillStudy {
         &mut self,
         m: &MultiProgress,
         m_sty: &ProgressStyle,
-        tx: Sender<(String, u32, u32, Instant)>,
+        tx: Sender<(String, u64, u64, Instant)>,
     ) {
-        let study_start_instant = Instant::now();
-        tx.send((
-            self.study.identifier.to_string(),
-            0u32,
-            self._count_skill_variations_total() as u32,
-            study_start_instant,
-        ))
-        .unwrap();
-        info!("Start Study: {}", self.study.identifier);
+        self.run_variation_study(m, m_sty, tx);
+    }
+}
 
-        self.study.status = StudyStatus::Running;
+impl VariationStudy for StaticDuoSkillStudy {
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
 
-        let pb = m.add(ProgressBar::new(
-            self._count_skill_variations_total().try_into().unwrap(),
-        ));
-        pb.set_style(m_sty.clone());
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
 
-        let abbr_preset_skills_string: String = self
-            .preset_skills
-            .iter()
-            .map(|skill| {
-                let abbr = self.skill_abbreviation_map.get(skill);
-                match abbr {
-                    Some(abbreviation) => return abbreviation.to_string() + ", ",
-                    None => return skill.to_string() + ", ",
-                }
-            })
-            .collect();
+    fn get_combination_index(&self) -> i64 {
+        return self.skill_combination_index;
+    }
 
-        pb.set_message(format!(
-            "{} ({})",
-            self.study.identifier.to_string(),
-            &abbr_preset_skills_string[..abbr_preset_skills_string.len() - 2]
-        ));
+    fn set_combination_index(&mut self, combination_index: i64) {
+        self.skill_combination_index = combination_index;
+    }
 
-        while self.count_skill_variations_remaining() > 0 {
-            tx.send((
-                self.study.identifier.to_string(),
-                self.skill_combination_index as u32,
-                self._count_skill_variations_total() as u32,
-                study_start_instant,
-            ))
-            .unwrap();
-            pb.set_position(self.skill_combination_index.try_into().unwrap());
+    fn count_variations_total(&self) -> i64 {
+        return self._count_skill_variations_total();
+    }
 
-            // Create the combination of skills to test
-            let skill_variation = self.get_full_translated_skillset_at_current_combination_index();
+    fn get_variation_at_combination_index(&self, combination_index: i64) -> Vec<String> {
+        return self.get_full_translated_skillset_at_combination_index(combination_index);
+    }
 
-            // Vary the target hero in the team
-            let mut new_team = self.base_team.clone();
-            let target_hero_index = new_team
-                .get_index_of_hero_with_identifier(&self.subject_hero_identifier)
-                .unwrap();
-            let mut new_hero = self.subject_hero_builder.clone();
-            new_hero.set_hero_skills(skill_variation.clone());
-            let heroes_hashmap: HashMap<String, crate::hero_builder::Hero> =
-                HashMap::from([(self.subject_hero_identifier.to_string(), new_hero)]);
-            let new_sim_heroes = convert_loaded_heroes_to_sim_heroes(
-                heroes_hashmap,
-                self.study.hero_builder_information.bp_map.clone(),
-                self.study
-                    .hero_builder_information
-                    .hero_skill_tier_1_name_map
-                    .clone(),
-                self.study.hero_builder_information.hero_skill_map.clone(),
-                self.study
-                    .hero_builder_information
-                    .class_innate_skill_names_map
-                    .clone(),
-                self.study.hero_builder_information.innate_skill_map.clone(),
-            );
-            new_team.set_hero_at_index(
-                target_hero_index,
-                new_sim_heroes[&self.subject_hero_identifier].clone(),
-            );
+    /// Create the team with the subject hero built using the given skillset
+    fn create_team_for_variation(&self, skill_variation: &Vec<String>) -> (Team, Vec<String>) {
+        return self.study.create_team_with_subject_hero_skillset(
+            &self.base_team,
+            &self.subject_hero_identifier,
+            &self.subject_hero_builder,
+            skill_variation,
+        );
+    }
 
-            // TODO: Per-trial logging
-            // Configure trial log file
-            // let mut i = 0;
-            // while std::path::Path::new(&f!(
-            //     "target/simulations/{}/logs/trial_{}.log",
-            //     self.study.identifier,
-            //     i
-            // ))
-            // .exists()
-            // {
-            //     // Create new log file each run
-            //     i += 1;
-            // }
-            // fast_log::init(fast_log::Config::new().file(&f!(
-            //     "target/simulations/{}/logs/trial_{}.log",
-            //     self.study.identifier,
-            //     i
-            // )))
-            // .unwrap();
-            // info!("Start of Log File");
+    fn get_progress_message(&self) -> String {
+        return format!(
+            "{} ({})",
+            self.study.get_identifier(),
+            abbreviate_skills(&self.preset_skills, &self.skill_abbreviation_map).join(", ")
+        );
+    }
 
-            // Create new trial with new team
-            let mut trial = create_trial(
-                format!("{}", self.study.identifier),
-                format!("{:?}", skill_variation),
-                self.study.simulation_qty as usize,
-                new_team,
-                self.dungeons[0].dungeon.clone(),
-                [self.dungeons[0].difficulty].to_vec(),
-                self.dungeons[0].force_minibosses,
-                false,
-            )
-            .unwrap();
+    fn get_trial_csv_paths(&self) -> Vec<String> {
+        return vec![
+            self.get_duo_skillz_results_csv_path(),
+            self.get_trial_results_csv_path(),
+        ];
+    }
 
-            // Run simulations
-            let timer = Instant::now();
-            trial.run_simulations_single_threaded();
-            let timer_duration = timer.elapsed().as_nanos() as f32 / 1000000.0f32;
-            info!("Completed trial in {:#?}ms.", timer_duration,);
+    /// Save the trial to both Peetee's DuoSkillz format and the trial results csv
+    fn save_trial_results(&self, trial: &Trial) -> Result<(), std::io::Error> {
+        return trial.save_duo_skillz_and_trial_result_to_csv(
+            self.get_duo_skillz_results_csv_path(),
+            self.get_trial_results_csv_path(),
+            self.skill_abbreviation_map.clone(),
+        );
+    }
 
-            // Save Duo Skillz Results
-            let duo_skillz_result_csv_path = f!(
-                "adept_data/output/simulations/{}/csvs/duo_skillz_results.csv",
-                self.study.identifier
-            );
-            if let Some(p) = std::path::Path::new(&duo_skillz_result_csv_path).parent() {
-                std::fs::create_dir_all(p).unwrap();
-            }
-            // Save Trial Results
-            let trial_result_csv_path = f!(
-                "adept_data/output/simulations/{}/csvs/trial_results.csv",
-                self.study.identifier
-            );
-            if let Some(p) = std::path::Path::new(&trial_result_csv_path).parent() {
-                std::fs::create_dir_all(p).unwrap();
-            }
-            trial
-                .save_duo_skillz_and_trial_result_to_csv(
-                    duo_skillz_result_csv_path,
-                    trial_result_csv_path,
-                    self.skill_abbreviation_map.clone(),
-                )
-                .unwrap();
-            self.increment_combination_index();
-        }
+    /// Save the ranked skillsets along with their slot placement and slot weighted score
+    /// The composite score uses the slot weighted success rate, so both rankings agree
+    fn get_success_rate_factor(&self, variation: &Vec<String>) -> f64 {
+        return self.get_slot_weight_factor(variation);
+    }
 
-        // Outside While, this is assumed but check anyways because why not...
-        if self.count_skill_variations_remaining() == 0 {
-            // TODO: Any other tasks that must be done once finished
-            tx.send((
-                self.study.identifier.to_string(),
-                self._count_skill_variations_total() as u32,
-                self._count_skill_variations_total() as u32,
-                study_start_instant,
-            ))
-            .unwrap();
-            self.study.status = StudyStatus::Finished;
-            pb.finish_with_message("Study Complete");
-        } else {
-            error!("This should not occur, while running study managed to escape while loop with skill variations remaining...");
-            log::logger().flush();
-            panic!("This should not occur, while running study managed to escape while loop with skill variations remaining...")
-        }
+    fn save_study_results(&self) -> Result<(), std::io::Error> {
+        return self.save_static_duo_skill_ranking_to_csv(f!(
+            "{}/csvs/static_duo_skill_ranking.csv",
+            self.study.get_output_path()
+        ));
     }
 }
 
 impl StaticDuoSkillStudy {
+    fn get_duo_skillz_results_csv_path(&self) -> String {
+        return f!(
+            "{}/csvs/duo_skillz_results.csv",
+            self.study.get_output_path()
+        );
+    }
+
+    fn get_trial_results_csv_path(&self) -> String {
+        return f!("{}/csvs/trial_results.csv", self.study.get_output_path());
+    }
+
     pub fn _count_skill_variations_completed(&self) -> i64 {
         return self.skill_combination_index;
     }
+    /// Returns the number of valid skillsets, excluding those with incompatible skills or too many of a skill category
     pub fn _count_skill_variations_total(&self) -> i64 {
-        return crate::combinations::count_combinations(
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
-        );
-    }
-    pub fn count_skill_variations_remaining(&self) -> i64 {
-        return crate::combinations::count_combinations(
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
-        ) - self.skill_combination_index;
-    }
-    pub fn _get_skillset_at_specific_combination_index(&self, combination_index: i64) -> Vec<i64> {
-        return crate::combinations::iter_combination(
-            combination_index,
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
-        );
+        return self.valid_combination_indices.len() as i64;
     }
-    pub fn get_skillset_at_current_combination_index(&self) -> Vec<i64> {
+    pub fn get_skillset_at_specific_combination_index(&self, combination_index: i64) -> Vec<i64> {
         return crate::combinations::iter_combination(
-            self.skill_combination_index,
+            self.valid_combination_indices[combination_index as usize],
             self.valid_skills_count,
             self.varying_skill_slot_count,
         );
     }
-    pub fn increment_combination_index(&mut self) {
-        self.skill_combination_index += 1;
-    }
     pub fn translate_skillset_from_indices(&self, indices_array: Vec<i64>) -> Vec<String> {
         let mut res = vec![];
         for idx in indices_array {
@@ -274,11 +210,125 @@ impl StaticDuoSkillStudy {
         }
         return res;
     }
-    pub fn get_full_translated_skillset_at_current_combination_index(&self) -> Vec<String> {
+    pub fn get_full_translated_skillset_at_combination_index(
+        &self,
+        combination_index: i64,
+    ) -> Vec<String> {
         let mut res = self.preset_skills.clone();
-        let mut translated_skillset =
-            self.translate_skillset_from_indices(self.get_skillset_at_current_combination_index());
+        let mut translated_skillset = self.translate_skillset_from_indices(
+            self.get_skillset_at_specific_combination_index(combination_index),
+        );
+        if self.ordered_skill_slots {
+            // Rarer skills are placed in earlier open slots, with ties in alphabetical order
+            translated_skillset.sort_by(|a, b| {
+                return self
+                    .get_skill_rarity_rank(b)
+                    .cmp(&self.get_skill_rarity_rank(a))
+                    .then(a.to_lowercase().cmp(&b.to_lowercase()));
+            });
+        }
         res.append(&mut translated_skillset);
         return res;
     }
+
+    fn get_skill_rarity_rank(&self, skill: &String) -> u8 {
+        return match self.study.get_hero_skill_map().get(skill) {
+            Some(hskill) => hskill.get_rarity_rank(),
+            None => 0,
+        };
+    }
+
+    /// Returns the factor a skillset's success rate is scaled by to give its slot weighted score, from the weights of the open slots the varied skills fill
+    /// Each open slot's weight counts in proportion to the rarity of the skill placed in it, since rarer skills are harder to roll
+    /// Equals 1 when slots are unordered or every slot weight is 1
+    fn get_slot_weight_factor(&self, skill_variation: &Vec<String>) -> f64 {
+        if !self.ordered_skill_slots {
+            return 1.0;
+        }
+        let mut weighted_total = 0.0;
+        let mut rarity_total = 0.0;
+        for (slot, skill) in skill_variation
+            .iter()
+            .enumerate()
+            .skip(self.preset_skills.len())
+        {
+            let rarity_rank = self.get_skill_rarity_rank(skill) as f64;
+            weighted_total += self.slot_weights[slot] * rarity_rank;
+            rarity_total += rarity_rank;
+        }
+        if rarity_total == 0.0 {
+            return 1.0;
+        }
+        return weighted_total / rarity_total;
+    }
+
+    /// Save the skillsets to a CSV in the same order as ranked_results.csv, whose composite score uses the slot weighted success rate
+    /// Replaces any previous ranking at the path
+    fn save_static_duo_skill_ranking_to_csv(
+        &self,
+        string_path: String,
+    ) -> Result<(), std::io::Error> {
+        if let Some(p) = std::path::Path::new(&string_path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let mut wtr = csv::Writer::from_path(string_path)?;
+
+        let scored_variations = rank_variations_by_composite_score(
+            self.study.get_ranked_variations(),
+            &self.study.report_settings,
+            &|variation| self.get_slot_weight_factor(variation),
+        );
+
+        for (i, (rv, _)) in scored_variations.iter().enumerate() {
+            let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+            let slot_weighted_score =
+                final_result.get_success_rate() * self.get_slot_weight_factor(&rv.get_variation());
+            let skills = abbreviate_skills(&rv.get_variation(), &self.skill_abbreviation_map);
+            let record = StaticDuoSkillRankingCSVRecord {
+                rank: i + 1,
+                skills: skills.join(";"),
+                slot_placement: if self.ordered_skill_slots {
+                    skills
+                        .iter()
+                        .enumerate()
+                        .map(|(slot, skill)| f!("{}:{}", slot + 1, skill))
+                        .collect::<Vec<String>>()
+                        .join(";")
+                } else {
+                    String::from("")
+                },
+                final_stage: *final_stage,
+                final_dungeon_identifier: final_result.get_dungeon_identifier(),
+                final_difficulty_settings: final_result
+                    .get_difficulty_settings()
+                    .iter()
+                    .map(|diff| get_difficulty_name(*diff))
+                    .collect::<Vec<&str>>()
+                    .join(";"),
+                success_rate: f!("{:.4}", round_to_4(final_result.get_success_rate())),
+                slot_weighted_score: f!("{:.4}", round_to_4(slot_weighted_score)),
+                average_rounds: f!("{:.2}", round_to_2(final_result.get_average_rounds())),
+                equipment_substitutions: final_result.get_equipment_substitutions().join(";"),
+            };
+            wtr.serialize(record)?;
+        }
+
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// Defines the format for saving the ranking of a static duo skill study to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct StaticDuoSkillRankingCSVRecord {
+    rank: usize,
+    skills: String,
+    slot_placement: String, // Semi-colon separated list of slot:skill for each skill slot, empty unless slots are ordered
+    final_stage: usize,
+    final_dungeon_identifier: String,
+    final_difficulty_settings: String,
+    success_rate: String,
+    slot_weighted_score: String,
+    average_rounds: String,
+    equipment_substitutions: String, // Semi-colon separated list of the alternate equipment substituted for the skillset
 }
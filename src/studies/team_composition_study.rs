@@ -0,0 +1,253 @@
+use std::time::Instant;
+
+use indicatif::{MultiProgress, ProgressStyle};
+
+use crate::{
+    decimals::{round_to_2, round_to_4},
+    dungeons::TrialDungeon,
+    equipment::BoosterType,
+    heroes::{create_team, SimHero, Team},
+    studies::*,
+    trials::{get_difficulty_name, AdaptiveSampling, Trial},
+};
+
+/// An extension of Study for generating and ranking Trials for each team that can be formed from a pool of heroes
+/// A variation is the identifiers of the heroes on the team, in the order they appear in the pool
+pub struct TeamCompositionStudy {
+    study: Study,
+    hero_pool: Vec<SimHero>,       // The heroes that teams are formed from
+    booster: Option<BoosterType>,  // The booster applied to every team
+    team_size: usize,              // The number of heroes on each team, not counting its champion
+    compositions: Vec<Vec<usize>>, // The indices into hero_pool of the heroes on each valid team, in the order they are trialed
+    composition_index: i64,        // The current index of the compositions being trialed
+}
+
+pub fn create_team_composition_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    runoff_scoring_threshold: f64,
+    hero_pool: Vec<SimHero>,
+    booster: Option<BoosterType>,
+    team_size: usize,
+    dungeons: Vec<TrialDungeon>,
+    automatic_rank_difficulty_optimization: bool,
+    rank_difficulty_ceiling: f64,
+    hero_builder_information: HeroBuilderInformation,
+    seed: u64,
+    docket_row_hash: u64,
+    output_path: String,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    combat_log_cap: usize,
+    report_settings: ReportSettings,
+) -> TeamCompositionStudy {
+    let compositions = enumerate_compositions(&hero_pool, team_size);
+    return TeamCompositionStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            runoff_scoring_threshold,
+            hero_builder_information,
+            seed,
+            docket_row_hash,
+            output_path,
+            adaptive_sampling,
+            combat_log_cap,
+            dungeons,
+            automatic_rank_difficulty_optimization,
+            rank_difficulty_ceiling,
+            report_settings,
+        ),
+        hero_pool,
+        booster,
+        team_size,
+        compositions,
+        composition_index: 0,
+    };
+}
+
+/// Returns every team of team_size heroes from the pool led by one of the pool's champions
+/// The heroes of each composition are in the order they appear in the pool
+fn enumerate_compositions(hero_pool: &Vec<SimHero>, team_size: usize) -> Vec<Vec<usize>> {
+    let champion_indices: Vec<usize> = (0..hero_pool.len())
+        .filter(|idx| hero_pool[*idx].is_champion())
+        .collect();
+    let hero_indices: Vec<usize> = (0..hero_pool.len())
+        .filter(|idx| !hero_pool[*idx].is_champion())
+        .collect();
+    let hero_qty = hero_indices.len() as i64;
+    let mut res = vec![];
+    for champion_idx in champion_indices.iter() {
+        for combination_index in
+            0..crate::combinations::count_combinations(hero_qty, team_size as i64)
+        {
+            let mut composition: Vec<usize> = crate::combinations::iter_combination(
+                combination_index,
+                hero_qty,
+                team_size as i64,
+            )
+            .iter()
+            .map(|idx| hero_indices[*idx as usize])
+            .collect();
+            composition.push(*champion_idx);
+            composition.sort();
+            res.push(composition);
+        }
+    }
+    return res;
+}
+
+impl Runnable for TeamCompositionStudy {
+    /// Handle running trials for the study
+    fn run(
+        &mut self,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: Sender<(String, u64, u64, Instant)>,
+    ) {
+        self.run_variation_study(m, m_sty, tx);
+    }
+}
+
+impl VariationStudy for TeamCompositionStudy {
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn get_combination_index(&self) -> i64 {
+        return self.composition_index;
+    }
+
+    fn set_combination_index(&mut self, combination_index: i64) {
+        self.composition_index = combination_index;
+    }
+
+    fn count_variations_total(&self) -> i64 {
+        return self.compositions.len() as i64;
+    }
+
+    fn get_variation_at_combination_index(&self, combination_index: i64) -> Vec<String> {
+        return self.compositions[combination_index as usize]
+            .iter()
+            .map(|idx| self.hero_pool[*idx].get_identifier())
+            .collect();
+    }
+
+    /// Create the team of the heroes in the variation, whose equipment is never substituted
+    fn create_team_for_variation(&self, variation: &Vec<String>) -> (Team, Vec<String>) {
+        let heroes: Vec<SimHero> = variation
+            .iter()
+            .map(|identifier| {
+                self.hero_pool
+                    .iter()
+                    .find(|hero| hero.get_identifier() == *identifier)
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        return (create_team(heroes, self.booster.clone()).unwrap(), vec![]);
+    }
+
+    fn get_progress_message(&self) -> String {
+        return format!(
+            "{} ({} of {} heroes)",
+            self.study.get_identifier(),
+            self.team_size,
+            self.hero_pool.len()
+        );
+    }
+
+    fn get_trial_csv_paths(&self) -> Vec<String> {
+        return vec![self.get_trial_results_csv_path()];
+    }
+
+    fn save_trial_results(&self, trial: &Trial) -> Result<(), std::io::Error> {
+        return trial.save_trial_result_to_csv(self.get_trial_results_csv_path());
+    }
+
+    /// Save the ranked teams along with the survival rate of each hero on the final stage each reached
+    fn save_study_results(&self) -> Result<(), std::io::Error> {
+        return save_team_composition_ranking_to_csv(
+            f!(
+                "{}/csvs/team_composition_ranking.csv",
+                self.study.get_output_path()
+            ),
+            self.study.get_ranked_variations(),
+        );
+    }
+}
+
+impl TeamCompositionStudy {
+    fn get_trial_results_csv_path(&self) -> String {
+        return f!("{}/csvs/trial_results.csv", self.study.get_output_path());
+    }
+}
+
+/// Defines the format for saving the ranking of a team composition study to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TeamCompositionRankingCSVRecord {
+    rank: usize,
+    team: String,
+    final_stage: usize,
+    final_dungeon_identifier: String,
+    final_difficulty_settings: String,
+    success_rate: String,
+    success_rate_ci_lower: String,
+    success_rate_ci_upper: String,
+    average_rounds: String,
+    hero_survival_rates: String, // Semi-colon separated list of hero:survival_rate for each hero on the team
+}
+
+/// Save the (ranked) teams to a CSV, replacing any previous ranking at the path
+fn save_team_composition_ranking_to_csv(
+    string_path: String,
+    ranked_variations: &Vec<RankedVariation>,
+) -> Result<(), std::io::Error> {
+    if let Some(p) = std::path::Path::new(&string_path).parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    let mut wtr = csv::Writer::from_path(string_path)?;
+
+    for (i, rv) in ranked_variations.iter().enumerate() {
+        let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+        let (ci_lower, ci_upper) = final_result.get_success_rate_interval();
+        let team = rv.get_variation();
+        let record = TeamCompositionRankingCSVRecord {
+            rank: i + 1,
+            team: team.join(";"),
+            final_stage: *final_stage,
+            final_dungeon_identifier: final_result.get_dungeon_identifier(),
+            final_difficulty_settings: final_result
+                .get_difficulty_settings()
+                .iter()
+                .map(|diff| get_difficulty_name(*diff))
+                .collect::<Vec<&str>>()
+                .join(";"),
+            success_rate: f!("{:.4}", round_to_4(final_result.get_success_rate())),
+            success_rate_ci_lower: f!("{:.4}", round_to_4(ci_lower)),
+            success_rate_ci_upper: f!("{:.4}", round_to_4(ci_upper)),
+            average_rounds: f!("{:.2}", round_to_2(final_result.get_average_rounds())),
+            hero_survival_rates: team
+                .iter()
+                .enumerate()
+                .map(|(hero_index, identifier)| {
+                    f!(
+                        "{}:{:.4}",
+                        identifier,
+                        round_to_4(final_result.get_hero_survival_rate(hero_index))
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(";"),
+        };
+        wtr.serialize(record)?;
+    }
+
+    wtr.flush()?;
+    return Ok(());
+}
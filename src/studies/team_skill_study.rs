@@ -0,0 +1,455 @@
+use std::time::Instant;
+
+use indicatif::{MultiProgress, ProgressStyle};
+
+use crate::{
+    decimals::{round_to_2, round_to_4},
+    dungeons::TrialDungeon,
+    heroes::Team,
+    studies::*,
+    trials::{get_difficulty_name, AdaptiveSampling, Trial},
+};
+
+/// A hero on the team whose skills are varied by a TeamSkillStudy
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VariedHero {
+    hero_identifier: String,
+    hero_builder: crate::hero_builder::Hero, // The hero builder representation of the hero, to be converted to a simhero for variation
+    valid_skills: Vec<String>,               // The vector of all skills to be varied upon
+    valid_skills_count: i64,                 // The number of valid skills to vary upon
+    preset_skills: Vec<String>, // A vector containing 0-3 skills that are preset and unchanging
+    varying_skill_slot_count: i64, // The number of skill slots to vary
+    valid_combination_indices: Vec<i64>, // The indices of the combinations of the valid_skills list that form valid skillsets
+}
+
+pub fn create_varied_hero(
+    hero_identifier: String,
+    hero_builder: crate::hero_builder::Hero,
+    valid_skills: Vec<String>,
+    preset_skills: Vec<String>,
+    hero_builder_information: &HeroBuilderInformation,
+) -> VariedHero {
+    let mut vs = valid_skills.clone();
+    vs.retain(|x| !preset_skills.contains(x));
+    vs.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+    let varying_skill_slot_count = 4 - preset_skills.len() as i64;
+    let valid_combination_indices = hero_builder_information.get_valid_skill_combination_indices(
+        &vs,
+        &preset_skills,
+        varying_skill_slot_count,
+    );
+    return VariedHero {
+        hero_identifier,
+        hero_builder,
+        valid_skills_count: vs.len() as i64,
+        valid_skills: vs,
+        varying_skill_slot_count,
+        preset_skills,
+        valid_combination_indices,
+    };
+}
+
+impl VariedHero {
+    /// Returns the number of valid skillsets, excluding those with incompatible skills or too many of a skill category
+    pub fn count_skill_variations_total(&self) -> i64 {
+        return self.valid_combination_indices.len() as i64;
+    }
+
+    /// Returns the number of skills in each of the hero's skillsets
+    pub fn count_skills(&self) -> usize {
+        return self.preset_skills.len() + self.varying_skill_slot_count as usize;
+    }
+
+    pub fn get_full_translated_skillset_at_combination_index(
+        &self,
+        combination_index: i64,
+    ) -> Vec<String> {
+        let mut res = self.preset_skills.clone();
+        for idx in crate::combinations::iter_combination(
+            self.valid_combination_indices[combination_index as usize],
+            self.valid_skills_count,
+            self.varying_skill_slot_count,
+        ) {
+            res.push(self.valid_skills[idx as usize].clone());
+        }
+        return res;
+    }
+}
+
+/// The most joint skillsets a TeamSkillStudy may trial, each is a full trial of simulation_qty simulations
+pub const MAX_TEAM_SKILL_VARIATIONS: i64 = 10_000_000;
+
+/// Returns the product of the varied heroes' skillset counts, None if it overflows an i64
+fn checked_count_joint_skill_variations(varied_heroes: &Vec<VariedHero>) -> Option<i64> {
+    return varied_heroes.iter().try_fold(1i64, |total, vh| {
+        total.checked_mul(vh.count_skill_variations_total())
+    });
+}
+
+/// Check that every varied hero has a valid skillset and that the joint skill space is no larger than MAX_TEAM_SKILL_VARIATIONS
+pub fn validate_joint_skill_variations(varied_heroes: &Vec<VariedHero>) -> Result<(), String> {
+    for vh in varied_heroes {
+        if vh.count_skill_variations_total() == 0 {
+            return Err(f!("{} has no valid skillsets", vh.hero_identifier));
+        }
+    }
+    match checked_count_joint_skill_variations(varied_heroes) {
+        Some(total) if total <= MAX_TEAM_SKILL_VARIATIONS => return Ok(()),
+        Some(total) => {
+            return Err(f!(
+                "The heroes' skillsets form {} joint combinations, more than the maximum of {}",
+                total,
+                MAX_TEAM_SKILL_VARIATIONS
+            ))
+        }
+        None => {
+            return Err(f!(
+                "The heroes' skillsets form more joint combinations than can be counted, the maximum is {}",
+                MAX_TEAM_SKILL_VARIATIONS
+            ))
+        }
+    }
+}
+
+/// An extension of Study for generating and ranking Trials for each joint combination of skills for several heroes on a team
+/// A variation is the skillsets of the varied heroes concatenated in order
+pub struct TeamSkillStudy {
+    study: Study,
+    base_team: Team,
+    varied_heroes: Vec<VariedHero>, // The heroes to vary upon, heroes on the team that are not listed keep their skills
+    combination_index: i64, // The current index of the joint combinations of the varied heroes' skills being trialed
+}
+
+pub fn create_team_skill_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    runoff_scoring_threshold: f64,
+    base_team: Team,
+    varied_heroes: Vec<VariedHero>,
+    dungeons: Vec<TrialDungeon>,
+    automatic_rank_difficulty_optimization: bool,
+    rank_difficulty_ceiling: f64,
+    hero_builder_information: HeroBuilderInformation,
+    seed: u64,
+    docket_row_hash: u64,
+    output_path: String,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    combat_log_cap: usize,
+    report_settings: ReportSettings,
+) -> TeamSkillStudy {
+    return TeamSkillStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            runoff_scoring_threshold,
+            hero_builder_information,
+            seed,
+            docket_row_hash,
+            output_path,
+            adaptive_sampling,
+            combat_log_cap,
+            dungeons,
+            automatic_rank_difficulty_optimization,
+            rank_difficulty_ceiling,
+            report_settings,
+        ),
+        base_team,
+        varied_heroes,
+        combination_index: 0,
+    };
+}
+
+impl Runnable for TeamSkillStudy {
+    /// Handle running trials for the study
+    fn run(
+        &mut self,
+        m: &MultiProgress,
+        m_sty: &ProgressStyle,
+        tx: Sender<(String, u64, u64, Instant)>,
+    ) {
+        self.run_variation_study(m, m_sty, tx);
+    }
+}
+
+impl VariationStudy for TeamSkillStudy {
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn get_combination_index(&self) -> i64 {
+        return self.combination_index;
+    }
+
+    fn set_combination_index(&mut self, combination_index: i64) {
+        self.combination_index = combination_index;
+    }
+
+    /// The joint skill space is the product of each varied hero's skill combinations
+    /// It is checked by validate_joint_skill_variations when the docket is loaded
+    fn count_variations_total(&self) -> i64 {
+        match checked_count_joint_skill_variations(&self.varied_heroes) {
+            Some(total) => return total,
+            None => {
+                error!(
+                    "The joint skill variations of {} overflow",
+                    self.study.get_identifier()
+                );
+                log::logger().flush();
+                panic!(
+                    "The joint skill variations of {} overflow",
+                    self.study.get_identifier()
+                );
+            }
+        }
+    }
+
+    /// The last varied hero's skillset changes fastest as the combination index increases
+    fn get_variation_at_combination_index(&self, combination_index: i64) -> Vec<String> {
+        let mut hero_combination_indices = vec![0; self.varied_heroes.len()];
+        let mut remaining_index = combination_index;
+        for (i, vh) in self.varied_heroes.iter().enumerate().rev() {
+            let hero_total = vh.count_skill_variations_total();
+            hero_combination_indices[i] = remaining_index % hero_total;
+            remaining_index /= hero_total;
+        }
+
+        let mut res = vec![];
+        for (vh, hero_combination_index) in self.varied_heroes.iter().zip(hero_combination_indices)
+        {
+            res.append(
+                &mut vh.get_full_translated_skillset_at_combination_index(hero_combination_index),
+            );
+        }
+        return res;
+    }
+
+    /// Create the team with each varied hero built using its skillset from the variation
+    fn create_team_for_variation(&self, variation: &Vec<String>) -> (Team, Vec<String>) {
+        let mut new_team = self.base_team.clone();
+        let mut equipment_substitutions: Vec<String> = vec![];
+        for (vh, skillset) in self
+            .varied_heroes
+            .iter()
+            .zip(self.split_variation_by_hero(variation))
+        {
+            let (hero_team, mut hero_substitutions) =
+                self.study.create_team_with_subject_hero_skillset(
+                    &new_team,
+                    &vh.hero_identifier,
+                    &vh.hero_builder,
+                    &skillset,
+                );
+            new_team = hero_team;
+            equipment_substitutions.append(&mut hero_substitutions);
+        }
+        return (new_team, equipment_substitutions);
+    }
+
+    fn get_progress_message(&self) -> String {
+        return format!(
+            "{} ({})",
+            self.study.get_identifier(),
+            self.varied_heroes
+                .iter()
+                .map(|vh| vh.hero_identifier.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+    }
+
+    fn get_trial_csv_paths(&self) -> Vec<String> {
+        return vec![self.get_trial_results_csv_path()];
+    }
+
+    fn save_trial_results(&self, trial: &Trial) -> Result<(), std::io::Error> {
+        return trial.save_trial_result_to_csv(self.get_trial_results_csv_path());
+    }
+
+    /// Save the ranked joint loadouts, then the marginal contribution of each hero's skillsets
+    fn save_study_results(&self) -> Result<(), std::io::Error> {
+        self.save_team_skill_ranking_to_csv(f!(
+            "{}/csvs/team_skill_ranking.csv",
+            self.study.get_output_path()
+        ))?;
+        return self.save_marginal_contributions_to_csv(f!(
+            "{}/csvs/team_skill_marginal_contributions.csv",
+            self.study.get_output_path()
+        ));
+    }
+}
+
+impl TeamSkillStudy {
+    fn get_trial_results_csv_path(&self) -> String {
+        return f!("{}/csvs/trial_results.csv", self.study.get_output_path());
+    }
+
+    /// Split a variation back into the skillset of each varied hero, in the order of varied_heroes
+    fn split_variation_by_hero(&self, variation: &Vec<String>) -> Vec<Vec<String>> {
+        let mut res = vec![];
+        let mut start = 0;
+        for vh in &self.varied_heroes {
+            let end = start + vh.count_skills();
+            res.push(variation[start..end].to_vec());
+            start = end;
+        }
+        return res;
+    }
+
+    /// Format a variation as "Hero: Skill;Skill;... | Hero: ...", abbreviating each skill where possible
+    fn format_loadouts(&self, variation: &Vec<String>) -> String {
+        return self
+            .varied_heroes
+            .iter()
+            .zip(self.split_variation_by_hero(variation))
+            .map(|(vh, skillset)| {
+                f!(
+                    "{}: {}",
+                    vh.hero_identifier,
+                    abbreviate_skills(&skillset, self.study.get_skill_abbreviation_map()).join(";")
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" | ");
+    }
+
+    /// Save the (ranked) joint loadouts to a CSV, replacing any previous ranking at the path
+    /// Survival rates are given for every hero on the team, including those whose skills are not varied
+    fn save_team_skill_ranking_to_csv(&self, string_path: String) -> Result<(), std::io::Error> {
+        if let Some(p) = std::path::Path::new(&string_path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let mut wtr = csv::Writer::from_path(string_path)?;
+
+        let team_hero_identifiers: Vec<String> = self
+            .base_team
+            .get_heroes()
+            .iter()
+            .map(|hero| hero.get_identifier())
+            .collect();
+        for (i, rv) in self.study.get_ranked_variations().iter().enumerate() {
+            let (final_stage, final_result) = rv.get_latest_stage_result().unwrap();
+            let (ci_lower, ci_upper) = final_result.get_success_rate_interval();
+            let record = TeamSkillRankingCSVRecord {
+                rank: i + 1,
+                loadouts: self.format_loadouts(&rv.get_variation()),
+                final_stage: *final_stage,
+                final_dungeon_identifier: final_result.get_dungeon_identifier(),
+                final_difficulty_settings: final_result
+                    .get_difficulty_settings()
+                    .iter()
+                    .map(|diff| get_difficulty_name(*diff))
+                    .collect::<Vec<&str>>()
+                    .join(";"),
+                success_rate: f!("{:.4}", round_to_4(final_result.get_success_rate())),
+                success_rate_ci_lower: f!("{:.4}", round_to_4(ci_lower)),
+                success_rate_ci_upper: f!("{:.4}", round_to_4(ci_upper)),
+                average_rounds: f!("{:.2}", round_to_2(final_result.get_average_rounds())),
+                hero_survival_rates: team_hero_identifiers
+                    .iter()
+                    .enumerate()
+                    .map(|(hero_index, identifier)| {
+                        f!(
+                            "{}:{:.4}",
+                            identifier,
+                            round_to_4(final_result.get_hero_survival_rate(hero_index))
+                        )
+                    })
+                    .collect::<Vec<String>>()
+                    .join(";"),
+                equipment_substitutions: final_result.get_equipment_substitutions().join(";"),
+            };
+            wtr.serialize(record)?;
+        }
+
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    /// Save the marginal contribution of each varied hero's skillsets to a CSV, replacing any previous results at the path
+    /// Every joint loadout is trialed on the initial stage, so a skillset's marginal contribution is the mean success rate of the
+    /// loadouts where the hero used it, less the mean success rate of every loadout
+    fn save_marginal_contributions_to_csv(
+        &self,
+        string_path: String,
+    ) -> Result<(), std::io::Error> {
+        if let Some(p) = std::path::Path::new(&string_path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let mut wtr = csv::Writer::from_path(string_path)?;
+
+        let ranked_variations = self.study.get_ranked_variations();
+        let overall_mean = ranked_variations
+            .iter()
+            .map(|rv| rv.get_stage_result(0).unwrap().get_success_rate())
+            .sum::<f64>()
+            / ranked_variations.len() as f64;
+
+        for (hero_index, vh) in self.varied_heroes.iter().enumerate() {
+            // Skillset -> (sum of success rates, number of loadouts)
+            let mut skillset_totals: HashMap<Vec<String>, (f64, usize)> = HashMap::new();
+            for rv in ranked_variations {
+                let skillset =
+                    self.split_variation_by_hero(&rv.get_variation())[hero_index].clone();
+                let totals = skillset_totals.entry(skillset).or_insert((0.0, 0));
+                totals.0 += rv.get_stage_result(0).unwrap().get_success_rate();
+                totals.1 += 1;
+            }
+
+            let mut skillset_means: Vec<(Vec<String>, usize, f64)> = skillset_totals
+                .into_iter()
+                .map(|(skillset, (total, qty))| (skillset, qty, total / qty as f64))
+                .collect();
+            skillset_means.sort_by(|a, b| {
+                b.2.partial_cmp(&a.2)
+                    .unwrap_or(Ordering::Equal)
+                    .then(a.0.cmp(&b.0))
+            });
+            for (skillset, qty, mean) in skillset_means {
+                let record = TeamSkillMarginalContributionCSVRecord {
+                    hero_identifier: vh.hero_identifier.to_string(),
+                    skills: abbreviate_skills(&skillset, self.study.get_skill_abbreviation_map())
+                        .join(";"),
+                    loadouts_trialed: qty,
+                    mean_success_rate: f!("{:.4}", round_to_4(mean)),
+                    marginal_contribution: f!("{:.4}", round_to_4(mean - overall_mean)),
+                };
+                wtr.serialize(record)?;
+            }
+        }
+
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// Defines the format for saving the ranking of a team skill study to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TeamSkillRankingCSVRecord {
+    rank: usize,
+    loadouts: String,
+    final_stage: usize,
+    final_dungeon_identifier: String,
+    final_difficulty_settings: String,
+    success_rate: String,
+    success_rate_ci_lower: String,
+    success_rate_ci_upper: String,
+    average_rounds: String,
+    hero_survival_rates: String, // Semi-colon separated list of hero:survival_rate for each hero on the team
+    equipment_substitutions: String, // Semi-colon separated list of the alternate equipment substituted for the loadouts
+}
+
+/// Defines the format for saving the marginal contribution of a hero's skillset to CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TeamSkillMarginalContributionCSVRecord {
+    hero_identifier: String,
+    skills: String,
+    loadouts_trialed: usize,
+    mean_success_rate: String,
+    marginal_contribution: String,
+}
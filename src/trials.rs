@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 
 use crate::decimals::{round_to_2, round_to_3, round_to_4};
+use crate::statistics::{percentile, std_dev, two_proportion_z_test, wilson_interval, Z_95};
 
-use super::dungeons::Dungeon;
-use super::heroes::Team;
+use super::combat_events::CombatLog;
+use super::dungeons::{BarrierSetting, Dungeon};
+use super::equipment::ElementType;
+use super::heroes::{HeroAccuracyStats, Team};
 use super::simulations::{create_simulation, SimResult};
 
-use log::info;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 extern crate csv;
@@ -21,38 +26,38 @@ struct TrialCSVRecord {
 
     hp_remaining_hero_1: f64,
     dmg_dealt_hero_1: f64,
-    crits_taken_hero_1: u8,
-    crits_dealt_hero_1: u8,
-    dodges_hero_1: u8,
-    attacks_missed_hero_1: u8,
+    crits_taken_hero_1: u32,
+    crits_dealt_hero_1: u32,
+    dodges_hero_1: u32,
+    attacks_missed_hero_1: u32,
 
     hp_remaining_hero_2: f64,
     dmg_dealt_hero_2: f64,
-    crits_taken_hero_2: u8,
-    crits_dealt_hero_2: u8,
-    dodges_hero_2: u8,
-    attacks_missed_hero_2: u8,
+    crits_taken_hero_2: u32,
+    crits_dealt_hero_2: u32,
+    dodges_hero_2: u32,
+    attacks_missed_hero_2: u32,
 
     hp_remaining_hero_3: f64,
     dmg_dealt_hero_3: f64,
-    crits_taken_hero_3: u8,
-    crits_dealt_hero_3: u8,
-    dodges_hero_3: u8,
-    attacks_missed_hero_3: u8,
+    crits_taken_hero_3: u32,
+    crits_dealt_hero_3: u32,
+    dodges_hero_3: u32,
+    attacks_missed_hero_3: u32,
 
     hp_remaining_hero_4: f64,
     dmg_dealt_hero_4: f64,
-    crits_taken_hero_4: u8,
-    crits_dealt_hero_4: u8,
-    dodges_hero_4: u8,
-    attacks_missed_hero_4: u8,
+    crits_taken_hero_4: u32,
+    crits_dealt_hero_4: u32,
+    dodges_hero_4: u32,
+    attacks_missed_hero_4: u32,
 
     hp_remaining_hero_5: f64,
     dmg_dealt_hero_5: f64,
-    crits_taken_hero_5: u8,
-    crits_dealt_hero_5: u8,
-    dodges_hero_5: u8,
-    attacks_missed_hero_5: u8,
+    crits_taken_hero_5: u32,
+    crits_dealt_hero_5: u32,
+    dodges_hero_5: u32,
+    attacks_missed_hero_5: u32,
 }
 
 impl TrialCSVRecord {
@@ -86,13 +91,18 @@ fn _create_trial_csv_record(
     result: bool,
     rounds: i16,
     encounter_hp_remaining: f64,
-    team_hp_remaining: [f64; 5],
-    team_dmg_dealt: [f64; 5],
-    team_crits_taken: [u8; 5],
-    team_crits_dealt: [u8; 5],
-    team_dodges: [u8; 5],
-    team_attacks_missed: [u8; 5],
+    team_hp_remaining: Vec<f64>,
+    team_dmg_dealt: Vec<f64>,
+    team_accuracy_stats: Vec<HeroAccuracyStats>,
 ) -> TrialCSVRecord {
+    // Teams smaller than 5 heroes leave the remaining hero columns at zero
+    let hp_remaining =
+        |i: usize| -> f64 { return team_hp_remaining.get(i).copied().unwrap_or(0.0) };
+    let dmg_dealt = |i: usize| -> f64 { return team_dmg_dealt.get(i).copied().unwrap_or(0.0) };
+    let stats = |i: usize| -> HeroAccuracyStats {
+        return team_accuracy_stats.get(i).copied().unwrap_or_default();
+    };
+
     let t_csv_rec = TrialCSVRecord {
         trial_identifier,
         simulation_identifier,
@@ -100,87 +110,239 @@ fn _create_trial_csv_record(
         rounds,
         encounter_hp_remaining,
 
-        hp_remaining_hero_1: team_hp_remaining[0],
-        dmg_dealt_hero_1: team_dmg_dealt[0],
-        crits_taken_hero_1: team_crits_taken[0],
-        crits_dealt_hero_1: team_crits_dealt[0],
-        dodges_hero_1: team_dodges[0],
-        attacks_missed_hero_1: team_attacks_missed[0],
-
-        hp_remaining_hero_2: team_hp_remaining[1],
-        dmg_dealt_hero_2: team_dmg_dealt[1],
-        crits_taken_hero_2: team_crits_taken[1],
-        crits_dealt_hero_2: team_crits_dealt[1],
-        dodges_hero_2: team_dodges[1],
-        attacks_missed_hero_2: team_attacks_missed[1],
-
-        hp_remaining_hero_3: team_hp_remaining[2],
-        dmg_dealt_hero_3: team_dmg_dealt[2],
-        crits_taken_hero_3: team_crits_taken[2],
-        crits_dealt_hero_3: team_crits_dealt[2],
-        dodges_hero_3: team_dodges[2],
-        attacks_missed_hero_3: team_attacks_missed[2],
-
-        hp_remaining_hero_4: team_hp_remaining[3],
-        dmg_dealt_hero_4: team_dmg_dealt[3],
-        crits_taken_hero_4: team_crits_taken[3],
-        crits_dealt_hero_4: team_crits_dealt[3],
-        dodges_hero_4: team_dodges[3],
-        attacks_missed_hero_4: team_attacks_missed[3],
-
-        hp_remaining_hero_5: team_hp_remaining[4],
-        dmg_dealt_hero_5: team_dmg_dealt[4],
-        crits_taken_hero_5: team_crits_taken[4],
-        crits_dealt_hero_5: team_crits_dealt[4],
-        dodges_hero_5: team_dodges[4],
-        attacks_missed_hero_5: team_attacks_missed[4],
+        hp_remaining_hero_1: hp_remaining(0),
+        dmg_dealt_hero_1: dmg_dealt(0),
+        crits_taken_hero_1: stats(0).crits_taken,
+        crits_dealt_hero_1: stats(0).crits_dealt,
+        dodges_hero_1: stats(0).dodges,
+        attacks_missed_hero_1: stats(0).attacks_missed,
+
+        hp_remaining_hero_2: hp_remaining(1),
+        dmg_dealt_hero_2: dmg_dealt(1),
+        crits_taken_hero_2: stats(1).crits_taken,
+        crits_dealt_hero_2: stats(1).crits_dealt,
+        dodges_hero_2: stats(1).dodges,
+        attacks_missed_hero_2: stats(1).attacks_missed,
+
+        hp_remaining_hero_3: hp_remaining(2),
+        dmg_dealt_hero_3: dmg_dealt(2),
+        crits_taken_hero_3: stats(2).crits_taken,
+        crits_dealt_hero_3: stats(2).crits_dealt,
+        dodges_hero_3: stats(2).dodges,
+        attacks_missed_hero_3: stats(2).attacks_missed,
+
+        hp_remaining_hero_4: hp_remaining(3),
+        dmg_dealt_hero_4: dmg_dealt(3),
+        crits_taken_hero_4: stats(3).crits_taken,
+        crits_dealt_hero_4: stats(3).crits_dealt,
+        dodges_hero_4: stats(3).dodges,
+        attacks_missed_hero_4: stats(3).attacks_missed,
+
+        hp_remaining_hero_5: hp_remaining(4),
+        dmg_dealt_hero_5: dmg_dealt(4),
+        crits_taken_hero_5: stats(4).crits_taken,
+        crits_dealt_hero_5: stats(4).crits_dealt,
+        dodges_hero_5: stats(4).dodges,
+        attacks_missed_hero_5: stats(4).attacks_missed,
     };
 
     return t_csv_rec;
 }
 
+/// Settings for running a trial's simulations in batches until its success rate is known precisely enough
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    pub batch_size: usize, // The number of simulations to run between checks of the success rate interval
+    pub target_interval_width: f64, // Sampling stops once the 95% confidence interval on the success rate is at most this wide
+    pub elimination_threshold: Option<f64>, // Sampling stops early once the upper bound of the interval falls below this success rate
+}
+
 /// Defines instructions for running one or more Simulations
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Trial {
     identifier: String,
     description: String,
-    simulation_qty: usize,
+    simulation_qty: usize, // The number of simulations to run, or the maximum number when sampling adaptively
     team: Team,
     dungeon: Dungeon,
     difficulty_settings: Vec<usize>,
     force_minibosses: Option<bool>,
+    barrier_setting: BarrierSetting,
     results: Vec<SimResult>,
-    log_all: bool,
+    log_all: bool, // Whether to save the combat log of every simulation rather than only failed simulations
+    combat_log_directory: Option<String>, // When set, combat logs are saved to <combat_log_directory>/sim_<simulation index>.jsonl
+    combat_log_cap: usize, // The maximum number of failed simulations to save combat logs for
+    seed: u64, // Each simulation draws from its own rng derived from this seed and the simulation index
+    adaptive_sampling: Option<AdaptiveSampling>,
+    equipment_substitutions: Vec<String>, // The alternate equipment substituted for the team's skillsets, recorded with the result
 }
 
 impl Trial {
-    pub fn run_simulations_single_threaded(&mut self) {
-        while self.results.len() < self.simulation_qty {
-            // let timer = Instant::now();
-            // print!("Running simulation iteration:  # {:#?}", self.results.len());
-            if self.log_all == true {
-                info!(
-                    "\n\nRunning simulation iteration: # {}\n",
-                    self.results.len()
+    pub fn set_adaptive_sampling(&mut self, adaptive_sampling: Option<AdaptiveSampling>) {
+        self.adaptive_sampling = adaptive_sampling;
+    }
+
+    pub fn set_equipment_substitutions(&mut self, equipment_substitutions: Vec<String>) {
+        self.equipment_substitutions = equipment_substitutions;
+    }
+
+    pub fn set_barrier_setting(&mut self, barrier_setting: BarrierSetting) {
+        self.barrier_setting = barrier_setting;
+    }
+
+    pub fn set_combat_logging(&mut self, combat_log_directory: String, combat_log_cap: usize) {
+        self.combat_log_directory = Some(combat_log_directory);
+        self.combat_log_cap = combat_log_cap;
+    }
+
+    /// Run the simulations one after another
+    /// When sampling adaptively, simulations are run in batches until the success rate interval is narrow enough,
+    /// the trial is clearly losing, or simulation_qty is reached
+    pub fn _run_simulations_single_threaded(&mut self) {
+        loop {
+            let batch_end = self.get_next_batch_end();
+            while self.results.len() < batch_end {
+                let sim_res = self.run_simulation_at_index(self.results.len());
+                self.results.push(sim_res);
+            }
+            if self.is_sampling_complete() {
+                break;
+            }
+        }
+    }
+
+    /// Run the simulations across the rayon thread pool
+    /// Each simulation is seeded from its index, so the results (and their order) are identical to _run_simulations_single_threaded
+    pub fn run_simulations_parallel(&mut self) {
+        loop {
+            let batch_end = self.get_next_batch_end();
+            let mut batch_results: Vec<SimResult> = (self.results.len()..batch_end)
+                .into_par_iter()
+                .map(|simulation_index| self.run_simulation_at_index(simulation_index))
+                .collect();
+            self.results.append(&mut batch_results);
+            if self.is_sampling_complete() {
+                break;
+            }
+        }
+    }
+
+    /// Run the simulation at the given index, seeding it from the trial seed so it can be re-run on its own
+    pub fn run_simulation_at_index(&self, simulation_index: usize) -> SimResult {
+        let (sim_res, _) = self.simulate_at_index(simulation_index, false);
+        return sim_res;
+    }
+
+    /// Re-run the simulation at the given index with logging enabled, returning its combat log
+    /// The simulation is seeded identically, so the log matches the result recorded when the trial was run
+    pub fn replay_simulation_at_index(&self, simulation_index: usize) -> CombatLog {
+        let (_, combat_log) = self.simulate_at_index(simulation_index, true);
+        return combat_log;
+    }
+
+    fn simulate_at_index(&self, simulation_index: usize, log_all: bool) -> (SimResult, CombatLog) {
+        // let timer = Instant::now();
+        // print!("Running simulation iteration:  # {:#?}", simulation_index);
+        let mut rng =
+            StdRng::seed_from_u64(derive_simulation_seed(self.seed, simulation_index as u64));
+        let encounter = self
+            .dungeon
+            .generate_encounter_from_dungeon(
+                &self.difficulty_settings,
+                self.force_minibosses,
+                self.barrier_setting,
+                simulation_index,
+                &mut rng,
+            )
+            .unwrap();
+        let mut simulation = create_simulation(&self.team, encounter, vec![], log_all).unwrap();
+        let sim_res = simulation.run(&mut rng).unwrap();
+        // print!(
+        //     "\rRunning simulation iteration: # {:#?} | Success: {:#?} in {:#?} rounds | Took {:#?}ms\n",
+        //     simulation_index,
+        //     sim_res.is_success(),
+        //     sim_res.get_rounds(),
+        //     timer.elapsed().as_nanos() as f32 / 1000000.0f32,
+        // );
+        return (sim_res, simulation.get_combat_log().clone());
+    }
+
+    /// Save the combat logs of the first combat_log_cap failed simulations (or every simulation if log_all is set)
+    /// Logs are regenerated by replaying each simulation, so they are not held in memory while the trial runs
+    pub fn save_combat_logs(&self) -> Result<(), std::io::Error> {
+        let combat_log_directory: String;
+        match &self.combat_log_directory {
+            Some(directory) => combat_log_directory = directory.to_string(),
+            None => return Ok(()),
+        }
+
+        let simulation_indices: Vec<usize>;
+        if self.log_all {
+            simulation_indices = (0..self.results.len()).collect();
+        } else {
+            simulation_indices = self
+                .results
+                .iter()
+                .enumerate()
+                .filter(|(_, res)| !res.is_success())
+                .map(|(i, _)| i)
+                .take(self.combat_log_cap)
+                .collect();
+        }
+        if simulation_indices.len() == 0 {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(&combat_log_directory)?;
+        for simulation_index in simulation_indices {
+            let combat_log = self.replay_simulation_at_index(simulation_index);
+            std::fs::write(
+                f!("{}/sim_{}.jsonl", combat_log_directory, simulation_index),
+                combat_log.to_json_lines()?,
+            )?;
+        }
+        return Ok(());
+    }
+
+    /// Returns the number of results there should be once the next batch of simulations has run
+    fn get_next_batch_end(&self) -> usize {
+        match self.adaptive_sampling {
+            Some(adaptive) => {
+                return std::cmp::min(
+                    self.results.len() + adaptive.batch_size.max(1),
+                    self.simulation_qty,
                 );
             }
-            let encounter = self
-                .dungeon
-                .generate_encounter_from_dungeon(&self.difficulty_settings, self.force_minibosses)
-                .unwrap();
-            let mut simulation =
-                create_simulation(&self.team, encounter, vec![], self.log_all).unwrap();
-            let sim_res = simulation.run().unwrap();
-            // print!(
-            //     "\rRunning simulation iteration: # {:#?} | Success: {:#?} in {:#?} rounds | Took {:#?}ms\n",
-            //     self.results.len(),
-            //     sim_res.is_success(),
-            //     sim_res.get_rounds(),
-            //     timer.elapsed().as_nanos() as f32 / 1000000.0f32,
-            // );
-            self.results.push(sim_res);
+            None => return self.simulation_qty,
+        }
+    }
+
+    /// Whether enough simulations have been run for the trial
+    fn is_sampling_complete(&self) -> bool {
+        if self.results.len() >= self.simulation_qty {
+            return true;
+        }
+        match self.adaptive_sampling {
+            Some(adaptive) => {
+                let (lower, upper) = self.get_success_rate_interval();
+                if upper - lower <= adaptive.target_interval_width {
+                    return true;
+                }
+                match adaptive.elimination_threshold {
+                    Some(threshold) => return upper < threshold,
+                    None => return false,
+                }
+            }
+            None => return false,
         }
     }
+
+    /// Returns the 95% Wilson confidence interval on the success rate of the simulations run so far
+    pub fn get_success_rate_interval(&self) -> (f64, f64) {
+        let successes = self.results.iter().filter(|res| res.is_success()).count();
+        return wilson_interval(successes, self.results.len(), Z_95);
+    }
+
     pub fn _get_results_unranked(&self) -> Vec<SimResult> {
         return self.results.clone();
     }
@@ -196,10 +358,7 @@ impl Trial {
                 res.get_encounter_hp_remaining(),
                 res.get_team_hp_remaining(),
                 res.get_team_damage_dealt(),
-                res.get_team_crits_taken(),
-                res.get_team_crits_dealt(),
-                res.get_team_dodges(),
-                res.get_team_attacks_missed(),
+                res.get_team_accuracy_stats(),
             );
             wtr.serialize(record._round_floats_for_display())?;
         }
@@ -228,108 +387,152 @@ impl Trial {
             miniboss_results_length = 1
         }
 
-        let mut vec_hero_survival_rate: [Vec<f64>; 5] = Default::default();
-        let mut vec_hero_avg_hp_remaining: [Vec<f64>; 5] = Default::default();
-        let mut vec_hero_avg_dmg: [Vec<f64>; 5] = Default::default();
-        let mut vec_hero_avg_dodges: [Vec<f64>; 5] = Default::default();
-        let mut vec_hero_avg_atk_accuracy: [Vec<f64>; 5] = Default::default();
-        let mut vec_hero_avg_crits_dealt: [Vec<f64>; 5] = Default::default();
-        let mut vec_hero_avg_crits_taken: [Vec<f64>; 5] = Default::default();
+        // Each hero's rates are pooled over every simulation, dividing the total count by the total attempts it applies to
+        let hero_names: Vec<String> = all_results[0].get_team().get_team_hero_names();
+        let hero_qty = hero_names.len();
+        let mut vec_hero_survival_rate: Vec<Vec<f64>> = vec![vec![]; hero_qty];
+        let mut vec_hero_hp_remaining: Vec<Vec<f64>> = vec![vec![]; hero_qty];
+        let mut vec_hero_dmg: Vec<Vec<f64>> = vec![vec![]; hero_qty];
+        let mut hero_accuracy_stats: Vec<HeroAccuracyStats> = vec![Default::default(); hero_qty];
 
         for res in all_results.iter() {
-            let sim_rounds = res.get_rounds() as f64;
-
             let team_hp_rem = res.get_team_hp_remaining();
             let team_dmg_dealt = res.get_team_damage_dealt();
-            let team_dodges = res.get_team_dodges();
-            let team_atks_missed = res.get_team_attacks_missed();
-            let team_crits_dealt = res.get_team_crits_dealt();
-            let team_crits_taken = res.get_team_crits_taken();
-            for i in 0..5 {
+            let team_accuracy_stats = res.get_team_accuracy_stats();
+            for i in 0..hero_qty {
                 let mut survived: f64 = 0.0;
                 if team_hp_rem[i] > 0.0 {
                     survived = 1.0;
                 }
                 vec_hero_survival_rate[i].push(survived);
-                vec_hero_avg_hp_remaining[i].push(team_hp_rem[i]);
-                vec_hero_avg_dmg[i].push(team_dmg_dealt[i]);
-                vec_hero_avg_dodges[i].push((sim_rounds - (team_dodges[i] as f64)) / sim_rounds);
-                vec_hero_avg_atk_accuracy[i]
-                    .push((sim_rounds - (team_atks_missed[i] as f64)) / sim_rounds);
-                vec_hero_avg_crits_dealt[i]
-                    .push((sim_rounds - (team_crits_dealt[i] as f64)) / sim_rounds);
-                vec_hero_avg_crits_taken[i]
-                    .push((sim_rounds - (team_crits_taken[i] as f64)) / sim_rounds);
+                vec_hero_hp_remaining[i].push(team_hp_rem[i]);
+                vec_hero_dmg[i].push(team_dmg_dealt[i]);
+                hero_accuracy_stats[i].add(&team_accuracy_stats[i]);
             }
         }
 
-        let hero_names: Vec<String> = all_results[0].get_team().get_team_hero_names();
-        let hero_survival_rate: [f64; 5] = vec_hero_survival_rate
-            .iter()
-            .map(|sr| sr.iter().sum::<f64>() / sr.len() as f64)
-            .collect::<Vec<f64>>()
-            .try_into()
-            .unwrap();
-        let hero_avg_hp_remaining: [f64; 5] = vec_hero_avg_hp_remaining
+        let (success_rate_ci_lower, success_rate_ci_upper) = self.get_success_rate_interval();
+        let vec_successes: Vec<f64> = all_results
             .iter()
-            .map(|hp| hp.iter().sum::<f64>() / hp.len() as f64)
-            .collect::<Vec<f64>>()
-            .try_into()
-            .unwrap();
-        let hero_avg_dmg: [f64; 5] = vec_hero_avg_dmg
+            .map(|res| res.is_success() as u32 as f64)
+            .collect();
+        let vec_rounds: Vec<f64> = all_results
             .iter()
-            .map(|dmg| dmg.iter().sum::<f64>() / dmg.len() as f64)
-            .collect::<Vec<f64>>()
-            .try_into()
-            .unwrap();
-        let hero_avg_dodge_rate: [f64; 5] = vec_hero_avg_dodges
+            .map(|res| res.get_rounds() as f64)
+            .collect();
+
+        let mean = |values: &Vec<f64>| -> f64 {
+            return values.iter().sum::<f64>() / values.len() as f64;
+        };
+        let mut hero_survival_rate_ci_lower: Vec<f64> = vec![];
+        let mut hero_survival_rate_ci_upper: Vec<f64> = vec![];
+        for survival in vec_hero_survival_rate.iter() {
+            let survivals = survival.iter().filter(|survived| **survived > 0.0).count();
+            let (lower, upper) = wilson_interval(survivals, survival.len(), Z_95);
+            hero_survival_rate_ci_lower.push(lower);
+            hero_survival_rate_ci_upper.push(upper);
+        }
+
+        // The barrier types in the order they were first encountered, with the success rate against each
+        let mut barrier_types: Vec<ElementType> = vec![];
+        for res in all_results.iter() {
+            match res.get_barrier_type() {
+                Some(barrier_type) => {
+                    if !barrier_types.contains(&barrier_type) {
+                        barrier_types.push(barrier_type);
+                    }
+                }
+                None => (),
+            }
+        }
+        let success_rate_vs_barrier: Vec<f64> = barrier_types
             .iter()
-            .map(|dg| dg.iter().sum::<f64>() / dg.len() as f64)
-            .collect::<Vec<f64>>()
-            .try_into()
-            .unwrap();
-        let hero_avg_atk_hit_rate: [f64; 5] = vec_hero_avg_atk_accuracy
+            .map(|barrier_type| {
+                let successes: Vec<f64> = all_results
+                    .iter()
+                    .filter(|res| res.get_barrier_type() == Some(*barrier_type))
+                    .map(|res| res.is_success() as u32 as f64)
+                    .collect();
+                return mean(&successes);
+            })
+            .collect();
+        // The round each barrier type was broken in on average, over the encounters where it was broken
+        let avg_barrier_broken_round_vs_barrier: Vec<f64> = barrier_types
             .iter()
-            .map(|acc| acc.iter().sum::<f64>() / acc.len() as f64)
-            .collect::<Vec<f64>>()
-            .try_into()
-            .unwrap();
-        let hero_avg_crit_dealt_rate: [f64; 5] = vec_hero_avg_crits_dealt
+            .map(|barrier_type| {
+                let broken_rounds: Vec<f64> = all_results
+                    .iter()
+                    .filter(|res| res.get_barrier_type() == Some(*barrier_type))
+                    .filter_map(|res| res.get_barrier_broken_round())
+                    .map(|round| round as f64)
+                    .collect();
+                if broken_rounds.len() == 0 {
+                    return 0.0;
+                }
+                return mean(&broken_rounds);
+            })
+            .collect();
+        // The share of the broken barriers each hero landed the breaking hit on
+        let barrier_breakers: Vec<String> = all_results
             .iter()
-            .map(|cd| cd.iter().sum::<f64>() / cd.len() as f64)
-            .collect::<Vec<f64>>()
-            .try_into()
-            .unwrap();
-        let hero_avg_crit_taken_rate: [f64; 5] = vec_hero_avg_crits_taken
+            .filter_map(|res| res.get_barrier_broken_by())
+            .collect();
+        let hero_barrier_break_share: Vec<f64> = hero_names
             .iter()
-            .map(|ct| ct.iter().sum::<f64>() / ct.len() as f64)
-            .collect::<Vec<f64>>()
-            .try_into()
-            .unwrap();
+            .map(|hero_name| {
+                if barrier_breakers.len() == 0 {
+                    return 0.0;
+                }
+                return barrier_breakers
+                    .iter()
+                    .filter(|breaker| *breaker == hero_name)
+                    .count() as f64
+                    / barrier_breakers.len() as f64;
+            })
+            .collect();
 
         let trial_result = TrialResult {
             trial_identifier: self.identifier.to_string(),
             trial_description: self.description.to_string(),
+            equipment_substitutions: self.equipment_substitutions.clone(),
             trial_simulation_qty: self.simulation_qty,
+            simulations_run: all_results.len(),
+            seed: self.seed,
             dungeon_identifier: self.dungeon._get_zone(),
             difficulty_settings: self.difficulty_settings.clone(),
             force_minibosses: self.force_minibosses,
+            barrier_setting: self.barrier_setting,
             trial_num_minibosses: miniboss_results.len(),
             success_rate: (all_results
                 .iter()
                 .map(|res| res.is_success() as u32 as f64)
                 .sum::<f64>()
                 / all_results_length as f64),
+            success_rate_ci_lower,
+            success_rate_ci_upper,
+            successes: vec_successes
+                .iter()
+                .filter(|success| **success > 0.0)
+                .count(),
+            success_rate_std_dev: std_dev(&vec_successes),
             success_rate_vs_miniboss: (miniboss_results
                 .iter()
                 .map(|res| res.is_success() as u32 as f64)
                 .sum::<f64>()
                 / miniboss_results_length as f64),
+            barrier_types,
+            success_rate_vs_barrier,
+            avg_barrier_broken_round_vs_barrier,
             average_rounds: (all_results
                 .iter()
                 .map(|res| res.get_rounds() as u32 as f64)
                 .sum::<f64>()
                 / all_results_length as f64),
+            rounds_total: all_results.iter().map(|res| res.get_rounds() as u64).sum(),
+            rounds_std_dev: std_dev(&vec_rounds),
+            rounds_median: percentile(&vec_rounds, 50.0),
+            rounds_p10: percentile(&vec_rounds, 10.0),
+            rounds_p90: percentile(&vec_rounds, 90.0),
             avg_rounds_vs_miniboss: (miniboss_results
                 .iter()
                 .map(|res| res.get_rounds() as u32 as f64)
@@ -347,14 +550,54 @@ impl Trial {
                 / miniboss_results_length as f64),
 
             hero_names,
-            hero_survival_rate,
-            hero_avg_hp_remaining,
-            hero_avg_dmg,
-
-            hero_avg_dodge_rate,
-            hero_avg_atk_hit_rate,
-            hero_avg_crit_dealt_rate,
-            hero_avg_crit_taken_rate,
+            hero_survival_rate: vec_hero_survival_rate.iter().map(mean).collect(),
+            hero_survivals: vec_hero_survival_rate
+                .iter()
+                .map(|survival| survival.iter().filter(|survived| **survived > 0.0).count())
+                .collect(),
+            hero_survival_rate_ci_lower,
+            hero_survival_rate_ci_upper,
+            hero_avg_hp_remaining: vec_hero_hp_remaining.iter().map(mean).collect(),
+            hero_hp_remaining_total: vec_hero_hp_remaining
+                .iter()
+                .map(|hp_remaining| hp_remaining.iter().sum::<f64>())
+                .collect(),
+            hero_avg_dmg: vec_hero_dmg.iter().map(mean).collect(),
+            hero_dmg_total: vec_hero_dmg
+                .iter()
+                .map(|dmg| dmg.iter().sum::<f64>())
+                .collect(),
+            hero_dmg_std_dev: vec_hero_dmg.iter().map(std_dev).collect(),
+            hero_dmg_median: vec_hero_dmg
+                .iter()
+                .map(|dmg| percentile(dmg, 50.0))
+                .collect(),
+            hero_dmg_p10: vec_hero_dmg
+                .iter()
+                .map(|dmg| percentile(dmg, 10.0))
+                .collect(),
+            hero_dmg_p90: vec_hero_dmg
+                .iter()
+                .map(|dmg| percentile(dmg, 90.0))
+                .collect(),
+            hero_barrier_break_share,
+
+            hero_dodge_rate: hero_accuracy_stats
+                .iter()
+                .map(|stats| stats.get_dodge_rate())
+                .collect(),
+            hero_atk_hit_rate: hero_accuracy_stats
+                .iter()
+                .map(|stats| stats.get_atk_hit_rate())
+                .collect(),
+            hero_crit_dealt_rate: hero_accuracy_stats
+                .iter()
+                .map(|stats| stats.get_crit_dealt_rate())
+                .collect(),
+            hero_crit_taken_rate: hero_accuracy_stats
+                .iter()
+                .map(|stats| stats.get_crit_taken_rate())
+                .collect(),
         };
 
         return trial_result;
@@ -371,22 +614,35 @@ impl Trial {
             .open(path)
             .unwrap();
 
-        let mut wtr: csv::Writer<std::fs::File>;
-        if path_exists {
-            wtr = csv::WriterBuilder::new()
-                .has_headers(false)
-                .from_writer(file);
-        } else {
-            wtr = csv::WriterBuilder::new()
-                .has_headers(true)
-                .from_writer(file);
-        }
+        // The hero columns are sized to the team, so the headers are written from the serde field names
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(file);
 
         let trial_result = self.create_trial_result();
 
-        let record = create_trial_result_csv_record_from_trial_result(trial_result);
+        let hero_records: Vec<HeroTrialResultCSVRecord> =
+            create_hero_trial_result_csv_records_from_trial_result(&trial_result)
+                .iter()
+                .map(|hero_record| hero_record.round_floats_for_display())
+                .collect();
+        let record = create_trial_result_csv_record_from_trial_result(trial_result)
+            .round_floats_for_display();
+
+        if !path_exists {
+            let mut headers = get_csv_headers(&record)?;
+            if let Some(hero_record) = hero_records.first() {
+                let hero_headers = get_csv_headers(hero_record)?;
+                for n in 1..=hero_records.len() {
+                    for hero_header in hero_headers.iter() {
+                        headers.push(f!("hero_{n}_{hero_header}"));
+                    }
+                }
+            }
+            wtr.write_record(&headers)?;
+        }
 
-        wtr.serialize(record.round_floats_for_display())?;
+        wtr.serialize((record, hero_records))?;
 
         wtr.flush()?;
         return Ok(());
@@ -447,6 +703,7 @@ pub fn create_trial(
     difficulty_settings: Vec<usize>,
     force_minibosses: Option<bool>,
     log_all: bool,
+    seed: u64,
 ) -> Result<Trial, &'static str> {
     if simulation_qty < 1 {
         return Err("simulation_qty must be > 0");
@@ -460,101 +717,194 @@ pub fn create_trial(
         dungeon,
         difficulty_settings,
         force_minibosses,
+        barrier_setting: BarrierSetting::Random,
         results: Vec::with_capacity(simulation_qty),
         log_all,
+        combat_log_directory: None,
+        combat_log_cap: 0,
+        seed,
+        adaptive_sampling: None,
+        equipment_substitutions: vec![],
     };
 
     return Ok(trial);
 }
 
+/// Derive the seed for a single simulation from the trial seed and the simulation's index (SplitMix64 finalizer)
+/// Seeding per simulation keeps every simulation reproducible on its own, regardless of run order
+pub fn derive_simulation_seed(trial_seed: u64, simulation_index: u64) -> u64 {
+    let mut z = trial_seed.wrapping_add(
+        simulation_index
+            .wrapping_add(1)
+            .wrapping_mul(0x9E3779B97F4A7C15),
+    );
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    return z ^ (z >> 31);
+}
+
+/// Returns the display name of a difficulty setting
+/// 1 - Easy, 2 - Medium, 3 - Hard, 4 - Extreme,
+/// 5 - Boss Easy, 6 - Boss Medium, 7 - Boss Hard, 8 - Boss Extreme
+pub fn get_difficulty_name(difficulty: usize) -> &'static str {
+    let diff_map: HashMap<usize, &str> = HashMap::from([
+        (1 as usize, "Easy"),
+        (2 as usize, "Medium"),
+        (3 as usize, "Hard"),
+        (4 as usize, "Extreme"),
+        (5 as usize, "Boss Easy"),
+        (6 as usize, "Boss Medium"),
+        (7 as usize, "Boss Hard"),
+        (8 as usize, "Boss Extreme"),
+    ]);
+    return diff_map[&difficulty];
+}
+
 /// The result of a trial
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TrialResult {
     trial_identifier: String,
     trial_description: String,
+    equipment_substitutions: Vec<String>,
     trial_simulation_qty: usize,
+    simulations_run: usize,
+    seed: u64,
     dungeon_identifier: String,
     difficulty_settings: Vec<usize>,
     force_minibosses: Option<bool>,
+    barrier_setting: BarrierSetting,
     trial_num_minibosses: usize,
     success_rate: f64,
+    success_rate_ci_lower: f64, // The bounds of the 95% Wilson confidence interval on success_rate
+    success_rate_ci_upper: f64,
+    successes: usize,
+    success_rate_std_dev: f64,
     success_rate_vs_miniboss: f64,
+    barrier_types: Vec<ElementType>, // The barrier types the encounters had, in the order first encountered
+    success_rate_vs_barrier: Vec<f64>, // The success rate against each of barrier_types
+    avg_barrier_broken_round_vs_barrier: Vec<f64>, // The average round each of barrier_types was broken in, over the encounters where it was broken, 0 if it never was
     average_rounds: f64,
+    rounds_total: u64, // The sum of rounds over the simulations, saved so average_rounds can be restored exactly
+    rounds_std_dev: f64,
+    rounds_median: f64,
+    rounds_p10: f64, // The 10th percentile of rounds, so 10% of simulations ended in this many rounds or fewer
+    rounds_p90: f64,
     avg_rounds_vs_miniboss: f64,
     avg_encounter_hp_remaining: f64,
     avg_encounter_hp_remaining_vs_miniboss: f64,
 
+    // Each of the per hero fields has one value per hero on the team, in team order
     hero_names: Vec<String>,
-    hero_survival_rate: [f64; 5],
-    hero_avg_hp_remaining: [f64; 5],
-    hero_avg_dmg: [f64; 5],
-
-    hero_avg_dodge_rate: [f64; 5],
-    hero_avg_atk_hit_rate: [f64; 5],
-    hero_avg_crit_dealt_rate: [f64; 5],
-    hero_avg_crit_taken_rate: [f64; 5],
+    hero_survival_rate: Vec<f64>,
+    hero_survivals: Vec<usize>, // The number of simulations the hero survived, saved so hero_survival_rate can be restored exactly
+    hero_survival_rate_ci_lower: Vec<f64>, // The bounds of the 95% Wilson confidence interval on hero_survival_rate
+    hero_survival_rate_ci_upper: Vec<f64>,
+    hero_avg_hp_remaining: Vec<f64>,
+    hero_hp_remaining_total: Vec<f64>, // The sum of hp remaining over the simulations, saved so hero_avg_hp_remaining can be restored exactly
+    hero_avg_dmg: Vec<f64>,
+    hero_dmg_total: Vec<f64>, // The sum of damage over the simulations, saved so hero_avg_dmg can be restored exactly
+    hero_dmg_std_dev: Vec<f64>,
+    hero_dmg_median: Vec<f64>,
+    hero_dmg_p10: Vec<f64>,
+    hero_dmg_p90: Vec<f64>,
+    hero_barrier_break_share: Vec<f64>, // The share of the broken barriers the hero landed the breaking hit on
+
+    hero_dodge_rate: Vec<f64>,      // Dodges per attack received
+    hero_atk_hit_rate: Vec<f64>,    // Hits per attack made
+    hero_crit_dealt_rate: Vec<f64>, // Crits per hit landed
+    hero_crit_taken_rate: Vec<f64>, // Crits per hit taken
+}
+
+impl TrialResult {
+    pub fn get_success_rate(&self) -> f64 {
+        return self.success_rate;
+    }
+
+    pub fn get_success_rate_interval(&self) -> (f64, f64) {
+        return (self.success_rate_ci_lower, self.success_rate_ci_upper);
+    }
+
+    /// Tests whether this result's success rate differs from other's beyond what chance would explain
+    /// Returns (z-score, two-sided p-value), where the z-score is positive when this result's success rate is higher
+    /// The test treats the trials as independent, but trials with the same seed share the seed of each simulation index,
+    /// so their outcomes are positively correlated and the p-value is conservative (it overstates the chance of no difference)
+    pub fn compare_success_rate(&self, other: &TrialResult) -> (f64, f64) {
+        return two_proportion_z_test(
+            self.successes,
+            self.simulations_run,
+            other.successes,
+            other.simulations_run,
+        );
+    }
+
+    pub fn get_trial_description(&self) -> String {
+        return self.trial_description.to_string();
+    }
+
+    pub fn get_dungeon_identifier(&self) -> String {
+        return self.dungeon_identifier.to_string();
+    }
+
+    pub fn get_difficulty_settings(&self) -> Vec<usize> {
+        return self.difficulty_settings.clone();
+    }
+
+    pub fn get_equipment_substitutions(&self) -> Vec<String> {
+        return self.equipment_substitutions.clone();
+    }
+
+    pub fn get_average_rounds(&self) -> f64 {
+        return self.average_rounds;
+    }
+
+    /// Returns the number of heroes on the team the trial was run with
+    pub fn get_hero_qty(&self) -> usize {
+        return self.hero_names.len();
+    }
+
+    pub fn get_hero_survival_rate(&self, hero_index: usize) -> f64 {
+        return self.hero_survival_rate[hero_index];
+    }
+
+    pub fn get_hero_avg_hp_remaining(&self, hero_index: usize) -> f64 {
+        return self.hero_avg_hp_remaining[hero_index];
+    }
+
+    pub fn get_hero_avg_dmg(&self, hero_index: usize) -> f64 {
+        return self.hero_avg_dmg[hero_index];
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct TrialResultCSVRecord {
     trial_identifier: String,
     trial_description: String,
+    equipment_substitutions: String, // Semi-colon separated list of the alternate equipment substituted
     trial_simulation_qty: usize,
+    simulations_run: usize,
+    successes: usize,
+    seed: u64,
     dungeon_identifier: String,
     difficulty_settings: String,
     force_minibosses: String,
+    barrier_setting: String,
     trial_num_minibosses: usize,
     success_rate: String,
+    success_rate_ci_lower: String,
+    success_rate_ci_upper: String,
+    success_rate_std_dev: String,
     success_rate_vs_miniboss: String,
+    success_rate_vs_barrier: String, // Semi-colon separated list of "<Element> <success rate>" for each barrier type encountered
+    avg_barrier_broken_round_vs_barrier: String, // Semi-colon separated list of "<Element> <average round broken>" for each barrier type encountered
     average_rounds: f64,
+    rounds_total: u64,
+    rounds_std_dev: f64,
+    rounds_median: f64,
+    rounds_p10: f64,
+    rounds_p90: f64,
     avg_rounds_vs_miniboss: f64,
     avg_encounter_hp_remaining: f64,
     avg_encounter_hp_remaining_vs_miniboss: f64,
-
-    hero_1_identifier: String,
-    hero_1_survival_rate: String,
-    hero_1_avg_hp_remaining: f64,
-    hero_1_avg_dmg: f64,
-    hero_1_avg_dodge_rate: f64,
-    hero_1_avg_atk_hit_rate: f64,
-    hero_1_avg_crit_dealt_rate: f64,
-    hero_1_avg_crit_taken_rate: f64,
-
-    hero_2_identifier: String,
-    hero_2_survival_rate: String,
-    hero_2_avg_hp_remaining: f64,
-    hero_2_avg_dmg: f64,
-    hero_2_avg_dodge_rate: f64,
-    hero_2_avg_atk_hit_rate: f64,
-    hero_2_avg_crit_dealt_rate: f64,
-    hero_2_avg_crit_taken_rate: f64,
-
-    hero_3_identifier: String,
-    hero_3_survival_rate: String,
-    hero_3_avg_hp_remaining: f64,
-    hero_3_avg_dmg: f64,
-    hero_3_avg_dodge_rate: f64,
-    hero_3_avg_atk_hit_rate: f64,
-    hero_3_avg_crit_dealt_rate: f64,
-    hero_3_avg_crit_taken_rate: f64,
-
-    hero_4_identifier: String,
-    hero_4_survival_rate: String,
-    hero_4_avg_hp_remaining: f64,
-    hero_4_avg_dmg: f64,
-    hero_4_avg_dodge_rate: f64,
-    hero_4_avg_atk_hit_rate: f64,
-    hero_4_avg_crit_dealt_rate: f64,
-    hero_4_avg_crit_taken_rate: f64,
-
-    hero_5_identifier: String,
-    hero_5_survival_rate: String,
-    hero_5_avg_hp_remaining: f64,
-    hero_5_avg_dmg: f64,
-    hero_5_avg_dodge_rate: f64,
-    hero_5_avg_atk_hit_rate: f64,
-    hero_5_avg_crit_dealt_rate: f64,
-    hero_5_avg_crit_taken_rate: f64,
 }
 
 impl TrialResultCSVRecord {
@@ -562,67 +912,26 @@ impl TrialResultCSVRecord {
         let mut tcr2 = self.clone();
 
         tcr2.average_rounds = round_to_4(tcr2.average_rounds);
+        tcr2.rounds_std_dev = round_to_4(tcr2.rounds_std_dev);
+        tcr2.rounds_median = round_to_4(tcr2.rounds_median);
+        tcr2.rounds_p10 = round_to_4(tcr2.rounds_p10);
+        tcr2.rounds_p90 = round_to_4(tcr2.rounds_p90);
         tcr2.avg_rounds_vs_miniboss = round_to_4(tcr2.avg_rounds_vs_miniboss);
         tcr2.avg_encounter_hp_remaining = round_to_2(tcr2.avg_encounter_hp_remaining);
         tcr2.avg_encounter_hp_remaining_vs_miniboss =
             round_to_2(tcr2.avg_encounter_hp_remaining_vs_miniboss);
 
-        tcr2.hero_1_avg_hp_remaining = round_to_2(tcr2.hero_1_avg_hp_remaining);
-        tcr2.hero_1_avg_dmg = round_to_2(tcr2.hero_1_avg_dmg);
-        tcr2.hero_1_avg_dodge_rate = round_to_2(tcr2.hero_1_avg_dodge_rate);
-        tcr2.hero_1_avg_atk_hit_rate = round_to_2(tcr2.hero_1_avg_atk_hit_rate);
-        tcr2.hero_1_avg_crit_dealt_rate = round_to_2(tcr2.hero_1_avg_crit_dealt_rate);
-        tcr2.hero_1_avg_crit_taken_rate = round_to_2(tcr2.hero_1_avg_crit_taken_rate);
-
-        tcr2.hero_2_avg_hp_remaining = round_to_2(tcr2.hero_2_avg_hp_remaining);
-        tcr2.hero_2_avg_dmg = round_to_2(tcr2.hero_2_avg_dmg);
-        tcr2.hero_2_avg_dodge_rate = round_to_2(tcr2.hero_2_avg_dodge_rate);
-        tcr2.hero_2_avg_atk_hit_rate = round_to_2(tcr2.hero_2_avg_atk_hit_rate);
-        tcr2.hero_2_avg_crit_dealt_rate = round_to_2(tcr2.hero_2_avg_crit_dealt_rate);
-        tcr2.hero_2_avg_crit_taken_rate = round_to_2(tcr2.hero_2_avg_crit_taken_rate);
-
-        tcr2.hero_3_avg_hp_remaining = round_to_2(tcr2.hero_3_avg_hp_remaining);
-        tcr2.hero_3_avg_dmg = round_to_2(tcr2.hero_3_avg_dmg);
-        tcr2.hero_3_avg_dodge_rate = round_to_2(tcr2.hero_3_avg_dodge_rate);
-        tcr2.hero_3_avg_atk_hit_rate = round_to_2(tcr2.hero_3_avg_atk_hit_rate);
-        tcr2.hero_3_avg_crit_dealt_rate = round_to_2(tcr2.hero_3_avg_crit_dealt_rate);
-        tcr2.hero_3_avg_crit_taken_rate = round_to_2(tcr2.hero_3_avg_crit_taken_rate);
-
-        tcr2.hero_4_avg_hp_remaining = round_to_2(tcr2.hero_4_avg_hp_remaining);
-        tcr2.hero_4_avg_dmg = round_to_2(tcr2.hero_4_avg_dmg);
-        tcr2.hero_4_avg_dodge_rate = round_to_2(tcr2.hero_4_avg_dodge_rate);
-        tcr2.hero_4_avg_atk_hit_rate = round_to_2(tcr2.hero_4_avg_atk_hit_rate);
-        tcr2.hero_4_avg_crit_dealt_rate = round_to_2(tcr2.hero_4_avg_crit_dealt_rate);
-        tcr2.hero_4_avg_crit_taken_rate = round_to_2(tcr2.hero_4_avg_crit_taken_rate);
-
-        tcr2.hero_5_avg_hp_remaining = round_to_2(tcr2.hero_5_avg_hp_remaining);
-        tcr2.hero_5_avg_dmg = round_to_2(tcr2.hero_5_avg_dmg);
-        tcr2.hero_5_avg_dodge_rate = round_to_2(tcr2.hero_5_avg_dodge_rate);
-        tcr2.hero_5_avg_atk_hit_rate = round_to_2(tcr2.hero_5_avg_atk_hit_rate);
-        tcr2.hero_5_avg_crit_dealt_rate = round_to_2(tcr2.hero_5_avg_crit_dealt_rate);
-        tcr2.hero_5_avg_crit_taken_rate = round_to_2(tcr2.hero_5_avg_crit_taken_rate);
-
         return tcr2;
     }
 }
 
 /// Create a trial csv record performing type validation and calculating certain fields
 fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> TrialResultCSVRecord {
-    let mut new_diff_settings: Vec<&str> = Default::default();
-    let diff_map: std::collections::HashMap<usize, &str> = std::collections::HashMap::from([
-        (1 as usize, "Easy"),
-        (2 as usize, "Medium"),
-        (3 as usize, "Hard"),
-        (4 as usize, "Extreme"),
-        (5 as usize, "Boss Easy"),
-        (6 as usize, "Boss Medium"),
-        (7 as usize, "Boss Hard"),
-        (8 as usize, "Boss Extreme"),
-    ]);
-
-    for diff in result.difficulty_settings {
-        new_diff_settings.push(diff_map[&diff]);
-    }
+    let new_diff_settings: Vec<&str> = result
+        .difficulty_settings
+        .iter()
+        .map(|diff| get_difficulty_name(*diff))
+        .collect();
 
     let new_force_miniboss: String;
 
@@ -637,90 +946,386 @@ fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> Tria
         None => new_force_miniboss = String::from("Minibosses Allowed with Random Chance"),
     }
 
+    let new_barrier_setting: String;
+
+    match result.barrier_setting {
+        BarrierSetting::Random => new_barrier_setting = String::from("Random Barrier"),
+        BarrierSetting::Pinned(barrier_type) => {
+            new_barrier_setting = f!("{} Barrier", barrier_type)
+        }
+        BarrierSetting::Sweep => new_barrier_setting = String::from("All Barriers"),
+    }
+
     let t_csv_rec = TrialResultCSVRecord {
         trial_identifier: result.trial_identifier,
         trial_description: result.trial_description,
+        equipment_substitutions: result.equipment_substitutions.join(";"),
         trial_simulation_qty: result.trial_simulation_qty,
+        simulations_run: result.simulations_run,
+        successes: result.successes,
+        seed: result.seed,
         dungeon_identifier: result.dungeon_identifier,
         difficulty_settings: format!("{:?}", new_diff_settings),
         force_minibosses: new_force_miniboss,
+        barrier_setting: new_barrier_setting,
         trial_num_minibosses: result.trial_num_minibosses,
         success_rate: f!("{:.4}", round_to_4(result.success_rate)),
+        success_rate_ci_lower: f!("{:.4}", round_to_4(result.success_rate_ci_lower)),
+        success_rate_ci_upper: f!("{:.4}", round_to_4(result.success_rate_ci_upper)),
+        success_rate_std_dev: f!("{:.4}", round_to_4(result.success_rate_std_dev)),
         success_rate_vs_miniboss: f!("{:.4}", round_to_4(result.success_rate_vs_miniboss)),
+        success_rate_vs_barrier: result
+            .barrier_types
+            .iter()
+            .zip(result.success_rate_vs_barrier.iter())
+            .map(|(barrier_type, success_rate)| {
+                f!("{} {:.4}", barrier_type, round_to_4(*success_rate))
+            })
+            .collect::<Vec<String>>()
+            .join(";"),
+        avg_barrier_broken_round_vs_barrier: result
+            .barrier_types
+            .iter()
+            .zip(result.avg_barrier_broken_round_vs_barrier.iter())
+            .map(|(barrier_type, broken_round)| {
+                f!("{} {:.4}", barrier_type, round_to_4(*broken_round))
+            })
+            .collect::<Vec<String>>()
+            .join(";"),
         average_rounds: result.average_rounds,
+        rounds_total: result.rounds_total,
+        rounds_std_dev: result.rounds_std_dev,
+        rounds_median: result.rounds_median,
+        rounds_p10: result.rounds_p10,
+        rounds_p90: result.rounds_p90,
         avg_rounds_vs_miniboss: result.avg_rounds_vs_miniboss,
         avg_encounter_hp_remaining: result.avg_encounter_hp_remaining,
         avg_encounter_hp_remaining_vs_miniboss: result.avg_encounter_hp_remaining_vs_miniboss,
-
-        hero_1_identifier: result
-            .hero_names
-            .get(0)
-            .unwrap_or(&String::from(""))
-            .to_string(),
-        hero_1_survival_rate: f!("{:.4}", round_to_4(result.hero_survival_rate[0])),
-        hero_1_avg_hp_remaining: result.hero_avg_hp_remaining[0],
-        hero_1_avg_dmg: result.hero_avg_dmg[0],
-        hero_1_avg_dodge_rate: result.hero_avg_dodge_rate[0],
-        hero_1_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[0],
-        hero_1_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[0],
-        hero_1_avg_crit_taken_rate: result.hero_avg_crit_taken_rate[0],
-
-        hero_2_identifier: result
-            .hero_names
-            .get(1)
-            .unwrap_or(&String::from(""))
-            .to_string(),
-        hero_2_survival_rate: f!("{:.4}", round_to_4(result.hero_survival_rate[1])),
-        hero_2_avg_hp_remaining: result.hero_avg_hp_remaining[1],
-        hero_2_avg_dmg: result.hero_avg_dmg[1],
-        hero_2_avg_dodge_rate: result.hero_avg_dodge_rate[1],
-        hero_2_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[1],
-        hero_2_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[1],
-        hero_2_avg_crit_taken_rate: result.hero_avg_crit_taken_rate[1],
-
-        hero_3_identifier: result
-            .hero_names
-            .get(2)
-            .unwrap_or(&String::from(""))
-            .to_string(),
-        hero_3_survival_rate: f!("{:.4}", round_to_4(result.hero_survival_rate[2])),
-        hero_3_avg_hp_remaining: result.hero_avg_hp_remaining[2],
-        hero_3_avg_dmg: result.hero_avg_dmg[2],
-        hero_3_avg_dodge_rate: result.hero_avg_dodge_rate[2],
-        hero_3_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[2],
-        hero_3_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[2],
-        hero_3_avg_crit_taken_rate: result.hero_avg_crit_taken_rate[2],
-
-        hero_4_identifier: result
-            .hero_names
-            .get(3)
-            .unwrap_or(&String::from(""))
-            .to_string(),
-        hero_4_survival_rate: f!("{:.4}", round_to_4(result.hero_survival_rate[3])),
-        hero_4_avg_hp_remaining: result.hero_avg_hp_remaining[3],
-        hero_4_avg_dmg: result.hero_avg_dmg[3],
-        hero_4_avg_dodge_rate: result.hero_avg_dodge_rate[3],
-        hero_4_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[3],
-        hero_4_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[3],
-        hero_4_avg_crit_taken_rate: result.hero_avg_crit_taken_rate[3],
-
-        hero_5_identifier: result
-            .hero_names
-            .get(4)
-            .unwrap_or(&String::from(""))
-            .to_string(),
-        hero_5_survival_rate: f!("{:.4}", round_to_4(result.hero_survival_rate[4])),
-        hero_5_avg_hp_remaining: result.hero_avg_hp_remaining[4],
-        hero_5_avg_dmg: result.hero_avg_dmg[4],
-        hero_5_avg_dodge_rate: result.hero_avg_dodge_rate[4],
-        hero_5_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[4],
-        hero_5_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[4],
-        hero_5_avg_crit_taken_rate: result.hero_avg_crit_taken_rate[4],
     };
 
     return t_csv_rec;
 }
 
+/// The columns for one hero of a trial result, repeated once per hero on the team as hero_<n>_<column>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct HeroTrialResultCSVRecord {
+    identifier: String,
+    survival_rate: f64,
+    survivals: usize,
+    survival_rate_ci_lower: f64,
+    survival_rate_ci_upper: f64,
+    avg_hp_remaining: f64,
+    hp_remaining_total: f64, // Not rounded for display, so the average can be restored exactly
+    avg_dmg: f64,
+    dmg_total: f64, // Not rounded for display, so the average can be restored exactly
+    dmg_std_dev: f64,
+    dmg_median: f64,
+    dmg_p10: f64,
+    dmg_p90: f64,
+    barrier_break_share: f64,
+    dodge_rate: f64,
+    atk_hit_rate: f64,
+    crit_dealt_rate: f64,
+    crit_taken_rate: f64,
+}
+
+impl HeroTrialResultCSVRecord {
+    pub fn round_floats_for_display(&self) -> HeroTrialResultCSVRecord {
+        let mut hcr2 = self.clone();
+
+        hcr2.survival_rate = round_to_4(hcr2.survival_rate);
+        hcr2.survival_rate_ci_lower = round_to_4(hcr2.survival_rate_ci_lower);
+        hcr2.survival_rate_ci_upper = round_to_4(hcr2.survival_rate_ci_upper);
+        hcr2.avg_hp_remaining = round_to_2(hcr2.avg_hp_remaining);
+        hcr2.avg_dmg = round_to_2(hcr2.avg_dmg);
+        hcr2.dmg_std_dev = round_to_2(hcr2.dmg_std_dev);
+        hcr2.dmg_median = round_to_2(hcr2.dmg_median);
+        hcr2.dmg_p10 = round_to_2(hcr2.dmg_p10);
+        hcr2.dmg_p90 = round_to_2(hcr2.dmg_p90);
+        hcr2.barrier_break_share = round_to_4(hcr2.barrier_break_share);
+        hcr2.dodge_rate = round_to_4(hcr2.dodge_rate);
+        hcr2.atk_hit_rate = round_to_4(hcr2.atk_hit_rate);
+        hcr2.crit_dealt_rate = round_to_4(hcr2.crit_dealt_rate);
+        hcr2.crit_taken_rate = round_to_4(hcr2.crit_taken_rate);
+
+        return hcr2;
+    }
+}
+
+/// Create one csv record per hero on the trial result's team, in team order
+fn create_hero_trial_result_csv_records_from_trial_result(
+    result: &TrialResult,
+) -> Vec<HeroTrialResultCSVRecord> {
+    return (0..result.get_hero_qty())
+        .map(|i| HeroTrialResultCSVRecord {
+            identifier: result.hero_names[i].to_string(),
+            survival_rate: result.hero_survival_rate[i],
+            survivals: result.hero_survivals[i],
+            survival_rate_ci_lower: result.hero_survival_rate_ci_lower[i],
+            survival_rate_ci_upper: result.hero_survival_rate_ci_upper[i],
+            avg_hp_remaining: result.hero_avg_hp_remaining[i],
+            hp_remaining_total: result.hero_hp_remaining_total[i],
+            avg_dmg: result.hero_avg_dmg[i],
+            dmg_total: result.hero_dmg_total[i],
+            dmg_std_dev: result.hero_dmg_std_dev[i],
+            dmg_median: result.hero_dmg_median[i],
+            dmg_p10: result.hero_dmg_p10[i],
+            dmg_p90: result.hero_dmg_p90[i],
+            barrier_break_share: result.hero_barrier_break_share[i],
+            dodge_rate: result.hero_dodge_rate[i],
+            atk_hit_rate: result.hero_atk_hit_rate[i],
+            crit_dealt_rate: result.hero_crit_dealt_rate[i],
+            crit_taken_rate: result.hero_crit_taken_rate[i],
+        })
+        .collect();
+}
+
+/// Load every trial result saved to a trial results csv, in the order they were saved
+/// The values variations are ranked by are restored exactly from the saved counts and totals, the rest as rounded for display
+pub fn load_trial_results_from_csv(
+    string_path: &String,
+) -> Result<Vec<TrialResult>, std::io::Error> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(string_path)?;
+
+    // The hero columns follow the trial columns, repeated once per hero on the team
+    let headers = rdr.headers()?.clone();
+    let trial_column_qty = headers
+        .iter()
+        .take_while(|header| !header.starts_with("hero_"))
+        .count();
+    let hero_column_qty = headers
+        .iter()
+        .filter(|header| header.starts_with("hero_1_"))
+        .count();
+
+    let mut trial_results: Vec<TrialResult> = vec![];
+    for row in rdr.records() {
+        let row = row?;
+        let record: TrialResultCSVRecord =
+            csv::StringRecord::from_iter(row.iter().take(trial_column_qty)).deserialize(None)?;
+        let hero_fields: Vec<&str> = row.iter().skip(trial_column_qty).collect();
+        let mut hero_records: Vec<HeroTrialResultCSVRecord> = vec![];
+        if hero_column_qty > 0 {
+            for fields in hero_fields.chunks(hero_column_qty) {
+                hero_records.push(csv::StringRecord::from(fields.to_vec()).deserialize(None)?);
+            }
+        }
+        trial_results.push(create_trial_result_from_csv_records(record, hero_records)?);
+    }
+    return Ok(trial_results);
+}
+
+/// Create a trial result from its csv records, the reverse of the csv record creation functions
+fn create_trial_result_from_csv_records(
+    record: TrialResultCSVRecord,
+    hero_records: Vec<HeroTrialResultCSVRecord>,
+) -> Result<TrialResult, std::io::Error> {
+    let invalid = |field: &str, value: &str| {
+        return std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            f!("Invalid {} in trial results csv: {}", field, value),
+        );
+    };
+    let parse_rate = |field: &str, value: &String| {
+        return value
+            .parse::<f64>()
+            .map_err(|_| invalid(field, value.as_str()));
+    };
+
+    let mut difficulty_settings: Vec<usize> = vec![];
+    for name in record
+        .difficulty_settings
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(", ")
+        .map(|name| name.trim_matches('"'))
+        .filter(|name| name.len() > 0)
+    {
+        match (1..=8).find(|difficulty| get_difficulty_name(*difficulty) == name) {
+            Some(difficulty) => difficulty_settings.push(difficulty),
+            None => return Err(invalid("difficulty setting", name)),
+        }
+    }
+
+    let force_minibosses: Option<bool>;
+    match record.force_minibosses.as_str() {
+        "Force Only Minibosses" => force_minibosses = Some(true),
+        "No Minibosses Allowed" => force_minibosses = Some(false),
+        "Minibosses Allowed with Random Chance" => force_minibosses = None,
+        other => return Err(invalid("force minibosses setting", other)),
+    }
+
+    let barrier_setting: BarrierSetting;
+    match record.barrier_setting.as_str() {
+        "Random Barrier" => barrier_setting = BarrierSetting::Random,
+        "All Barriers" => barrier_setting = BarrierSetting::Sweep,
+        other => match other
+            .strip_suffix(" Barrier")
+            .and_then(|element| element.parse::<ElementType>().ok())
+        {
+            Some(barrier_type) => barrier_setting = BarrierSetting::Pinned(barrier_type),
+            None => return Err(invalid("barrier setting", other)),
+        },
+    }
+
+    let mut barrier_types: Vec<ElementType> = vec![];
+    let mut success_rate_vs_barrier: Vec<f64> = vec![];
+    for barrier_success_rate in record
+        .success_rate_vs_barrier
+        .split(";")
+        .filter(|entry| entry.len() > 0)
+    {
+        match barrier_success_rate.split_once(" ") {
+            Some((element, success_rate)) => {
+                barrier_types.push(
+                    element
+                        .parse::<ElementType>()
+                        .map_err(|_| invalid("barrier type", element))?,
+                );
+                success_rate_vs_barrier.push(parse_rate(
+                    "barrier success rate",
+                    &success_rate.to_string(),
+                )?);
+            }
+            None => return Err(invalid("barrier success rate", barrier_success_rate)),
+        }
+    }
+
+    let mut avg_barrier_broken_round_vs_barrier: Vec<f64> = vec![];
+    for barrier_broken_round in record
+        .avg_barrier_broken_round_vs_barrier
+        .split(";")
+        .filter(|entry| entry.len() > 0)
+    {
+        match barrier_broken_round.split_once(" ") {
+            Some((_, broken_round)) => avg_barrier_broken_round_vs_barrier.push(parse_rate(
+                "barrier broken round",
+                &broken_round.to_string(),
+            )?),
+            None => return Err(invalid("barrier broken round", barrier_broken_round)),
+        }
+    }
+
+    // Divided the same way as in create_trial_result, so the restored values are identical
+    let simulations_run = record.simulations_run.max(1) as f64;
+    let (success_rate_ci_lower, success_rate_ci_upper) =
+        wilson_interval(record.successes, record.simulations_run, Z_95);
+    let hero_survival_rate_intervals: Vec<(f64, f64)> = hero_records
+        .iter()
+        .map(|hero| wilson_interval(hero.survivals, record.simulations_run, Z_95))
+        .collect();
+
+    return Ok(TrialResult {
+        trial_identifier: record.trial_identifier,
+        trial_description: record.trial_description,
+        equipment_substitutions: record
+            .equipment_substitutions
+            .split(";")
+            .filter(|substitution| substitution.len() > 0)
+            .map(|substitution| substitution.to_string())
+            .collect(),
+        trial_simulation_qty: record.trial_simulation_qty,
+        simulations_run: record.simulations_run,
+        seed: record.seed,
+        dungeon_identifier: record.dungeon_identifier,
+        difficulty_settings,
+        force_minibosses,
+        barrier_setting,
+        trial_num_minibosses: record.trial_num_minibosses,
+        success_rate: record.successes as f64 / simulations_run,
+        success_rate_ci_lower,
+        success_rate_ci_upper,
+        successes: record.successes,
+        success_rate_std_dev: parse_rate("success rate std dev", &record.success_rate_std_dev)?,
+        success_rate_vs_miniboss: parse_rate(
+            "success rate vs miniboss",
+            &record.success_rate_vs_miniboss,
+        )?,
+        barrier_types,
+        success_rate_vs_barrier,
+        avg_barrier_broken_round_vs_barrier,
+        average_rounds: record.rounds_total as f64 / simulations_run,
+        rounds_total: record.rounds_total,
+        rounds_std_dev: record.rounds_std_dev,
+        rounds_median: record.rounds_median,
+        rounds_p10: record.rounds_p10,
+        rounds_p90: record.rounds_p90,
+        avg_rounds_vs_miniboss: record.avg_rounds_vs_miniboss,
+        avg_encounter_hp_remaining: record.avg_encounter_hp_remaining,
+        avg_encounter_hp_remaining_vs_miniboss: record.avg_encounter_hp_remaining_vs_miniboss,
+
+        hero_names: hero_records
+            .iter()
+            .map(|hero| hero.identifier.to_string())
+            .collect(),
+        hero_survival_rate: hero_records
+            .iter()
+            .map(|hero| hero.survivals as f64 / simulations_run)
+            .collect(),
+        hero_survivals: hero_records.iter().map(|hero| hero.survivals).collect(),
+        hero_survival_rate_ci_lower: hero_survival_rate_intervals
+            .iter()
+            .map(|(lower, _)| *lower)
+            .collect(),
+        hero_survival_rate_ci_upper: hero_survival_rate_intervals
+            .iter()
+            .map(|(_, upper)| *upper)
+            .collect(),
+        hero_avg_hp_remaining: hero_records
+            .iter()
+            .map(|hero| hero.hp_remaining_total / simulations_run)
+            .collect(),
+        hero_hp_remaining_total: hero_records
+            .iter()
+            .map(|hero| hero.hp_remaining_total)
+            .collect(),
+        hero_avg_dmg: hero_records
+            .iter()
+            .map(|hero| hero.dmg_total / simulations_run)
+            .collect(),
+        hero_dmg_total: hero_records.iter().map(|hero| hero.dmg_total).collect(),
+        hero_dmg_std_dev: hero_records.iter().map(|hero| hero.dmg_std_dev).collect(),
+        hero_dmg_median: hero_records.iter().map(|hero| hero.dmg_median).collect(),
+        hero_dmg_p10: hero_records.iter().map(|hero| hero.dmg_p10).collect(),
+        hero_dmg_p90: hero_records.iter().map(|hero| hero.dmg_p90).collect(),
+        hero_barrier_break_share: hero_records
+            .iter()
+            .map(|hero| hero.barrier_break_share)
+            .collect(),
+        hero_dodge_rate: hero_records.iter().map(|hero| hero.dodge_rate).collect(),
+        hero_atk_hit_rate: hero_records.iter().map(|hero| hero.atk_hit_rate).collect(),
+        hero_crit_dealt_rate: hero_records
+            .iter()
+            .map(|hero| hero.crit_dealt_rate)
+            .collect(),
+        hero_crit_taken_rate: hero_records
+            .iter()
+            .map(|hero| hero.crit_taken_rate)
+            .collect(),
+    });
+}
+
+/// Returns the column names serde gives a record, without writing it anywhere
+fn get_csv_headers<T: Serialize>(record: &T) -> Result<Vec<String>, std::io::Error> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(true)
+        .from_writer(vec![]);
+    wtr.serialize(record)?;
+    let data = wtr
+        .into_inner()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))?;
+
+    let mut rdr = csv::Reader::from_reader(data.as_slice());
+    let headers = rdr
+        .headers()?
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    return Ok(headers);
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct PeeteeDuoSkillzTrialResultCSVRecord {
     skill_1: String,     // 3-letter Code, not T1 Name
@@ -771,21 +1376,11 @@ fn create_peetee_duoskillz_trial_result_csv_record_from_trial_result(
     result: TrialResult,
     skill_abbreviation_map: HashMap<String, String>,
 ) -> PeeteeDuoSkillzTrialResultCSVRecord {
-    let mut new_diff_settings: Vec<&str> = Default::default();
-    let diff_map: std::collections::HashMap<usize, &str> = std::collections::HashMap::from([
-        (1 as usize, "Easy"),
-        (2 as usize, "Medium"),
-        (3 as usize, "Hard"),
-        (4 as usize, "Extreme"),
-        (5 as usize, "Boss Easy"),
-        (6 as usize, "Boss Medium"),
-        (7 as usize, "Boss Hard"),
-        (8 as usize, "Boss Extreme"),
-    ]);
-
-    for diff in result.difficulty_settings {
-        new_diff_settings.push(diff_map[&diff]);
-    }
+    let new_diff_settings: Vec<&str> = result
+        .difficulty_settings
+        .iter()
+        .map(|diff| get_difficulty_name(*diff))
+        .collect();
 
     let new_force_miniboss: String;
 
@@ -849,3 +1444,166 @@ fn create_peetee_duoskillz_trial_result_csv_record_from_trial_result(
 
     return t_csv_rec;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::champions::Champion;
+    use crate::heroes::{create_sim_hero, create_team, SimHero};
+    use crate::inputs::load_dungeons_from_yaml;
+
+    fn create_test_trial(seed: u64, adaptive_sampling: Option<AdaptiveSampling>) -> Trial {
+        // Strong enough to win most but not all Extreme Aurora Caves encounters, so the outcomes vary by simulation
+        let mut heroes: Vec<SimHero> = ["Soldier", "Mage"]
+            .iter()
+            .map(|class| {
+                return create_sim_hero(
+                    f!("{}_Test", class),
+                    class.to_string(),
+                    40,
+                    1,
+                    1,
+                    3000.0,
+                    0.0,
+                    2400.0,
+                    1600.0,
+                    100,
+                    0.05,
+                    2.0,
+                    0.05,
+                    0.0,
+                    0,
+                    String::from("Fire"),
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0.0,
+                    0.0,
+                )
+                .unwrap();
+            })
+            .collect();
+        let reader =
+            std::fs::File::open(f!("{}/bundle/champions.yaml", env!("CARGO_MANIFEST_DIR")))
+                .unwrap();
+        let champions: HashMap<String, Champion> = serde_yaml::from_reader(reader).unwrap();
+        heroes.push(
+            champions["Argon"]
+                .create_sim_hero(String::from("Argon_Test"), 40, 1)
+                .unwrap(),
+        );
+        let dungeons =
+            load_dungeons_from_yaml(&f!("{}/bundle/dungeons.yaml", env!("CARGO_MANIFEST_DIR")));
+        let mut trial = create_trial(
+            String::from("Test"),
+            String::from("Test"),
+            200,
+            create_team(heroes, None).unwrap(),
+            dungeons["Aurora Caves"].clone(),
+            vec![4],
+            None,
+            false,
+            seed,
+        )
+        .unwrap();
+        trial.set_adaptive_sampling(adaptive_sampling);
+        return trial;
+    }
+
+    #[test]
+    fn trial_results_csv_loads_back_to_the_same_rows() {
+        let mut trial = create_test_trial(42, None);
+        trial.set_barrier_setting(BarrierSetting::Sweep);
+        trial.run_simulations_parallel();
+
+        let directory = std::env::temp_dir().join(f!("trial_results_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let saved_path = directory.join("saved.csv").to_string_lossy().to_string();
+        let resaved_path = directory.join("resaved.csv").to_string_lossy().to_string();
+        let _ = std::fs::remove_file(&saved_path);
+        let _ = std::fs::remove_file(&resaved_path);
+        trial
+            .save_trial_result_to_csv(saved_path.to_string())
+            .unwrap();
+
+        let loaded = load_trial_results_from_csv(&saved_path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        // The values variations are ranked by are restored exactly
+        let result = trial.create_trial_result();
+        assert_eq!(loaded[0].successes, result.successes);
+        assert_eq!(loaded[0].success_rate, result.success_rate);
+        assert_eq!(
+            loaded[0].get_success_rate_interval(),
+            result.get_success_rate_interval()
+        );
+        assert_eq!(loaded[0].average_rounds, result.average_rounds);
+        assert_eq!(loaded[0].hero_survival_rate, result.hero_survival_rate);
+        assert_eq!(
+            loaded[0].hero_survival_rate_ci_lower,
+            result.hero_survival_rate_ci_lower
+        );
+        assert_eq!(
+            loaded[0].hero_avg_hp_remaining,
+            result.hero_avg_hp_remaining
+        );
+        assert_eq!(loaded[0].hero_avg_dmg, result.hero_avg_dmg);
+        assert!(loaded[0].barrier_types.len() > 1);
+
+        // Saving the loaded result again writes the same row, so no column is lost
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_path(&resaved_path)
+            .unwrap();
+        let hero_records: Vec<HeroTrialResultCSVRecord> =
+            create_hero_trial_result_csv_records_from_trial_result(&loaded[0])
+                .iter()
+                .map(|hero_record| hero_record.round_floats_for_display())
+                .collect();
+        let record = create_trial_result_csv_record_from_trial_result(loaded[0].clone())
+            .round_floats_for_display();
+        wtr.serialize((record, hero_records)).unwrap();
+        wtr.flush().unwrap();
+
+        let saved = std::fs::read_to_string(&saved_path).unwrap();
+        let resaved = std::fs::read_to_string(&resaved_path).unwrap();
+        assert_eq!(saved.lines().nth(1), resaved.lines().next());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn parallel_simulations_match_single_threaded() {
+        let mut single_threaded = create_test_trial(42, None);
+        single_threaded._run_simulations_single_threaded();
+        let mut parallel = create_test_trial(42, None);
+        parallel.run_simulations_parallel();
+
+        assert_eq!(single_threaded.results, parallel.results);
+        assert_eq!(
+            single_threaded.create_trial_result(),
+            parallel.create_trial_result()
+        );
+    }
+
+    #[test]
+    fn parallel_adaptive_sampling_matches_single_threaded() {
+        // The trial stops between batches, so both must stop after the same batch
+        let adaptive_sampling = Some(AdaptiveSampling {
+            batch_size: 30,
+            target_interval_width: 0.1,
+            elimination_threshold: None,
+        });
+        let mut single_threaded = create_test_trial(42, adaptive_sampling);
+        single_threaded._run_simulations_single_threaded();
+        let mut parallel = create_test_trial(42, adaptive_sampling);
+        parallel.run_simulations_parallel();
+
+        assert!(parallel.results.len() > 30 && parallel.results.len() < 200);
+        assert_eq!(single_threaded.results, parallel.results);
+        assert_eq!(
+            single_threaded.create_trial_result(),
+            parallel.create_trial_result()
+        );
+    }
+}